@@ -32,6 +32,12 @@ impl App {
         }
     }
 
+    /// Show the given logs in the log window
+    pub(crate) fn with_logger(mut self, logger: crate::logging::Logger) -> Self {
+        self.editor.set_logger(logger);
+        self
+    }
+
     /// Run the application
     #[inline(always)]
     pub(crate) fn run(
@@ -45,9 +51,17 @@ impl App {
             if let Err(err) = self.editor.update() {
                 self.handle_error(err);
             }
+            if self.editor.quit_requested() {
+                // Let plugins handle the events dispatched this frame, like pending saves
+                if let Err(err) = self.editor.update() {
+                    self.handle_error(err);
+                }
+                self.quit();
+            }
             terminal.draw(|frame| self.draw(frame))?;
         }
         event!(Level::INFO, "Exiting Application");
+        self.editor.shutdown()?;
         Ok(())
     }
 
@@ -65,14 +79,45 @@ impl App {
 
     /// Handle input for the application
     fn read_events(&mut self) -> Result<()> {
-        if crossterm::event::poll(Duration::from_millis(10))? {
-            if let Event::Key(key) = crossterm::event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    self.handle_key(key);
+        let mut timeout = self.editor.event_poll_rate();
+        while crossterm::event::poll(timeout)? {
+            // Only wait for the first event, then handle the rest that are already queued
+            timeout = Duration::ZERO;
+            let event = crossterm::event::read()?;
+            self.handle_event(event);
+        }
+        Ok(())
+    }
+
+    /// Handle a single terminal event
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                self.handle_key(key);
+            }
+            Event::Key(key) if key.kind == KeyEventKind::Repeat => {
+                // A held key still acts as repeated presses, like before event types were reported
+                if self.error_popup.is_none() {
+                    self.editor.handle_key_repeat(key);
                 }
+                self.handle_key(key);
             }
+            Event::Key(key) if self.error_popup.is_none() => {
+                if key.kind == KeyEventKind::Release {
+                    self.editor.handle_key_release(key);
+                }
+            }
+            Event::Paste(text) if self.error_popup.is_none() => {
+                self.editor.handle_paste(text);
+            }
+            Event::Mouse(mouse) if self.error_popup.is_none() => {
+                self.editor.handle_mouse(mouse);
+            }
+            Event::Resize(width, height) => {
+                self.editor.handle_resize(width, height);
+            }
+            _ => {}
         }
-        Ok(())
     }
 
     /// Handle a single key press
@@ -132,7 +177,7 @@ impl App {
 #[cfg(test)]
 mod tests {
     use color_eyre::eyre::eyre;
-    use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
     use ratatui::backend::TestBackend;
 
     use super::App;
@@ -163,6 +208,21 @@ mod tests {
         assert!(app.exit_application);
     }
 
+    #[test]
+    fn key_repeat_presses() {
+        let mut app = App::new();
+        let key =
+            KeyEvent::new_with_kind(KeyCode::Char('j'), KeyModifiers::NONE, KeyEventKind::Repeat);
+        app.handle_event(Event::Key(key));
+        app.editor.update().unwrap();
+
+        let events = app.editor.events();
+        assert_eq!(events.read::<arcane_core::KeyRepeatEvent>().len(), 1);
+        let pressed = events.read::<arcane_core::KeydownEvent>();
+        assert_eq!(pressed.len(), 1);
+        assert!(pressed.iter().all(|event| event.0 == key));
+    }
+
     #[test]
     fn error_open() {
         let mut app = App::new();
@@ -1,11 +1,60 @@
 //! The editor root
 
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use arcane_core::Result;
 use crossterm::event::KeyEvent;
 use ratatui::layout::Rect;
 use ratatui::Frame;
+use serde::{Deserialize, Serialize};
+
+/// The frame cap used when the settings are missing or invalid
+const DEFAULT_TARGET_FPS: i32 = 60;
+
+/// Settings for the editor loop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct EditorSettings {
+    /// The most frames drawn per second, the loop waits for input in between
+    target_fps: i32,
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        Self {
+            target_fps: DEFAULT_TARGET_FPS,
+        }
+    }
+}
+
+#[typetag::serde]
+impl arcane_settings::PluginSettings for EditorSettings {
+    fn name(&self) -> &'static str {
+        "Editor"
+    }
+
+    fn values(&mut self) -> Box<[arcane_settings::SettingsValueCommon]> {
+        Box::new([arcane_settings::SettingsValueCommon {
+            name: "target_fps",
+            section: None,
+            value: arcane_settings::SettingsValue::Integer {
+                value: &mut self.target_fps,
+                min: 1,
+                max: 240,
+                step: 5,
+            },
+        }])
+    }
+}
+
+/// How long a frame takes at the target fps
+fn frame_duration(target_fps: i32) -> Duration {
+    let fps = u32::try_from(target_fps)
+        .ok()
+        .filter(|fps| *fps > 0)
+        .unwrap_or(DEFAULT_TARGET_FPS.unsigned_abs());
+    Duration::from_secs(1).checked_div(fps).unwrap_or_default()
+}
 
 /// The core editor
 pub(crate) struct Editor {
@@ -24,12 +73,36 @@ impl Editor {
         }
     }
 
+    /// Show the given logs in the log window
+    pub(crate) fn set_logger(&mut self, logger: crate::logging::Logger) {
+        self.state
+            .plugins
+            .insert(crate::logging::LogPlugin::with_logger(logger));
+    }
+
     /// Does inital setup
     pub(crate) fn on_load(&mut self) -> Result<()> {
+        self.state
+            .events
+            .dispatch(arcane_settings::RegisterSettings(Box::new(
+                EditorSettings::default(),
+            )));
         self.state.on_load()?;
         Ok(())
     }
 
+    /// Lets plugins clean up before exiting
+    pub(crate) fn shutdown(&mut self) -> Result<()> {
+        self.state.shutdown()
+    }
+
+    /// How long to wait for input before the next frame is due
+    pub(crate) fn event_poll_rate(&self) -> Duration {
+        let target_fps = arcane_settings::get_settings::<EditorSettings>(&self.state.plugins)
+            .map_or(DEFAULT_TARGET_FPS, |settings| settings.target_fps);
+        frame_duration(target_fps).saturating_sub(self.last_frame.elapsed())
+    }
+
     /// Draw the editor ui
     pub(crate) fn draw(&self, frame: &mut Frame, area: Rect) {
         self.state.draw(frame, area);
@@ -49,10 +122,52 @@ impl Editor {
         Ok(())
     }
 
+    /// Did a plugin request the application to exit
+    pub(crate) fn quit_requested(&self) -> bool {
+        !self
+            .state
+            .events
+            .read::<arcane_core::QuitEvent>()
+            .is_empty()
+    }
+
+    /// The events of the editor
+    #[cfg(test)]
+    pub(crate) const fn events(&self) -> &arcane_core::EventManager {
+        &self.state.events
+    }
+
     /// Handle editor key inputs
     pub(crate) fn handle_key(&mut self, key: KeyEvent) {
         self.state.events.dispatch(arcane_core::KeydownEvent(key));
     }
+
+    /// Handle a key being released
+    pub(crate) fn handle_key_release(&mut self, key: KeyEvent) {
+        self.state.events.dispatch(arcane_core::KeyupEvent(key));
+    }
+
+    /// Handle a held key repeating
+    pub(crate) fn handle_key_repeat(&mut self, key: KeyEvent) {
+        self.state.events.dispatch(arcane_core::KeyRepeatEvent(key));
+    }
+
+    /// Handle text being pasted
+    pub(crate) fn handle_paste(&mut self, text: String) {
+        self.state.events.dispatch(arcane_core::PasteEvent(text));
+    }
+
+    /// Handle mouse inputs
+    pub(crate) fn handle_mouse(&mut self, mouse: crossterm::event::MouseEvent) {
+        self.state.events.dispatch(arcane_core::MouseEvent(mouse));
+    }
+
+    /// Handle the terminal being resized
+    pub(crate) fn handle_resize(&mut self, width: u16, height: u16) {
+        self.state
+            .events
+            .dispatch(arcane_core::ResizeEvent { width, height });
+    }
 }
 
 #[cfg(test)]
@@ -60,6 +175,8 @@ mod tests {
     use std::thread;
     use std::time::Duration;
 
+    use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+
     use super::*;
 
     #[test]
@@ -68,6 +185,92 @@ mod tests {
         editor.on_load().unwrap();
     }
 
+    #[test]
+    fn resize() {
+        let mut editor = Editor::new();
+        editor.handle_resize(120, 40);
+        editor.update().unwrap();
+
+        assert_eq!(
+            editor.state.events.read::<arcane_core::ResizeEvent>(),
+            &[arcane_core::ResizeEvent {
+                width: 120,
+                height: 40
+            }]
+        );
+    }
+
+    #[test]
+    fn key_release() {
+        let mut editor = Editor::new();
+        let key = KeyEvent::new_with_kind(
+            KeyCode::Char('a'),
+            KeyModifiers::NONE,
+            KeyEventKind::Release,
+        );
+        editor.handle_key_release(key);
+        editor.update().unwrap();
+
+        let released = editor.state.events.read::<arcane_core::KeyupEvent>();
+        assert_eq!(released.len(), 1);
+        assert!(released.iter().all(|event| event.0 == key));
+        assert!(editor
+            .state
+            .events
+            .read::<arcane_core::KeydownEvent>()
+            .is_empty());
+    }
+
+    #[test]
+    fn key_repeat() {
+        let mut editor = Editor::new();
+        let key =
+            KeyEvent::new_with_kind(KeyCode::Char('a'), KeyModifiers::NONE, KeyEventKind::Repeat);
+        editor.handle_key_repeat(key);
+        editor.update().unwrap();
+
+        let repeated = editor.state.events.read::<arcane_core::KeyRepeatEvent>();
+        assert_eq!(repeated.len(), 1);
+        assert!(repeated.iter().all(|event| event.0 == key));
+    }
+
+    #[test]
+    fn quit_event() {
+        let mut editor = Editor::new();
+        editor.update().unwrap();
+        assert!(!editor.quit_requested());
+
+        editor.state.events.dispatch(arcane_core::QuitEvent);
+        editor.update().unwrap();
+        assert!(editor.quit_requested());
+    }
+
+    #[test]
+    fn frame_duration() {
+        assert_eq!(super::frame_duration(50), Duration::from_millis(20));
+        assert_eq!(super::frame_duration(0), super::frame_duration(60));
+        assert_eq!(super::frame_duration(-5), super::frame_duration(60));
+    }
+
+    #[test]
+    fn event_poll_rate() {
+        let editor = Editor::new();
+        editor
+            .state
+            .plugins
+            .get_mut::<arcane_settings::SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(EditorSettings { target_fps: 10 }));
+
+        let poll_rate = editor.event_poll_rate();
+        assert!(poll_rate <= Duration::from_millis(100));
+        assert!(poll_rate > Duration::from_millis(50), "{poll_rate:?}");
+
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(editor.event_poll_rate(), Duration::ZERO);
+    }
+
     #[test]
     fn test_update_delta() {
         const DURATION: f32 = 0.5;
@@ -1,10 +1,18 @@
 //! Setups panic hooks for errors and contains logging widget
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
+use ansi_to_tui::IntoText;
 use arcane_core::{event, Level, Result};
+use arcane_keybindings::{BindResult, KeyBind, KeyCode, KeyModifiers, MenuEvent, RegisterKeybind};
+use arcane_windows::{Window, WindowEvent};
 use error_mancer::errors;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState};
+use serde::{Deserialize, Serialize};
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::fmt::MakeWriter;
 use tracing_subscriber::layer::SubscriberExt;
@@ -34,7 +42,15 @@ impl std::io::Write for MemoryLogger {
 
         let result = logger.write(buf);
         if logger.len() > LOGGER_MEMORY_MAX {
-            let drain_end = logger.len().saturating_sub(LOGGER_MEMORY_MAX);
+            // Drain whole lines so the buffer always starts at the start of a line
+            let overflow = logger.len().saturating_sub(LOGGER_MEMORY_MAX);
+            let drain_end = logger
+                .iter()
+                .skip(overflow)
+                .position(|byte| *byte == b'\n')
+                .map_or(logger.len(), |newline| {
+                    overflow.saturating_add(newline).saturating_add(1)
+                });
             logger.drain(0..drain_end);
         }
         result
@@ -111,3 +127,599 @@ pub fn setup() -> Result<Logger> {
 
     Ok(in_app_logs)
 }
+
+/// The levels the log window can filter by, from most to least verbose
+const LEVELS: [Level; 5] = [
+    Level::TRACE,
+    Level::DEBUG,
+    Level::INFO,
+    Level::WARN,
+    Level::ERROR,
+];
+
+/// Remove ansi escape sequences from a line
+fn strip_ansi(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip until the final byte of the escape sequence
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// The index into `LEVELS` of the level a log line starts with
+fn line_level(line: &str) -> Option<usize> {
+    let line = strip_ansi(line);
+    let level = line.split_whitespace().next()?;
+    LEVELS.iter().position(|known| known.as_str() == level)
+}
+
+/// Only keep the log lines at or above the given index into `LEVELS`
+///
+/// Lines without a level, like multi line messages, share the level of the line before them.
+fn filter_logs(logs: &str, min_level: usize) -> String {
+    let mut current_level = 0;
+    logs.lines()
+        .filter(|line| {
+            if let Some(level) = line_level(line) {
+                current_level = level;
+            }
+            current_level >= min_level
+        })
+        .intersperse("\n")
+        .collect()
+}
+
+/// The indexes of the log lines containing the query
+fn match_lines(logs: &str, query: &str) -> Vec<usize> {
+    logs.lines()
+        .enumerate()
+        .filter(|(_, line)| strip_ansi(line).contains(query))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Highlight every occurrence of the query in the line
+fn highlight<'l>(line: Line<'l>, query: &str) -> Line<'l> {
+    if query.is_empty() {
+        return line;
+    }
+
+    let Line {
+        style,
+        alignment,
+        spans,
+    } = line;
+    let mut highlighted = Vec::with_capacity(spans.len());
+    for span in spans {
+        let mut rest = span.content.as_ref();
+        while let Some((before, after)) = rest.split_once(query) {
+            highlighted.push(Span::styled(before.to_owned(), span.style));
+            highlighted.push(
+                Span::styled(query.to_owned(), span.style)
+                    .black()
+                    .on_yellow(),
+            );
+            rest = after;
+        }
+        highlighted.push(Span::styled(rest.to_owned(), span.style));
+    }
+
+    Line {
+        style,
+        alignment,
+        spans: highlighted,
+    }
+}
+
+/// Open the log window
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct OpenLogs;
+
+#[typetag::serde]
+impl BindResult for OpenLogs {}
+
+/// Save the logs of the focused log window to a file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SaveLogs;
+
+#[typetag::serde]
+impl BindResult for SaveLogs {}
+
+/// Write the in app logs to a file
+#[derive(Debug, Clone)]
+pub struct DumpLogs {
+    /// The file to write the logs to
+    pub path: PathBuf,
+}
+
+impl DumpLogs {
+    /// Dump the logs to a timestamped file in the data directory
+    pub fn timestamped() -> Self {
+        Self {
+            path: arcane_core::timestamped_data_file("logs", "txt"),
+        }
+    }
+}
+
+/// Opens windows showing the in app logs
+#[derive(Debug)]
+pub(crate) struct LogPlugin {
+    /// The logs to show
+    logger: Logger,
+}
+
+impl LogPlugin {
+    /// Show the logs written to the given logger
+    pub(crate) const fn with_logger(logger: Logger) -> Self {
+        Self { logger }
+    }
+
+    /// Write the logs to the given path
+    #[errors(std::io::Error)]
+    fn dump_logs(&self, path: &Path) -> Result<()> {
+        let Ok(logs) = self.logger.lock() else {
+            return Err(std::io::Error::other("Mutex Posioned").into());
+        };
+
+        if let Some(parent_dir) = path.parent() {
+            std::fs::create_dir_all(parent_dir)?;
+        }
+        std::fs::write(path, logs.as_slice())?;
+        Ok(())
+    }
+}
+
+arcane_core::register_plugin!(LogPlugin);
+
+impl arcane_core::Plugin for LogPlugin {
+    fn new() -> Self {
+        Self::with_logger(Logger::default())
+    }
+
+    #[errors]
+    fn on_load(&mut self, events: &mut arcane_core::EventManager) -> Result<()> {
+        events.ensure_event::<OpenLogs>();
+        events.dispatch(RegisterKeybind::chord(
+            [
+                KeyBind {
+                    modifiers: KeyModifiers::CONTROL,
+                    key: KeyCode::Char('p'),
+                },
+                KeyBind {
+                    modifiers: KeyModifiers::CONTROL,
+                    key: KeyCode::Char('l'),
+                },
+            ],
+            OpenLogs,
+        ));
+        events.ensure_event::<DumpLogs>();
+        events.ensure_event::<SaveLogs>();
+        events.dispatch(RegisterKeybind::single_key(
+            KeyBind {
+                modifiers: KeyModifiers::CONTROL,
+                key: KeyCode::Char('s'),
+            },
+            SaveLogs,
+        ));
+
+        Ok(())
+    }
+
+    #[errors]
+    fn update(
+        &mut self,
+        events: &mut arcane_core::EventManager,
+        _plugins: &arcane_core::PluginStore,
+    ) -> Result<()> {
+        let (reader, mut writer) = events.split();
+        for _ in reader.read::<OpenLogs>() {
+            writer.dispatch(WindowEvent::CreateWindow(Box::new(LogWindow::new(
+                Arc::clone(&self.logger),
+            ))));
+        }
+
+        for event in reader.read::<DumpLogs>() {
+            match self.dump_logs(&event.path) {
+                Ok(()) => event!(Level::INFO, "Wrote logs to {}", event.path.display()),
+                Err(err) => event!(
+                    Level::WARN,
+                    "Failed to write logs to {}: {err}",
+                    event.path.display()
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn plugin_name(&self) -> &'static str {
+        "Logs"
+    }
+}
+
+/// A window showing the in app logs
+#[derive(Clone)]
+struct LogWindow {
+    /// The logs to show
+    logger: Logger,
+    /// How many lines scrolled up from the newest log
+    scroll: usize,
+    /// Index into `LEVELS` of the least severe level shown
+    min_level: usize,
+    /// The text to search for
+    search: String,
+    /// Is the search being typed
+    searching: bool,
+    /// Index into the matches of the search that was last jumped to
+    current_match: Option<usize>,
+    /// The timer for when to blink the cursor
+    cursor_blink: f32,
+}
+
+impl LogWindow {
+    /// Create a window showing all levels, scrolled to the newest logs
+    const fn new(logger: Logger) -> Self {
+        Self {
+            logger,
+            scroll: 0,
+            min_level: 0,
+            search: String::new(),
+            searching: false,
+            current_match: None,
+            cursor_blink: 0.0,
+        }
+    }
+
+    /// Scroll to the next, or previous, line matching the search
+    fn step_match(&mut self, forward: bool) {
+        let logs = self.visible_logs();
+        let matches = match_lines(&logs, &self.search);
+        let last = matches.len().saturating_sub(1);
+        if matches.is_empty() {
+            self.current_match = None;
+            return;
+        }
+
+        let next = match self.current_match {
+            None if forward => 0,
+            None => last,
+            Some(current) if forward => current
+                .saturating_add(1)
+                .checked_rem(matches.len())
+                .unwrap_or_default(),
+            Some(current) => current.checked_sub(1).unwrap_or(last),
+        };
+        self.current_match = Some(next);
+        if let Some(line) = matches.get(next) {
+            self.scroll = logs.lines().count().saturating_sub(line.saturating_add(1));
+        }
+    }
+
+    /// The logs that pass the level filter
+    fn visible_logs(&self) -> String {
+        let Ok(logs) = self.logger.lock() else {
+            return String::from("Log mutex poisoned");
+        };
+        filter_logs(&String::from_utf8_lossy(&logs), self.min_level)
+    }
+}
+
+impl Window for LogWindow {
+    fn name(&self) -> String {
+        let level = LEVELS.get(self.min_level).map_or("", Level::as_str);
+        format!("Logs ({level}+)")
+    }
+
+    fn wants_keyboard(&self) -> bool {
+        self.searching
+    }
+
+    #[errors]
+    fn update(
+        &mut self,
+        events: &mut arcane_core::EventManager,
+        _plugins: &arcane_core::PluginStore,
+        focused: bool,
+        _id: arcane_windows::WindowID,
+    ) -> Result<()> {
+        if !focused {
+            return Ok(());
+        }
+
+        if self.searching {
+            for event in events.read::<arcane_core::KeydownEvent>() {
+                match event.0.code {
+                    KeyCode::Char(c) => {
+                        self.search.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        self.search.pop();
+                    }
+                    KeyCode::Enter => {
+                        self.searching = false;
+                        if !self.search.is_empty() {
+                            self.step_match(true);
+                        }
+                        break;
+                    }
+                    KeyCode::Esc => {
+                        self.searching = false;
+                        self.search.clear();
+                        self.current_match = None;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+
+            for event in events.read::<arcane_core::DeltaTimeEvent>() {
+                self.cursor_blink += event.0.as_secs_f32();
+                self.cursor_blink %= 1.0;
+            }
+        } else if !events.read::<SaveLogs>().is_empty() {
+            events.dispatch(DumpLogs::timestamped());
+        } else if events
+            .read::<arcane_core::KeydownEvent>()
+            .iter()
+            .any(|event| event.0.code == KeyCode::Char('/'))
+        {
+            self.searching = true;
+            self.search.clear();
+            self.current_match = None;
+        }
+
+        let menu_events = events.read::<MenuEvent>();
+        for event in menu_events {
+            match event {
+                _ if self.searching => {}
+                MenuEvent::Down if !self.search.is_empty() => {
+                    self.step_match(true);
+                }
+                MenuEvent::Up if !self.search.is_empty() => {
+                    self.step_match(false);
+                }
+                MenuEvent::Left => {
+                    self.min_level = self.min_level.saturating_sub(1);
+                }
+                MenuEvent::Right => {
+                    self.min_level = self
+                        .min_level
+                        .saturating_add(1)
+                        .min(LEVELS.len().saturating_sub(1));
+                }
+                MenuEvent::Up => {
+                    self.scroll = self.scroll.saturating_add(1);
+                }
+                MenuEvent::Down => {
+                    self.scroll = self.scroll.saturating_sub(1);
+                }
+                MenuEvent::Top => {
+                    self.scroll = usize::MAX;
+                }
+                MenuEvent::Bottom => {
+                    self.scroll = 0;
+                }
+                _ => {}
+            }
+        }
+        if !menu_events.is_empty() {
+            self.scroll = self.scroll.min(self.visible_logs().lines().count());
+        }
+
+        Ok(())
+    }
+
+    fn draw(
+        &self,
+        frame: &mut ratatui::Frame,
+        area: ratatui::prelude::Rect,
+        _plugins: &arcane_core::PluginStore,
+    ) {
+        let area = if self.searching || !self.search.is_empty() {
+            let [search_area, area] =
+                Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(area);
+            let cursor = if self.searching && self.cursor_blink > 0.5 {
+                Color::White
+            } else {
+                Color::Black
+            };
+            let background = if self.searching {
+                Color::DarkGray
+            } else {
+                Color::Black
+            };
+            let search = Line::from(vec!["/".into(), self.search.clone().into(), "_".fg(cursor)])
+                .bg(background);
+            frame.render_widget(search, search_area);
+            area
+        } else {
+            area
+        };
+
+        let logs = self.visible_logs();
+        let mut text = logs.into_text().unwrap_or_else(|err| {
+            event!(Level::WARN, "Failed to render log ansi: {err}");
+            Text::from(logs.clone())
+        });
+        text.lines = text
+            .lines
+            .into_iter()
+            .map(|line| highlight(line, &self.search))
+            .collect();
+
+        let max_scroll = text.lines.len().saturating_sub(area.height.into());
+        let top = max_scroll.saturating_sub(self.scroll);
+
+        let paragraph = Paragraph::new(text).scroll((u16::try_from(top).unwrap_or(u16::MAX), 0));
+        frame.render_widget(paragraph, area);
+
+        let mut scrollbar_state = ScrollbarState::new(max_scroll).position(top);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            area,
+            &mut scrollbar_state,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    use arcane_core::{EventManager, KeydownEvent, Plugin, PluginStore};
+    use arcane_keybindings::{KeyCode, KeyModifiers, MenuEvent};
+    use arcane_windows::Window;
+    use crossterm::event::KeyEvent;
+
+    use super::{
+        filter_logs,
+        line_level,
+        match_lines,
+        DumpLogs,
+        LogPlugin,
+        LogWindow,
+        MemoryLogger,
+        LOGGER_MEMORY_MAX,
+    };
+
+    const LOGS: &str = "\x1b[34mDEBUG\x1b[0m \x1b[2marcane\x1b[0m: loading\n  continued\n\x1b[31mERROR\x1b[0m \x1b[2marcane\x1b[0m: failed";
+
+    #[test]
+    fn parse_level() {
+        assert_eq!(line_level(LOGS), Some(1));
+        assert_eq!(line_level("  continued"), None);
+    }
+
+    #[test]
+    fn filter_error() {
+        let logs = filter_logs(LOGS, 4);
+        assert!(logs.contains("failed"));
+        assert!(!logs.contains("loading"));
+        assert!(!logs.contains("continued"));
+
+        let logs = filter_logs(LOGS, 1);
+        assert!(logs.contains("loading"));
+        assert!(logs.contains("continued"));
+    }
+
+    #[test]
+    fn cycle_level() {
+        let logger = Arc::new(Mutex::new(LOGS.as_bytes().to_vec()));
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        let mut window = LogWindow::new(logger);
+
+        for _ in 0..10 {
+            events.dispatch(MenuEvent::Right);
+        }
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+
+        assert_eq!(window.name(), "Logs (ERROR+)");
+        assert!(!window.visible_logs().contains("loading"));
+    }
+
+    #[test]
+    fn search_matches() {
+        let logs = "INFO a: found\nINFO b: other\nINFO c: found again\nINFO d: nothing";
+        assert_eq!(match_lines(logs, "found"), vec![0, 2]);
+
+        let logger = Arc::new(Mutex::new(logs.as_bytes().to_vec()));
+        let mut window = LogWindow::new(logger);
+        window.search = String::from("found");
+
+        window.step_match(true);
+        assert_eq!(window.current_match, Some(0));
+        assert_eq!(window.scroll, 3);
+
+        window.step_match(true);
+        assert_eq!(window.current_match, Some(1));
+        assert_eq!(window.scroll, 1);
+
+        window.step_match(true);
+        assert_eq!(window.current_match, Some(0));
+
+        window.step_match(false);
+        assert_eq!(window.current_match, Some(1));
+    }
+
+    #[test]
+    fn search_typed() {
+        let logs = "INFO a: other\nINFO b: found\nINFO c: nothing";
+        let logger = Arc::new(Mutex::new(logs.as_bytes().to_vec()));
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        let mut window = LogWindow::new(logger);
+
+        let mut press = |window: &mut LogWindow, keys: &[KeyCode]| {
+            for key in keys {
+                events.dispatch(KeydownEvent(KeyEvent::new(*key, KeyModifiers::NONE)));
+            }
+            events.swap_buffers();
+            window.update(&mut events, &plugins, true, 0).unwrap();
+        };
+
+        press(&mut window, &[KeyCode::Char('/')]);
+        assert!(window.wants_keyboard());
+        assert!(window.search.is_empty());
+
+        press(&mut window, &[KeyCode::Char('f'), KeyCode::Enter]);
+        assert!(!window.wants_keyboard());
+        assert_eq!(window.search, "f");
+        assert_eq!(window.current_match, Some(0));
+
+        press(&mut window, &[KeyCode::Char('/')]);
+        press(&mut window, &[KeyCode::Char('x'), KeyCode::Esc]);
+        assert!(!window.wants_keyboard());
+        assert!(window.search.is_empty());
+        assert_eq!(window.current_match, None);
+    }
+
+    #[test]
+    fn dump_logs() {
+        let path = std::env::temp_dir().join("arcane_dump_logs.txt");
+        let _ = std::fs::remove_file(&path);
+
+        let logger = Arc::new(Mutex::new(LOGS.as_bytes().to_vec()));
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        let mut plugin = LogPlugin::with_logger(logger);
+        plugin.on_load(&mut events).unwrap();
+
+        events.dispatch(DumpLogs { path: path.clone() });
+        events.swap_buffers();
+        plugin.update(&mut events, &plugins).unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), LOGS);
+    }
+
+    #[test]
+    fn trim_whole_lines() {
+        let logger = Arc::new(Mutex::new(Vec::new()));
+        let mut writer = MemoryLogger {
+            logger: Arc::clone(&logger),
+        };
+
+        let line = format!("{}\n", "x".repeat(999));
+        for _ in 0..LOGGER_MEMORY_MAX / 1000 {
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+        writer.write_all(b"partial line start").unwrap();
+
+        let logs = logger.lock().unwrap();
+        assert!(logs.len() <= LOGGER_MEMORY_MAX);
+        assert!(logs.starts_with(line.as_bytes()));
+        assert!(logs.ends_with(b"partial line start"));
+    }
+}
@@ -46,6 +46,10 @@ mod logging;
 
 use arcane_core::Result;
 use crossterm::event::{
+    DisableBracketedPaste,
+    DisableMouseCapture,
+    EnableBracketedPaste,
+    EnableMouseCapture,
     KeyboardEnhancementFlags,
     PopKeyboardEnhancementFlags,
     PushKeyboardEnhancementFlags,
@@ -55,27 +59,36 @@ use crossterm::execute;
 include!(concat!(env!("OUT_DIR"), "/auto_load.rs"));
 
 fn main() -> Result<()> {
-    let _logs = logging::setup()?;
+    let logs = logging::setup()?;
 
-    let result = start_application();
+    let result = start_application(logs);
     ratatui::restore();
 
     result
 }
 
 /// Create terminal and start the app
-fn start_application() -> Result<()> {
+fn start_application(logs: logging::Logger) -> Result<()> {
     let mut terminal = ratatui::init();
     execute!(
         terminal.backend_mut(),
         PushKeyboardEnhancementFlags(
             KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
                 | KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES
-        )
+                | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+        ),
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )
+    .unwrap();
+    app::App::new().with_logger(logs).run(&mut terminal)?;
+    execute!(
+        terminal.backend_mut(),
+        PopKeyboardEnhancementFlags,
+        DisableMouseCapture,
+        DisableBracketedPaste
     )
     .unwrap();
-    app::App::new().run(&mut terminal)?;
-    execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags).unwrap();
 
     Ok(())
 }
@@ -92,6 +92,13 @@ where
         Some(A::downcast(any).expect("AnyMap corrupted"))
     }
 
+    /// Get a mutable reference to a value from the map
+    #[allow(clippy::expect_used)]
+    pub fn get_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        let any = self.0.get_mut(&TypeId::of::<T>())?;
+        Some(A::downcast_mut(any).expect("AnyMap corrupted"))
+    }
+
     /// Get a mutable reference to a entry based on a type id
     ///
     /// # Safety
@@ -138,6 +145,11 @@ where
         let key = (*value).type_id();
         let _ = self.0.try_insert(key, value);
     }
+
+    /// Only keep the values for which the predicate returns true
+    pub fn retain(&mut self, mut predicate: impl FnMut(&A) -> bool) {
+        self.0.retain(|_, value| predicate(value));
+    }
 }
 
 /// A entry in the map
@@ -258,6 +270,17 @@ mod tests {
         assert_eq!(map.get::<i32>(), Some(&20));
     }
 
+    #[test]
+    fn retain() {
+        let mut map = AnyMap::new();
+        map.insert(10_i32);
+        map.insert(20_i8);
+        map.retain(|value| value.is::<i8>());
+
+        assert_eq!(map.get::<i32>(), None);
+        assert_eq!(map.get::<i8>(), Some(&20));
+    }
+
     #[should_panic(expected = "AnyMap corrupted")]
     #[test]
     fn corruped_map_get() {
@@ -0,0 +1,179 @@
+//! Access to the system clipboard through events
+
+use arcane_core::{event, EventManager, Level, Plugin, PluginStore, Result};
+use error_mancer::errors;
+
+/// Put the text on the system clipboard
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CopyToClipboard(pub String);
+
+/// Read the system clipboard, answered with a `ClipboardContents`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequestPaste;
+
+/// The text on the system clipboard, in response to a `RequestPaste`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClipboardContents(pub String);
+
+/// Something that can store and retrieve clipboard text
+pub trait ClipboardBackend {
+    /// Get the text on the clipboard
+    ///
+    /// # Errors
+    /// If the clipboard is empty, holds something other than text, or can not be reached
+    fn get_text(&mut self) -> std::result::Result<String, arboard::Error>;
+
+    /// Replace the clipboard contents with `text`
+    ///
+    /// # Errors
+    /// If the clipboard can not be reached
+    fn set_text(&mut self, text: String) -> std::result::Result<(), arboard::Error>;
+}
+
+impl ClipboardBackend for arboard::Clipboard {
+    fn get_text(&mut self) -> std::result::Result<String, arboard::Error> {
+        arboard::Clipboard::get_text(self)
+    }
+
+    fn set_text(&mut self, text: String) -> std::result::Result<(), arboard::Error> {
+        arboard::Clipboard::set_text(self, text)
+    }
+}
+
+/// Handles the clipboard events using the system clipboard
+pub struct ClipboardPlugin {
+    /// The clipboard in use, `None` when there is no clipboard, like on a headless system
+    backend: Option<Box<dyn ClipboardBackend>>,
+}
+
+arcane_core::register_plugin!(ClipboardPlugin);
+
+impl ClipboardPlugin {
+    /// Create the plugin with the given clipboard, instead of the system clipboard
+    pub fn with_backend(backend: impl ClipboardBackend + 'static) -> Self {
+        Self {
+            backend: Some(Box::new(backend)),
+        }
+    }
+}
+
+impl Plugin for ClipboardPlugin {
+    fn new() -> Self {
+        Self { backend: None }
+    }
+
+    #[errors]
+    fn on_load(&mut self, events: &mut EventManager) -> Result<()> {
+        events.ensure_event::<ClipboardContents>();
+
+        if self.backend.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.backend = Some(Box::new(clipboard)),
+                Err(err) => {
+                    event!(
+                        Level::WARN,
+                        "No system clipboard, copy and paste are disabled: {err}"
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[errors]
+    fn update(&mut self, events: &mut EventManager, _plugins: &PluginStore) -> Result<()> {
+        let Some(backend) = &mut self.backend else {
+            return Ok(());
+        };
+
+        for event in events.read::<CopyToClipboard>() {
+            event!(Level::DEBUG, "Copying {} bytes", event.0.len());
+            if let Err(err) = backend.set_text(event.0.clone()) {
+                event!(Level::WARN, "Failed to copy to the clipboard: {err}");
+            }
+        }
+
+        let (reader, mut writer) = events.split();
+        if !reader.read::<RequestPaste>().is_empty() {
+            match backend.get_text() {
+                Ok(text) => writer.dispatch(ClipboardContents(text)),
+                Err(err) => event!(Level::WARN, "Failed to read the clipboard: {err}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn plugin_name(&self) -> &'static str {
+        "Clipboard"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use arcane_core::{Persistence, Plugin, StateManager};
+
+    use super::{
+        ClipboardBackend,
+        ClipboardContents,
+        ClipboardPlugin,
+        CopyToClipboard,
+        RequestPaste,
+    };
+
+    /// A clipboard kept in memory
+    #[derive(Clone, Default)]
+    struct MockClipboard(Rc<RefCell<Option<String>>>);
+
+    impl ClipboardBackend for MockClipboard {
+        fn get_text(&mut self) -> Result<String, arboard::Error> {
+            self.0
+                .borrow()
+                .clone()
+                .ok_or(arboard::Error::ContentNotAvailable)
+        }
+
+        fn set_text(&mut self, text: String) -> Result<(), arboard::Error> {
+            *self.0.borrow_mut() = Some(text);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let clipboard = MockClipboard::default();
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states
+            .plugins
+            .insert(ClipboardPlugin::with_backend(clipboard.clone()));
+        states.on_load().unwrap();
+
+        states
+            .dispatch_and_tick(CopyToClipboard(String::from("src/main.rs")))
+            .unwrap();
+        assert_eq!(clipboard.0.borrow().as_deref(), Some("src/main.rs"));
+
+        states.dispatch_and_tick(RequestPaste).unwrap();
+        assert_eq!(
+            states.events.read::<ClipboardContents>(),
+            &[ClipboardContents(String::from("src/main.rs"))]
+        );
+    }
+
+    #[test]
+    fn no_clipboard() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(ClipboardPlugin::new());
+
+        states
+            .events
+            .dispatch(CopyToClipboard(String::from("text")));
+        states.events.dispatch(RequestPaste);
+        states.tick().unwrap();
+        assert!(states.events.read::<ClipboardContents>().is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+//! A shared yes/no dialog for destructive actions
+
+use std::collections::VecDeque;
+
+use arcane_anymap::dyn_clone;
+use arcane_core::{event, EventManager, KeydownEvent, Level, Plugin, PluginStore, Result};
+use arcane_keybindings::{DispatchBindResult, KeyBindEvent, LockKeybindings};
+use error_mancer::errors;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::layout::{Constraint, Flex, Layout};
+use ratatui::style::Stylize;
+use ratatui::symbols::border;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear, Padding, Paragraph, Wrap};
+
+/// The `LockKeybindings` source used while a dialog is shown
+const CONFIRM_LOCK: &str = "confirm";
+
+/// Ask the user to confirm before dispatching `on_confirm`
+///
+/// The queue for the wrapped event has to exsist, see `DispatchBindResult`.
+#[derive(Debug)]
+pub struct ConfirmRequest {
+    /// The question shown to the user
+    pub message: String,
+    /// Dispatched when the user confirms
+    pub on_confirm: KeyBindEvent,
+}
+
+/// Shows `ConfirmRequest`s one at a time as a centered dialog
+#[derive(Debug)]
+pub struct ConfirmPlugin {
+    /// The requests waiting for an answer, the first one is shown
+    requests: VecDeque<ConfirmRequest>,
+}
+
+arcane_core::register_plugin!(ConfirmPlugin);
+
+impl Plugin for ConfirmPlugin {
+    fn new() -> Self {
+        Self {
+            requests: VecDeque::new(),
+        }
+    }
+
+    #[errors]
+    fn on_load(&mut self, events: &mut EventManager) -> Result<()> {
+        events.ensure_event::<ConfirmRequest>();
+        Ok(())
+    }
+
+    #[errors]
+    fn update(&mut self, events: &mut EventManager, _plugins: &PluginStore) -> Result<()> {
+        let (reader, mut writer) = events.split();
+        if !self.requests.is_empty() {
+            for key in reader.read::<KeydownEvent>() {
+                let confirmed = match key.0.code {
+                    KeyCode::Enter => true,
+                    KeyCode::Esc => false,
+                    _ => continue,
+                };
+                let Some(request) = self.requests.pop_front() else {
+                    break;
+                };
+                if confirmed {
+                    event!(Level::DEBUG, "Confirmed {:?}", request.on_confirm);
+                    writer.dispatch_bind_result(&request.on_confirm);
+                } else {
+                    event!(Level::DEBUG, "Cancelled {:?}", request.on_confirm);
+                }
+                if self.requests.is_empty() {
+                    writer.dispatch(LockKeybindings {
+                        source: CONFIRM_LOCK,
+                        locked: false,
+                    });
+                }
+            }
+        }
+
+        let was_empty = self.requests.is_empty();
+        for request in reader.read::<ConfirmRequest>() {
+            self.requests.push_back(ConfirmRequest {
+                message: request.message.clone(),
+                on_confirm: dyn_clone::clone_box(&*request.on_confirm),
+            });
+        }
+        if was_empty && !self.requests.is_empty() {
+            writer.dispatch(LockKeybindings {
+                source: CONFIRM_LOCK,
+                locked: true,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn z_index(&self) -> u32 {
+        u32::MAX
+    }
+
+    fn draw(
+        &self,
+        frame: &mut ratatui::Frame,
+        area: ratatui::prelude::Rect,
+        _plugins: &PluginStore,
+    ) {
+        let Some(request) = self.requests.front() else {
+            return;
+        };
+
+        let block = Block::bordered()
+            .border_set(border::ROUNDED)
+            .title_top("Confirm")
+            .title_bottom(Line::from("enter to confirm, esc to cancel").centered())
+            .yellow()
+            .padding(Padding::horizontal(1));
+        let dialog = Paragraph::new(request.message.as_str())
+            .wrap(Wrap { trim: true })
+            .block(block);
+
+        let [area] = Layout::vertical([Constraint::Length(5)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(50)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(dialog, area);
+    }
+
+    fn plugin_name(&self) -> &'static str {
+        "Confirm"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arcane_core::{EventManager, KeydownEvent, Plugin, PluginStore};
+    use arcane_keybindings::MenuEvent;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{ConfirmPlugin, ConfirmRequest};
+
+    /// Show a request wrapping `MenuEvent::Down`, then answer it with `key`
+    fn answer(key: KeyCode) -> Vec<MenuEvent> {
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        events.ensure_event::<MenuEvent>();
+        let mut confirm = ConfirmPlugin::new();
+        confirm.on_load(&mut events).unwrap();
+
+        events.dispatch(ConfirmRequest {
+            message: String::from("Delete everything?"),
+            on_confirm: Box::new(MenuEvent::Down),
+        });
+        events.swap_buffers();
+        confirm.update(&mut events, &plugins).unwrap();
+        assert_eq!(confirm.requests.len(), 1);
+
+        events.dispatch(KeydownEvent(KeyEvent::new(key, KeyModifiers::NONE)));
+        events.swap_buffers();
+        confirm.update(&mut events, &plugins).unwrap();
+        assert!(confirm.requests.is_empty());
+
+        events.swap_buffers();
+        events.read::<MenuEvent>().to_vec()
+    }
+
+    #[test]
+    fn confirm_dispatches() {
+        assert_eq!(answer(KeyCode::Enter), [MenuEvent::Down]);
+    }
+
+    #[test]
+    fn cancel_does_not_dispatch() {
+        assert_eq!(answer(KeyCode::Esc), []);
+    }
+}
@@ -4,9 +4,11 @@
 
 use std::any::{Any, TypeId};
 use std::cell::{Ref, RefCell, RefMut};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use derive_more::Debug;
-use directories::ProjectDirs;
 pub use inventory;
 pub use tracing::{event, Level};
 
@@ -18,21 +20,245 @@ pub struct DeltaTimeEvent(pub std::time::Duration);
 #[derive(Clone, Copy, Debug)]
 pub struct KeydownEvent(pub crossterm::event::KeyEvent);
 
+/// A key was released
+///
+/// Only reported by terminals supporting the kitty keyboard protocol
+#[derive(Clone, Copy, Debug)]
+pub struct KeyupEvent(pub crossterm::event::KeyEvent);
+
+/// A key is being held down and was repeated
+///
+/// Only reported by terminals supporting the kitty keyboard protocol
+#[derive(Clone, Copy, Debug)]
+pub struct KeyRepeatEvent(pub crossterm::event::KeyEvent);
+
+/// Text was pasted into the terminal
+#[derive(Clone, Debug)]
+pub struct PasteEvent(pub String);
+
+/// The mouse was clicked, moved or scrolled
+#[derive(Clone, Copy, Debug)]
+pub struct MouseEvent(pub crossterm::event::MouseEvent);
+
+/// The terminal was resized
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ResizeEvent {
+    /// The new width in columns
+    pub width: u16,
+    /// The new height in rows
+    pub height: u16,
+}
+
+/// Exit the application
+///
+/// Plugins get one more update after this is read, so they can flush pending saves.
+#[derive(Clone, Copy, Debug)]
+pub struct QuitEvent;
+
+/// Turn a plugin on or off, disabled plugins are neither updated nor drawn.
+///
+/// Plugins that return `false` from `Plugin::can_disable` can not be turned off.
+///
+/// The set of disabled plugins is persisted between runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SetPluginEnabled {
+    /// The `Plugin::plugin_name` of the plugin
+    pub name: String,
+    /// Should the plugin run
+    pub enabled: bool,
+}
+
+/// Tell the user about a problem that does not stop the editor, like an invalid config entry
+#[derive(Clone, Debug)]
+pub struct NotificationEvent {
+    /// How severe the problem is
+    pub level: Level,
+    /// The text shown to the user
+    pub message: String,
+}
+
+/// Fires every time the given interval has passed
+///
+/// Driven by the durations of `DeltaTimeEvent`s.
+#[derive(Clone, Copy, Debug)]
+pub struct Timer {
+    /// How often the timer fires
+    interval: Duration,
+    /// Time passed since the timer last fired
+    elapsed: Duration,
+}
+
+impl Timer {
+    /// Create a timer firing every `interval`
+    pub const fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Change how often the timer fires, keeping the time already passed
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Advance the timer, returns true if the interval has passed
+    ///
+    /// If multiple intervals passed during `delta` the timer only fires once.
+    pub fn tick(&mut self, delta: Duration) -> bool {
+        self.elapsed = self.elapsed.saturating_add(delta);
+        if self.elapsed < self.interval {
+            return false;
+        }
+
+        let remainder = self
+            .elapsed
+            .as_nanos()
+            .checked_rem(self.interval.as_nanos())
+            .unwrap_or_default();
+        self.elapsed = Duration::from_nanos(u64::try_from(remainder).unwrap_or_default());
+        true
+    }
+}
+
+/// Loops smoothly from 0 to 1 over a period
+///
+/// Driven by the durations of `DeltaTimeEvent`s.
+#[derive(Clone, Copy, Debug)]
+pub struct Accumulator {
+    /// How long one loop takes
+    period: Duration,
+    /// Time passed in the current loop
+    elapsed: Duration,
+}
+
+impl Accumulator {
+    /// Create a accumulator looping every `period`
+    pub const fn new(period: Duration) -> Self {
+        Self {
+            period,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Advance the accumulator, wrapping around at the end of the period
+    pub fn tick(&mut self, delta: Duration) {
+        let elapsed = self
+            .elapsed
+            .saturating_add(delta)
+            .as_nanos()
+            .checked_rem(self.period.as_nanos())
+            .unwrap_or_default();
+        self.elapsed = Duration::from_nanos(u64::try_from(elapsed).unwrap_or_default());
+    }
+
+    /// How far into the current loop, in the range `0..1`
+    pub fn progress(&self) -> f32 {
+        if self.period.is_zero() {
+            return 0.0;
+        }
+        self.elapsed.as_secs_f32() / self.period.as_secs_f32()
+    }
+}
+
 pub type Result<T> = color_eyre::Result<T>;
+
+/// The directories arcane stores its files in
+#[derive(Debug, Clone)]
+pub struct ProjectDirs {
+    /// Where config files are stored
+    config_dir: PathBuf,
+    /// Where data files like logs are stored
+    data_dir: PathBuf,
+}
+
+impl ProjectDirs {
+    /// Where config files are stored
+    pub fn config_dir(&self) -> &Path {
+        &self.config_dir
+    }
+
+    /// Where data files like logs are stored
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+}
+
+/// Where a plugin reads and writes the files it persists
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Persistence {
+    /// Use files in this directory
+    Disk(PathBuf),
+    /// Never touch the filesystem, everything only lives in memory
+    Memory,
+}
+
+impl Persistence {
+    /// Persist to the config directory, or only in memory if there is none
+    pub fn config() -> Self {
+        project_dirs().map_or(Self::Memory, |dirs| {
+            Self::Disk(dirs.config_dir().to_path_buf())
+        })
+    }
+
+    /// The directory to persist to, `None` when in memory
+    pub fn dir(&self) -> Option<&Path> {
+        match self {
+            Self::Disk(dir) => Some(dir),
+            Self::Memory => None,
+        }
+    }
+}
+
 ///
 /// Get a struct that can be used to get the project directories to use
 ///
+/// If `ARCANE_CONFIG_DIR` is set both the config and data directory are that path.
+///
 /// # Errors
 /// If missing envs
 pub fn project_dirs() -> Option<ProjectDirs> {
-    let result = ProjectDirs::from("dev", "viv", "arcane");
+    project_dirs_with(|name| std::env::var_os(name))
+}
+
+/// `project_dirs`, looking up environment variables with `env`
+fn project_dirs_with(env: impl Fn(&str) -> Option<std::ffi::OsString>) -> Option<ProjectDirs> {
+    if let Some(dir) = env("ARCANE_CONFIG_DIR") {
+        let dir = PathBuf::from(dir);
+        return Some(ProjectDirs {
+            config_dir: dir.clone(),
+            data_dir: dir,
+        });
+    }
+
+    let result = directories::ProjectDirs::from("dev", "viv", "arcane");
     if result.is_none() {
         event!(
             Level::ERROR,
             "Project Directories not found, config and similar will not be saved."
         );
     }
-    result
+    result.map(|dirs| ProjectDirs {
+        config_dir: dirs.config_dir().to_path_buf(),
+        data_dir: dirs.data_dir().to_path_buf(),
+    })
+}
+
+/// A `{prefix}-{unix timestamp}.{extension}` file in the data directory
+///
+/// Falls back to the working directory if there is no data directory.
+pub fn timestamped_data_file(prefix: &str, extension: &str) -> PathBuf {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let file_name = format!("{prefix}-{timestamp}.{extension}");
+
+    if let Some(dirs) = project_dirs() {
+        dirs.data_dir().join(file_name)
+    } else {
+        PathBuf::from(file_name)
+    }
 }
 
 /// Plugin trait
@@ -48,6 +274,14 @@ pub trait Plugin: Any {
         Ok(())
     }
 
+    /// Called when the editor exits, to flush anything not saved yet
+    ///
+    /// # Errors
+    /// For whatever reason the plugin wants
+    fn on_unload(&mut self, _events: &mut EventManager) -> Result<()> {
+        Ok(())
+    }
+
     /// Read the events you care about from the event manager
     /// and access readonly state thru the plugins store
     ///
@@ -74,6 +308,28 @@ pub trait Plugin: Any {
     fn z_index(&self) -> u32 {
         0
     }
+
+    /// A human readable name for the plugin, also the key `SetPluginEnabled` persists it under
+    ///
+    /// Defaults to the type name of the plugin, which is not stable between builds,
+    /// so registered plugins should return a fixed name
+    fn plugin_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Can the plugin be turned off with `SetPluginEnabled`
+    ///
+    /// Defaults to true, plugins the user needs to turn others back on should return false.
+    fn can_disable(&self) -> bool {
+        true
+    }
+
+    /// Plugins with a higher priority are updated first
+    ///
+    /// Defaults to 0, the order of plugins with equal priorities is undefined.
+    fn update_priority(&self) -> i32 {
+        0
+    }
 }
 
 /// A wrapper trait around vectors (or anything really) that can be used as a queue.
@@ -135,6 +391,20 @@ impl<T: Any> RawEvent for T {
     }
 }
 
+/// Returns the current time, used to decide when deferred events are due
+pub type Clock = Box<dyn Fn() -> Instant>;
+
+/// A buffer holding a queue for each event type
+type EventBuffer = arcane_anymap::AnyMap<dyn DynVec>;
+
+/// A event waiting to be dispatched
+struct DeferredEvent {
+    /// When the event should be dispatched
+    due: Instant,
+    /// Push the event into the given buffer
+    dispatch: Box<dyn FnOnce(&mut EventBuffer)>,
+}
+
 /// Holds a reference to all event queues
 #[derive(Debug)]
 pub struct EventManager {
@@ -144,6 +414,12 @@ pub struct EventManager {
     /// The buffer new events will be written to
     #[debug(skip)]
     write_buffer: arcane_anymap::AnyMap<dyn DynVec>,
+    /// Events waiting for their delay to pass
+    #[debug(skip)]
+    deferred: Vec<DeferredEvent>,
+    /// The clock deferred events are timed with
+    #[debug(skip)]
+    clock: Clock,
 }
 
 /// A seperated out reader for events
@@ -161,6 +437,15 @@ impl EventReader<'_> {
             None => &[],
         }
     }
+
+    /// Same as `has_queue` method on `EventManager`
+    #[must_use]
+    pub fn has_queue<E>(&self) -> bool
+    where
+        E: 'static,
+    {
+        self.0.get::<Vec<E>>().is_some()
+    }
 }
 
 /// A seperated out writer for events
@@ -195,9 +480,16 @@ impl Default for EventManager {
 impl EventManager {
     /// Create a empty queue
     pub fn new() -> Self {
+        Self::with_clock(Box::new(Instant::now))
+    }
+
+    /// Create a empty queue timing deferred events with the given clock
+    pub fn with_clock(clock: Clock) -> Self {
         Self {
             read_buffer: arcane_anymap::AnyMap::new(),
             write_buffer: arcane_anymap::AnyMap::new(),
+            deferred: Vec::new(),
+            clock,
         }
     }
 
@@ -209,6 +501,22 @@ impl EventManager {
         self.write_buffer.entry::<Vec<E>>().or_default().push(event);
     }
 
+    /// Dispatch the event once the delay has passed
+    ///
+    /// The event becomes readable after the first `swap_buffers` following the delay.
+    pub fn dispatch_after<E>(&mut self, delay: Duration, event: E)
+    where
+        E: 'static,
+    {
+        let now = (self.clock)();
+        self.deferred.push(DeferredEvent {
+            due: now.checked_add(delay).unwrap_or(now),
+            dispatch: Box::new(move |buffer| {
+                buffer.entry::<Vec<E>>().or_default().push(event);
+            }),
+        });
+    }
+
     /// Returns clones of all events in the queue
     #[must_use]
     pub fn read<E>(&self) -> &[E]
@@ -221,6 +529,20 @@ impl EventManager {
         }
     }
 
+    /// Take ownership of all events of a type in the read buffer, leaving it empty.
+    ///
+    /// Plugins updating after this one will not see the drained events, so only use this for
+    /// events with a single consumer.
+    pub fn drain<E>(&mut self) -> Vec<E>
+    where
+        E: 'static,
+    {
+        self.read_buffer
+            .get_mut::<Vec<E>>()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
     #[must_use]
     /// Split the event manager into a reader and writer to allow writing events based on read
     /// events easialy
@@ -239,6 +561,18 @@ impl EventManager {
         self.read_buffer.entry::<Vec<E>>().or_default();
     }
 
+    /// Does a queue exsist for the specified type
+    ///
+    /// A queue exsists in either buffer if the event was ensured or dispatched at least once,
+    /// use `ensure_event` to make sure `dispatch_raw` will be able to insert the event.
+    #[must_use]
+    pub fn has_queue<E>(&self) -> bool
+    where
+        E: 'static,
+    {
+        self.write_buffer.get::<Vec<E>>().is_some() || self.read_buffer.get::<Vec<E>>().is_some()
+    }
+
     /// Insert a `dyn Any` into its corresponding queue.
     /// `ensure_event` should have been called beforehand to ensure there is a queue to push into.
     pub fn dispatch_raw(&mut self, event: Box<dyn RawEvent>) {
@@ -250,7 +584,18 @@ impl EventManager {
     }
 
     /// Clear the current read buffer, then swap the buffers;
+    ///
+    /// Deferred events that are due are moved into the new read buffer.
     pub fn swap_buffers(&mut self) {
+        let now = (self.clock)();
+        let (due, waiting) = std::mem::take(&mut self.deferred)
+            .into_iter()
+            .partition::<Vec<_>, _>(|event| event.due <= now);
+        self.deferred = waiting;
+        for event in due {
+            (event.dispatch)(&mut self.write_buffer);
+        }
+
         for queue in self.read_buffer.iter_mut() {
             queue.clear();
         }
@@ -384,6 +729,24 @@ impl PluginStore {
     pub fn iter(&self) -> impl Iterator<Item = Ref<dyn Plugin>> {
         self.plugins.iter().filter_map(|plugin| plugin.borrow())
     }
+
+    /// The names of all plugins that are not currently mutably borrowed, sorted alphabetically
+    pub fn plugin_names(&self) -> Vec<&'static str> {
+        let mut names = self
+            .iter()
+            .map(|plugin| plugin.plugin_name())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    /// Iterate over mutable references to the plugins
+    ///
+    /// Plugins that are already borrowed are skipped instead of panicking.
+    /// While a yielded reference is alive `get` and `get_mut` return `None` for that plugin.
+    pub fn iter_mut(&self) -> impl Iterator<Item = RefMut<dyn Plugin>> {
+        self.plugins.iter().filter_map(|plugin| plugin.borrow_mut())
+    }
 }
 
 /// The plugin manager
@@ -393,6 +756,10 @@ pub struct StateManager {
     pub plugins: PluginStore,
     /// Holds all the events
     pub events: EventManager,
+    /// The names of the plugins that are not updated or drawn
+    disabled: HashSet<String>,
+    /// Where `disabled_plugins` is loaded from and saved to
+    persistence: Persistence,
 }
 
 impl Default for StateManager {
@@ -404,15 +771,102 @@ impl Default for StateManager {
 impl StateManager {
     /// Create a new plugin manager
     pub fn new() -> Self {
+        Self::with_persistence(Persistence::config())
+    }
+
+    /// Create a plugin manager with the given persistence, `new` persists to the config directory
+    pub fn with_persistence(persistence: Persistence) -> Self {
         Self {
             plugins: PluginStore::new(),
             events: EventManager::new(),
+            disabled: HashSet::new(),
+            persistence,
         }
     }
 
-    /// Call the handle event method of every plugin
+    /// Is the plugin with the given `Plugin::plugin_name` updated and drawn
+    pub fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.contains(name)
+    }
+
+    /// Load the disabled plugins from `disabled_plugins`, one plugin name per line
+    fn load_disabled(&mut self) {
+        let Some(dir) = self.persistence.dir() else {
+            return;
+        };
+        let Ok(disabled) = std::fs::read_to_string(dir.join("disabled_plugins")) else {
+            return;
+        };
+        self.disabled = disabled
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty() && self.can_disable(name))
+            .map(String::from)
+            .collect();
+    }
+
+    /// Can the plugin with the given `Plugin::plugin_name` be disabled, see `Plugin::can_disable`
+    fn can_disable(&self, name: &str) -> bool {
+        !self
+            .plugins
+            .iter()
+            .any(|plugin| plugin.plugin_name() == name && !plugin.can_disable())
+    }
+
+    /// Save the disabled plugins to `disabled_plugins`
+    fn save_disabled(&self) -> Result<()> {
+        let Some(dir) = self.persistence.dir() else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(dir)?;
+        let mut disabled = self.disabled.iter().map(String::as_str).collect::<Vec<_>>();
+        disabled.sort_unstable();
+        std::fs::write(dir.join("disabled_plugins"), disabled.join("\n"))?;
+        Ok(())
+    }
+
+    /// Call the handle event method of every enabled plugin, in order of `update_priority`
     pub fn update(&mut self) -> Result<()> {
-        for plugin in self.plugins.plugins.iter() {
+        let mut changed = false;
+        for event in self.events.read::<SetPluginEnabled>() {
+            event!(
+                Level::INFO,
+                "Setting plugin {} enabled: {}",
+                event.name,
+                event.enabled
+            );
+            changed |= if event.enabled {
+                self.disabled.remove(&event.name)
+            } else if !self.can_disable(&event.name) {
+                event!(Level::WARN, "Plugin {} can not be disabled", event.name);
+                false
+            } else {
+                self.disabled.insert(event.name.clone())
+            };
+        }
+        if changed {
+            self.save_disabled()?;
+        }
+
+        let mut plugins = self
+            .plugins
+            .plugins
+            .iter()
+            .filter(|plugin| {
+                plugin
+                    .borrow()
+                    .is_some_and(|plugin| self.is_enabled(plugin.plugin_name()))
+            })
+            .collect::<Vec<_>>();
+        plugins.sort_by_key(|plugin| {
+            std::cmp::Reverse(
+                plugin
+                    .borrow()
+                    .map(|p| p.update_priority())
+                    .unwrap_or_default(),
+            )
+        });
+        for plugin in plugins {
             if let Some(mut plugin) = plugin.borrow_mut() {
                 plugin.update(&mut self.events, &self.plugins)?;
             }
@@ -420,9 +874,36 @@ impl StateManager {
         Ok(())
     }
 
-    /// Call the draw method of every plugin
+    /// Swap the buffers, update, then swap again so the events the plugins dispatched are readable.
+    ///
+    /// Events dispatched before the tick are seen by the plugins exactly once. The events the
+    /// plugins dispatch during the tick are cleared by the next one without reaching any plugin.
+    pub fn tick(&mut self) -> Result<()> {
+        self.events.swap_buffers();
+        self.update()?;
+        self.events.swap_buffers();
+        Ok(())
+    }
+
+    /// Dispatch the event, then `tick`
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn dispatch_and_tick<E: 'static>(&mut self, event: E) -> Result<()> {
+        self.events.dispatch(event);
+        self.tick()
+    }
+
+    /// Call the draw method of every enabled plugin
     pub fn draw(&self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect) {
-        let mut plugins = self.plugins.plugins.iter().collect::<Vec<_>>();
+        let mut plugins = self
+            .plugins
+            .plugins
+            .iter()
+            .filter(|plugin| {
+                plugin
+                    .borrow()
+                    .is_some_and(|plugin| self.is_enabled(plugin.plugin_name()))
+            })
+            .collect::<Vec<_>>();
         plugins.sort_by_key(|plugin| plugin.borrow().map(|p| p.z_index()).unwrap_or_default());
         for plugin in plugins {
             if let Some(plugin) = plugin.borrow() {
@@ -431,9 +912,10 @@ impl StateManager {
         }
     }
 
-    /// Run on load of all plugins
+    /// Run on load of all plugins, including disabled ones so they are ready once enabled
     pub fn on_load(&mut self) -> Result<()> {
         event!(Level::INFO, "Running on loads");
+        self.load_disabled();
         for plugin in self.plugins.plugins.iter() {
             if let Some(mut plugin) = plugin.borrow_mut() {
                 plugin.on_load(&mut self.events)?;
@@ -441,18 +923,112 @@ impl StateManager {
         }
         Ok(())
     }
+
+    /// Run on unload of all plugins, all plugins are unloaded even if some fail
+    ///
+    /// # Errors
+    /// The first error returned by a plugin
+    pub fn shutdown(&mut self) -> Result<()> {
+        event!(Level::INFO, "Running on unloads");
+        let mut result = Ok(());
+        for plugin in self.plugins.plugins.iter() {
+            if let Some(mut plugin) = plugin.borrow_mut() {
+                if let Err(err) = plugin.on_unload(&mut self.events) {
+                    event!(Level::ERROR, "Plugin failed to unload: {err}");
+                    if result.is_ok() {
+                        result = Err(err);
+                    }
+                }
+            }
+        }
+        result
+    }
 }
 
 #[cfg(test)]
 #[allow(clippy::arithmetic_side_effects)]
 mod tests {
+    use std::cell::RefCell;
+
     use color_eyre::eyre::eyre;
 
-    use super::{Plugin, StateManager};
+    use super::{Persistence, Plugin, SetPluginEnabled, StateManager};
     use crate::PluginStore;
 
+    #[test]
+    fn config_dir_override() {
+        let dir = std::env::temp_dir().join("arcane_config_dir_override");
+        let dirs = super::project_dirs_with(|name| {
+            (name == "ARCANE_CONFIG_DIR").then(|| dir.clone().into_os_string())
+        })
+        .unwrap();
+
+        assert_eq!(dirs.config_dir(), dir);
+        assert_eq!(dirs.data_dir(), dir);
+    }
+
+    mod timing {
+        use std::time::Duration;
+
+        use crate::{Accumulator, Timer};
+
+        #[test]
+        fn timer_fires_on_interval() {
+            let mut timer = Timer::new(Duration::from_millis(100));
+            assert!(!timer.tick(Duration::from_millis(60)));
+            assert!(timer.tick(Duration::from_millis(60)));
+            assert!(!timer.tick(Duration::from_millis(60)));
+            assert!(timer.tick(Duration::from_millis(20)));
+        }
+
+        #[test]
+        fn timer_keeps_remainder() {
+            let mut timer = Timer::new(Duration::from_millis(100));
+            assert!(timer.tick(Duration::from_millis(250)));
+            assert!(!timer.tick(Duration::from_millis(40)));
+            assert!(timer.tick(Duration::from_millis(10)));
+        }
+
+        #[test]
+        fn timer_zero_interval() {
+            let mut timer = Timer::new(Duration::ZERO);
+            assert!(timer.tick(Duration::ZERO));
+            assert!(timer.tick(Duration::from_millis(10)));
+        }
+
+        #[test]
+        fn accumulator_progress() {
+            let mut accumulator = Accumulator::new(Duration::from_secs(2));
+            assert_eq!(accumulator.progress(), 0.0);
+            accumulator.tick(Duration::from_millis(500));
+            assert!((accumulator.progress() - 0.25).abs() < f32::EPSILON);
+        }
+
+        #[test]
+        fn accumulator_wraps() {
+            let mut accumulator = Accumulator::new(Duration::from_secs(1));
+            accumulator.tick(Duration::from_millis(750));
+            accumulator.tick(Duration::from_millis(500));
+            assert!((accumulator.progress() - 0.25).abs() < f32::EPSILON);
+
+            accumulator.tick(Duration::from_secs(3));
+            assert!((accumulator.progress() - 0.25).abs() < f32::EPSILON);
+        }
+
+        #[test]
+        fn accumulator_zero_period() {
+            let mut accumulator = Accumulator::new(Duration::ZERO);
+            accumulator.tick(Duration::from_secs(1));
+            assert_eq!(accumulator.progress(), 0.0);
+        }
+    }
+
     mod events {
-        use crate::{EventManager, RawEvent};
+        use std::cell::Cell;
+        use std::rc::Rc;
+        use std::time::{Duration, Instant};
+
+        use crate::{Clock, EventManager, RawEvent};
 
         #[test]
         fn read_empty() {
@@ -483,6 +1059,27 @@ mod tests {
             assert_eq!(events.read::<i32>(), [10, 20]);
         }
 
+        #[test]
+        fn drain() {
+            let mut events = EventManager::new();
+            events.dispatch(10_i32);
+            events.dispatch(20_i32);
+            events.dispatch(30_i8);
+            events.swap_buffers();
+
+            assert_eq!(events.drain::<i32>(), [10, 20]);
+            assert_eq!(events.drain::<i32>(), []);
+            assert_eq!(events.read::<i32>(), []);
+            assert_eq!(events.read::<i8>(), [30]);
+        }
+
+        #[test]
+        fn drain_missing() {
+            let mut events = EventManager::new();
+            assert_eq!(events.drain::<i32>(), []);
+            assert!(!events.has_queue::<i32>());
+        }
+
         #[test]
         fn multiple_types() {
             let mut events = EventManager::new();
@@ -566,6 +1163,92 @@ mod tests {
             events.swap_buffers();
             assert_eq!(events.read::<i32>(), &[10]);
         }
+
+        #[test]
+        fn has_queue_missing() {
+            let mut events = EventManager::new();
+            assert!(!events.has_queue::<i32>());
+
+            let (reader, _writer) = events.split();
+            assert!(!reader.has_queue::<i32>());
+        }
+
+        #[test]
+        fn has_queue_ensured() {
+            let mut events = EventManager::new();
+            events.ensure_event::<i32>();
+            assert!(events.has_queue::<i32>());
+            assert!(!events.has_queue::<i8>());
+
+            let (reader, _writer) = events.split();
+            assert!(reader.has_queue::<i32>());
+        }
+
+        #[test]
+        fn has_queue_dispatched() {
+            let mut events = EventManager::new();
+            events.dispatch(10_i32);
+            assert!(events.has_queue::<i32>());
+
+            events.swap_buffers();
+            assert!(events.has_queue::<i32>());
+            let (reader, _writer) = events.split();
+            assert!(reader.has_queue::<i32>());
+        }
+
+        fn mock_clock() -> (Rc<Cell<Instant>>, Clock) {
+            let now = Rc::new(Cell::new(Instant::now()));
+            let clock_now = Rc::clone(&now);
+            (now, Box::new(move || clock_now.get()))
+        }
+
+        #[test]
+        fn dispatch_after() {
+            let (now, clock) = mock_clock();
+            let mut events = EventManager::with_clock(clock);
+            events.dispatch_after(Duration::from_millis(100), 10_i32);
+
+            events.swap_buffers();
+            assert!(events.read::<i32>().is_empty());
+
+            now.set(now.get() + Duration::from_millis(50));
+            events.swap_buffers();
+            assert!(events.read::<i32>().is_empty());
+
+            now.set(now.get() + Duration::from_millis(50));
+            events.swap_buffers();
+            assert_eq!(events.read::<i32>(), &[10]);
+
+            events.swap_buffers();
+            assert!(events.read::<i32>().is_empty());
+        }
+
+        #[test]
+        fn dispatch_after_keeps_order() {
+            let (now, clock) = mock_clock();
+            let mut events = EventManager::with_clock(clock);
+            events.dispatch_after(Duration::from_millis(200), 20_i32);
+            events.dispatch_after(Duration::from_millis(100), 10_i32);
+
+            now.set(now.get() + Duration::from_millis(100));
+            events.swap_buffers();
+            assert_eq!(events.read::<i32>(), &[10]);
+
+            now.set(now.get() + Duration::from_millis(100));
+            events.swap_buffers();
+            assert_eq!(events.read::<i32>(), &[20]);
+        }
+
+        #[test]
+        fn dispatch_after_zero_delay() {
+            let (_now, clock) = mock_clock();
+            let mut events = EventManager::with_clock(clock);
+            events.dispatch(10_i32);
+            events.dispatch_after(Duration::ZERO, 20_i32);
+            events.swap_buffers();
+
+            assert_eq!(events.read::<i32>(), &[10, 20]);
+        }
     }
 
     #[derive(PartialEq, Eq, Debug, Clone, Copy)]
@@ -604,9 +1287,44 @@ mod tests {
         }
     }
 
+    thread_local! {
+        /// Records which plugins have been unloaded
+        static UNLOADED: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Records its name when unloaded
+    struct UnloadPlugin<const FAIL: bool>;
+
+    impl<const FAIL: bool> Plugin for UnloadPlugin<FAIL> {
+        fn new() -> Self {
+            UnloadPlugin
+        }
+        fn on_unload(&mut self, _events: &mut super::EventManager) -> color_eyre::eyre::Result<()> {
+            if FAIL {
+                UNLOADED.with(|unloaded| unloaded.borrow_mut().push("failing"));
+                Err(eyre!("OH NO!"))
+            } else {
+                UNLOADED.with(|unloaded| unloaded.borrow_mut().push("ok"));
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn shutdown() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state.plugins.insert(UnloadPlugin::<true>);
+        state.plugins.insert(UnloadPlugin::<false>);
+        assert!(state.shutdown().is_err());
+
+        let mut unloaded = UNLOADED.with(|unloaded| unloaded.borrow().clone());
+        unloaded.sort_unstable();
+        assert_eq!(unloaded, ["failing", "ok"]);
+    }
+
     #[test]
     fn update() {
-        let mut state = StateManager::new();
+        let mut state = StateManager::with_persistence(Persistence::Memory);
         state.plugins.insert(TestPlugin(10));
         state.update().unwrap();
 
@@ -616,9 +1334,199 @@ mod tests {
         );
     }
 
+    /// A request counted by `EchoPlugin`
+    struct Ping;
+
+    /// The `EchoPlugin` response, with the number of pings seen so far
+    #[derive(PartialEq, Eq, Debug)]
+    struct Pong(usize);
+
+    /// Answers every `Ping` with a `Pong`
+    struct EchoPlugin(usize);
+
+    impl Plugin for EchoPlugin {
+        fn new() -> Self {
+            EchoPlugin(0)
+        }
+        fn update(
+            &mut self,
+            events: &mut super::EventManager,
+            _plugins: &PluginStore,
+        ) -> color_eyre::eyre::Result<()> {
+            let (reader, mut writer) = events.split();
+            for _ in reader.read::<Ping>() {
+                self.0 += 1;
+                writer.dispatch(Pong(self.0));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn tick_delivers_once() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state.plugins.insert(EchoPlugin(0));
+        state.events.ensure_event::<Ping>();
+
+        state.dispatch_and_tick(Ping).unwrap();
+        assert_eq!(state.events.read::<Pong>(), &[Pong(1)]);
+
+        state.tick().unwrap();
+        assert!(state.events.read::<Pong>().is_empty());
+        assert_eq!(state.plugins.get::<EchoPlugin>().unwrap().0, 1);
+    }
+
+    #[test]
+    fn disabled_plugin_not_updated() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state.plugins.insert(TestPlugin(1));
+        let name = state.plugins.get::<TestPlugin>().unwrap().plugin_name();
+
+        state.events.dispatch(SetPluginEnabled {
+            name: String::from(name),
+            enabled: false,
+        });
+        state.events.swap_buffers();
+        state.update().unwrap();
+        state.update().unwrap();
+        assert!(!state.is_enabled(name));
+        assert_eq!(
+            state.plugins.get::<TestPlugin>().map(|x| *x),
+            Some(TestPlugin(1))
+        );
+
+        state
+            .dispatch_and_tick(SetPluginEnabled {
+                name: String::from(name),
+                enabled: true,
+            })
+            .unwrap();
+        assert_eq!(
+            state.plugins.get::<TestPlugin>().map(|x| *x),
+            Some(TestPlugin(10))
+        );
+    }
+
+    #[test]
+    fn disabled_plugins_persisted() {
+        let dir = std::env::temp_dir().join("arcane_disabled_plugins_persisted");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut state = StateManager::with_persistence(Persistence::Disk(dir.clone()));
+        state.events.dispatch(SetPluginEnabled {
+            name: String::from("some::Plugin"),
+            enabled: false,
+        });
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        let mut state = StateManager::with_persistence(Persistence::Disk(dir.clone()));
+        assert!(state.is_enabled("some::Plugin"));
+        state.on_load().unwrap();
+        assert!(!state.is_enabled("some::Plugin"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Records whether it was loaded
+    struct LoadPlugin(bool);
+
+    impl Plugin for LoadPlugin {
+        fn new() -> Self {
+            LoadPlugin(false)
+        }
+        fn on_load(&mut self, _events: &mut super::EventManager) -> color_eyre::eyre::Result<()> {
+            self.0 = true;
+            Ok(())
+        }
+        fn plugin_name(&self) -> &'static str {
+            "Load"
+        }
+    }
+
+    /// A plugin that refuses to be disabled
+    struct RequiredPlugin;
+
+    impl Plugin for RequiredPlugin {
+        fn new() -> Self {
+            RequiredPlugin
+        }
+        fn plugin_name(&self) -> &'static str {
+            "Required"
+        }
+        fn can_disable(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn required_plugin_not_disabled() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state.plugins.insert(RequiredPlugin);
+        state
+            .dispatch_and_tick(SetPluginEnabled {
+                name: String::from("Required"),
+                enabled: false,
+            })
+            .unwrap();
+        assert!(state.is_enabled("Required"));
+    }
+
+    #[test]
+    fn disabled_plugin_loaded() {
+        let dir = std::env::temp_dir().join("arcane_disabled_plugin_loaded");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("disabled_plugins"), "Load").unwrap();
+
+        let mut state = StateManager::with_persistence(Persistence::Disk(dir.clone()));
+        state.plugins.insert(LoadPlugin(false));
+        state.on_load().unwrap();
+        assert!(!state.is_enabled("Load"));
+        assert!(state.plugins.get::<LoadPlugin>().unwrap().0);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    thread_local! {
+        /// Records the order plugins are updated in
+        static UPDATE_ORDER: RefCell<Vec<i32>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Records its priority when updated
+    struct PriorityPlugin<const P: i32>;
+
+    impl<const P: i32> Plugin for PriorityPlugin<P> {
+        fn new() -> Self {
+            PriorityPlugin
+        }
+        fn update(
+            &mut self,
+            _events: &mut super::EventManager,
+            _plugins: &PluginStore,
+        ) -> color_eyre::eyre::Result<()> {
+            UPDATE_ORDER.with(|order| order.borrow_mut().push(P));
+            Ok(())
+        }
+        fn update_priority(&self) -> i32 {
+            P
+        }
+    }
+
+    #[test]
+    fn update_priority() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state.plugins.insert(PriorityPlugin::<-5>);
+        state.plugins.insert(PriorityPlugin::<10>);
+        state.plugins.insert(PriorityPlugin::<0>);
+        state.update().unwrap();
+
+        assert_eq!(UPDATE_ORDER.with(|order| order.take()), vec![10, 0, -5]);
+    }
+
     #[test]
     fn update_error() {
-        let mut state = StateManager::new();
+        let mut state = StateManager::with_persistence(Persistence::Memory);
         state.plugins.insert(TestPlugin(10));
         state.plugins.insert(ErrPlugin);
 
@@ -651,6 +1559,72 @@ mod tests {
         );
     }
 
+    #[derive(PartialEq, Eq, Debug, Clone, Copy)]
+    struct OtherPlugin(u8);
+
+    impl Plugin for OtherPlugin {
+        fn new() -> Self {
+            OtherPlugin(1)
+        }
+        fn update(
+            &mut self,
+            _events: &mut super::EventManager,
+            _plugins: &PluginStore,
+        ) -> color_eyre::eyre::Result<()> {
+            self.0 += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut plugins = PluginStore::new();
+        plugins.insert(TestPlugin(10));
+        plugins.insert(OtherPlugin(10));
+
+        let mut events = super::EventManager::new();
+        let other_plugins = PluginStore::new();
+        for mut plugin in plugins.iter_mut() {
+            plugin.update(&mut events, &other_plugins).unwrap();
+        }
+
+        assert_eq!(
+            plugins.get::<TestPlugin>().map(|x| *x),
+            Some(TestPlugin(100))
+        );
+        assert_eq!(
+            plugins.get::<OtherPlugin>().map(|x| *x),
+            Some(OtherPlugin(11))
+        );
+    }
+
+    #[test]
+    fn plugin_names() {
+        let mut plugins = PluginStore::new();
+        plugins.insert(TestPlugin(10));
+        plugins.insert(ErrPlugin);
+
+        assert_eq!(
+            plugins.plugin_names(),
+            [
+                "arcane_core::tests::ErrPlugin",
+                "arcane_core::tests::TestPlugin"
+            ]
+        );
+    }
+
+    #[test]
+    fn iter_mut_skips_borrowed() {
+        let mut plugins = PluginStore::new();
+        plugins.insert(TestPlugin(10));
+        plugins.insert(OtherPlugin(10));
+
+        let borrowed = plugins.get::<TestPlugin>();
+        assert_eq!(plugins.iter_mut().count(), 1);
+        drop(borrowed);
+        assert_eq!(plugins.iter_mut().count(), 2);
+    }
+
     #[test]
     fn break_borrow_rules_mut() {
         let mut plugins = PluginStore::new();
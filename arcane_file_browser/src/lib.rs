@@ -1,14 +1,18 @@
 use std::io;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
 
-use arcane_core::{event, Level};
+use arcane_core::{event, Level, NotificationEvent};
 use arcane_keybindings::MenuEvent;
+use arcane_windows::SelectableList;
 use devicons::FileIcon;
 use error_mancer::errors;
 use ignore::gitignore;
+use ratatui::crossterm::event::KeyCode;
 use ratatui::crossterm::style;
-use ratatui::style::Stylize;
-use ratatui::text::{Line, Span, Text};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Scrollbar, ScrollbarOrientation, ScrollbarState};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -17,6 +21,52 @@ struct OpenFileBrowser;
 #[typetag::serde]
 impl arcane_keybindings::BindResult for OpenFileBrowser {}
 
+/// Re-read the open folders of every file browser
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RefreshFileBrowser;
+
+#[typetag::serde]
+impl arcane_keybindings::BindResult for RefreshFileBrowser {}
+
+/// Create a file in the focused folder, or next to the focused file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateFile;
+
+#[typetag::serde]
+impl arcane_keybindings::BindResult for CreateFile {}
+
+/// Create a folder in the focused folder, or next to the focused file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateFolder;
+
+#[typetag::serde]
+impl arcane_keybindings::BindResult for CreateFolder {}
+
+/// Delete the focused file or folder
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Delete;
+
+#[typetag::serde]
+impl arcane_keybindings::BindResult for Delete {}
+
+/// Rename the focused file or folder
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Rename;
+
+#[typetag::serde]
+impl arcane_keybindings::BindResult for Rename {}
+
+/// Open a file browser rooted at the given directory
+#[derive(Debug, Clone)]
+pub struct OpenFileBrowserAt(pub PathBuf);
+
+/// A file was selected in the file browser
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenFile {
+    /// The absolute path of the file
+    pub path: PathBuf,
+}
+
 struct FileBrowserPlugin;
 
 arcane_core::register_plugin!(FileBrowserPlugin);
@@ -36,6 +86,46 @@ impl arcane_core::Plugin for FileBrowserPlugin {
             },
             OpenFileBrowser,
         ));
+        events.ensure_event::<RefreshFileBrowser>();
+        events.dispatch(arcane_keybindings::RegisterKeybind::single_key(
+            arcane_keybindings::KeyBind {
+                modifiers: arcane_keybindings::KeyModifiers::NONE,
+                key: arcane_keybindings::KeyCode::F(5),
+            },
+            RefreshFileBrowser,
+        ));
+        events.ensure_event::<CreateFile>();
+        events.dispatch(arcane_keybindings::RegisterKeybind::single_key(
+            arcane_keybindings::KeyBind {
+                modifiers: arcane_keybindings::KeyModifiers::CONTROL,
+                key: arcane_keybindings::KeyCode::Char('n'),
+            },
+            CreateFile,
+        ));
+        events.ensure_event::<CreateFolder>();
+        events.dispatch(arcane_keybindings::RegisterKeybind::single_key(
+            arcane_keybindings::KeyBind {
+                modifiers: arcane_keybindings::KeyModifiers::SHIFT,
+                key: arcane_keybindings::KeyCode::Char('A'),
+            },
+            CreateFolder,
+        ));
+        events.ensure_event::<Delete>();
+        events.dispatch(arcane_keybindings::RegisterKeybind::single_key(
+            arcane_keybindings::KeyBind {
+                modifiers: arcane_keybindings::KeyModifiers::NONE,
+                key: arcane_keybindings::KeyCode::Delete,
+            },
+            Delete,
+        ));
+        events.ensure_event::<Rename>();
+        events.dispatch(arcane_keybindings::RegisterKeybind::single_key(
+            arcane_keybindings::KeyBind {
+                modifiers: arcane_keybindings::KeyModifiers::NONE,
+                key: arcane_keybindings::KeyCode::F(2),
+            },
+            Rename,
+        ));
         events.dispatch(arcane_settings::RegisterSettings(Box::new(
             FileBrowserSettings::default(),
         )));
@@ -54,21 +144,39 @@ impl arcane_core::Plugin for FileBrowserPlugin {
                 FileBrowserWindow::new()?,
             )));
         }
+        for OpenFileBrowserAt(path) in reader.read::<OpenFileBrowserAt>() {
+            writer.dispatch(arcane_windows::WindowEvent::CreateWindow(Box::new(
+                FileBrowserWindow::with_root(path.clone())?,
+            )));
+        }
 
         Ok(())
     }
+
+    fn plugin_name(&self) -> &'static str {
+        "File Browser"
+    }
 }
 
 const CLOSED_FOLDER_ICON: &str = "󰉋";
 const OPEN_FOLDER_ICON: &str = "";
 const FOLDER_ICON_COLOR: style::Color = style::Color::Blue;
+/// Used for file icons whose color can not be parsed
+const FALLBACK_ICON_COLOR: style::Color = style::Color::Grey;
 
 #[derive(Clone)]
 enum FilesystemItem {
     File {
         name: String,
         abs_path: PathBuf,
-        icon: FileIcon,
+        /// The devicon glyph, looked up once on construction
+        icon: String,
+        /// The parsed devicon color
+        icon_color: style::Color,
+        /// The size in bytes
+        size: u64,
+        /// When the file was last modified, if the platform supports it
+        modified: Option<SystemTime>,
     },
     Folder {
         name: String,
@@ -78,24 +186,86 @@ enum FilesystemItem {
     },
 }
 
-/// # Panics
-/// Panics if the hex color is invalid
-/// While we try to not have panics in the codebase
-/// This function should only be called with values from `devicons::FileIcon::color`
-/// which are valid
-fn parse_color_hex(hex: &str) -> style::Color {
-    let hex = hex.trim().trim_start_matches('#');
-    let r = u8::from_str_radix(hex.get(0..2).unwrap(), 16).unwrap();
-    let g = u8::from_str_radix(hex.get(2..4).unwrap(), 16).unwrap();
-    let b = u8::from_str_radix(hex.get(4..6).unwrap(), 16).unwrap();
-    style::Color::Rgb { r, g, b }
+/// Is `name` a single file name, so joining it onto a folder stays inside that folder
+fn is_plain_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    !name.contains(['/', std::path::MAIN_SEPARATOR])
+        && matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none()
+}
+
+/// Parse a `#rrggbb` color, `None` if it is malformed
+fn parse_color_hex(hex: &str) -> Option<style::Color> {
+    let hex = hex.trim().strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let r = u8::from_str_radix(hex.get(0..2)?, 16).ok()?;
+    let g = u8::from_str_radix(hex.get(2..4)?, 16).ok()?;
+    let b = u8::from_str_radix(hex.get(4..6)?, 16).ok()?;
+    Some(style::Color::Rgb { r, g, b })
+}
+
+/// The color of a devicon, `FALLBACK_ICON_COLOR` if it is malformed
+///
+/// The user is told about malformed colors through `notifications`, once per color.
+fn icon_color(hex: &str, notifications: &mut Vec<NotificationEvent>) -> style::Color {
+    parse_color_hex(hex).unwrap_or_else(|| {
+        let message = format!("Invalid icon color {hex:?}, using fallback");
+        event!(Level::WARN, "{message}");
+        if !notifications
+            .iter()
+            .any(|notification| notification.message == message)
+        {
+            notifications.push(NotificationEvent {
+                level: Level::WARN,
+                message,
+            });
+        }
+        FALLBACK_ICON_COLOR
+    })
+}
+
+/// Format a byte count using binary units, e.g `1.5 KiB`
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in UNITS.iter().skip(1) {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+    format!("{size:.1} {unit}")
+}
+
+/// Format how long ago a timestamp was, e.g `5m ago`
+fn format_age(time: SystemTime) -> String {
+    let seconds = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or_default()
+        .as_secs();
+    match seconds {
+        0..60 => format!("{seconds}s ago"),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
 }
 
 impl FilesystemItem {
-    fn new(path: PathBuf) -> Result<Self, io::Error> {
+    fn new(path: PathBuf, notifications: &mut Vec<NotificationEvent>) -> Result<Self, io::Error> {
         event!(Level::TRACE, "Processing path: {}", path.display());
         if path.is_file() {
             let icon = FileIcon::from(&path);
+            let metadata = path.metadata()?;
             Ok(Self::File {
                 name: path
                     .file_name()
@@ -103,7 +273,10 @@ impl FilesystemItem {
                     .to_string_lossy()
                     .to_string(),
                 abs_path: path.canonicalize().unwrap_or_default(),
-                icon,
+                icon: icon.icon.to_string(),
+                icon_color: icon_color(icon.color, notifications),
+                size: metadata.len(),
+                modified: metadata.modified().ok(),
             })
         } else {
             Ok(Self::Folder {
@@ -121,7 +294,10 @@ impl FilesystemItem {
         }
     }
 
-    fn toggle_folder(&mut self) -> Result<(), io::Error> {
+    fn toggle_folder(
+        &mut self,
+        notifications: &mut Vec<NotificationEvent>,
+    ) -> Result<(), io::Error> {
         let path = self.abs_path().clone();
         if let FilesystemItem::Folder { children, open, .. } = self {
             *open = !*open;
@@ -129,7 +305,7 @@ impl FilesystemItem {
                 event!(Level::DEBUG, "Loading children of {}", path.display());
                 children.extend(
                     path.read_dir()?
-                        .map(|dir| FilesystemItem::new(dir?.path()))
+                        .map(|dir| FilesystemItem::new(dir?.path(), notifications))
                         .collect::<Result<Vec<_>, _>>()?,
                 );
                 children.sort_by(|a, b| a.name().cmp(b.name()));
@@ -139,6 +315,67 @@ impl FilesystemItem {
         Ok(())
     }
 
+    /// Re-read the contents of every open folder, keeping the state of entries that still exist
+    ///
+    /// Entries that can not be read are logged and skipped instead of failing the whole refresh.
+    fn refresh(&mut self, notifications: &mut Vec<NotificationEvent>) -> Result<(), io::Error> {
+        let path = self.abs_path().clone();
+        if let FilesystemItem::Folder {
+            children,
+            open: true,
+            ..
+        } = self
+        {
+            event!(Level::DEBUG, "Refreshing children of {}", path.display());
+            let entries = path
+                .read_dir()?
+                .filter_map(|dir| {
+                    dir.inspect_err(|err| {
+                        event!(
+                            Level::WARN,
+                            "Skipping unreadable entry in {}: {err}",
+                            path.display()
+                        );
+                    })
+                    .ok()
+                })
+                .map(|dir| {
+                    let path = dir.path();
+                    (path.canonicalize().unwrap_or_default(), path)
+                })
+                .collect::<Vec<_>>();
+
+            children.retain(|child| {
+                entries
+                    .iter()
+                    .any(|(abs_path, _)| abs_path == child.abs_path())
+            });
+            for (abs_path, path) in entries {
+                if !children.iter().any(|child| child.abs_path() == &abs_path) {
+                    match FilesystemItem::new(path, notifications) {
+                        Ok(child) => children.push(child),
+                        Err(err) => {
+                            event!(Level::WARN, "Skipping {}: {err}", abs_path.display());
+                        }
+                    }
+                }
+            }
+            children.sort_by(|a, b| a.name().cmp(b.name()));
+
+            for child in children {
+                if let Err(err) = child.refresh(notifications) {
+                    event!(
+                        Level::WARN,
+                        "Failed to refresh {}: {err}",
+                        child.abs_path().display()
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     fn name(&self) -> &str {
         match self {
             FilesystemItem::File { name, .. } => name,
@@ -186,10 +423,9 @@ impl FilesystemItem {
 
     fn icon(&self) -> Span {
         match self {
-            FilesystemItem::File { icon, .. } => {
-                let color = parse_color_hex(icon.color);
-                Span::from(icon.icon.to_string()).fg(color)
-            }
+            FilesystemItem::File {
+                icon, icon_color, ..
+            } => Span::from(icon.as_str()).fg(*icon_color),
             FilesystemItem::Folder { open: false, .. } => {
                 Span::from(CLOSED_FOLDER_ICON).fg(FOLDER_ICON_COLOR)
             }
@@ -270,17 +506,41 @@ impl FilesystemItem {
         }
     }
 
-    fn render(&self, padding: String) -> Line {
+    /// The size and age of files, or the number of loaded children of folders
+    fn metadata(&self) -> String {
+        match self {
+            FilesystemItem::File {
+                size,
+                modified: Some(modified),
+                ..
+            } => format!("{}, {}", format_size(*size), format_age(*modified)),
+            FilesystemItem::File { size, .. } => format_size(*size),
+            FilesystemItem::Folder { children, .. } if children.is_empty() => String::new(),
+            FilesystemItem::Folder { children, .. } if children.len() == 1 => {
+                String::from("1 item")
+            }
+            FilesystemItem::Folder { children, .. } => format!("{} items", children.len()),
+        }
+    }
+
+    fn render(&self, padding: String, settings: &FileBrowserSettings) -> Line {
         let icon = self.icon();
         let name = self.name();
 
-        Line::from(vec![
+        let mut line = Line::from(vec![
             Span::from(padding.to_owned()),
             Span::from("\u{200B}"),
             icon,
             Span::from("\u{200B} "),
             Span::from(name),
-        ])
+        ]);
+        if settings.show_metadata {
+            let metadata = self.metadata();
+            if !metadata.is_empty() {
+                line.push_span(Span::from(format!("  {metadata}")).dark_gray());
+            }
+        }
+        line
     }
 
     fn render_tree(
@@ -291,7 +551,7 @@ impl FilesystemItem {
         mut padding: String,
     ) -> Vec<Line> {
         let mut lines = Vec::with_capacity(self.len(settings, gitignore));
-        lines.push(self.render(padding.clone()));
+        lines.push(self.render(padding.clone(), settings));
         if let FilesystemItem::Folder { open, .. } = self {
             if *open {
                 let children = self.shown_children(settings, gitignore);
@@ -317,28 +577,170 @@ impl FilesystemItem {
     }
 }
 
+/// A filesystem action waiting for the user to enter a name or confirm
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PromptAction {
+    CreateFile,
+    CreateFolder,
+    Delete,
+    Rename,
+}
+
+/// The inline input shown at the bottom of the file browser
+#[derive(Clone, Debug)]
+struct Prompt {
+    action: PromptAction,
+    input: String,
+}
+
 #[derive(Clone)]
 struct FileBrowserWindow {
     root_file: FilesystemItem,
-    focused: usize,
+    list: SelectableList,
     gitignore: gitignore::Gitignore,
+    /// The action being entered, keybindings are locked while this is set
+    prompt: Option<Prompt>,
+    /// Problems found while reading the tree, dispatched on the next update
+    notifications: Vec<NotificationEvent>,
 }
 
 impl FileBrowserWindow {
     fn new() -> Result<Self, io::Error> {
-        let mut gitignore = gitignore::GitignoreBuilder::new(PathBuf::from(".").canonicalize()?);
-        let _ = gitignore.add(".gitignore");
+        Self::with_root(PathBuf::from("."))
+    }
+
+    fn with_root(root: PathBuf) -> Result<Self, io::Error> {
+        let root = root.canonicalize()?;
+        if !root.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotADirectory,
+                format!("{} is not a directory", root.display()),
+            ));
+        }
+
+        let mut gitignore = gitignore::GitignoreBuilder::new(&root);
+        let _ = gitignore.add(root.join(".gitignore"));
         let gitignore = gitignore.build().unwrap();
+        let mut notifications = Vec::new();
         Ok(Self {
-            root_file: FilesystemItem::new(PathBuf::from("."))?,
-            focused: 0,
+            root_file: FilesystemItem::new(root, &mut notifications)?,
+            notifications,
+            list: SelectableList::default(),
             gitignore,
+            prompt: None,
         })
     }
 
+    /// Start prompting for the given action on the focused item
+    fn start_prompt(
+        &mut self,
+        action: PromptAction,
+        settings: &FileBrowserSettings,
+    ) -> Option<Prompt> {
+        if self.list.focused() == 0 && matches!(action, PromptAction::Delete | PromptAction::Rename)
+        {
+            event!(
+                Level::WARN,
+                "Can not {action:?} the root of the file browser"
+            );
+            return None;
+        }
+
+        let item = self
+            .root_file
+            .get(self.list.focused(), settings, &self.gitignore)?;
+        let input = if action == PromptAction::Rename {
+            item.name().to_string()
+        } else {
+            String::new()
+        };
+        Some(Prompt { action, input })
+    }
+
+    /// Perform the prompted action on the focused item, then refresh the tree
+    fn apply_prompt(
+        &mut self,
+        prompt: &Prompt,
+        settings: &FileBrowserSettings,
+    ) -> Result<(), io::Error> {
+        let is_root = self.list.focused() == 0;
+        let Some(item) = self
+            .root_file
+            .get(self.list.focused(), settings, &self.gitignore)
+        else {
+            return Ok(());
+        };
+        let path = item.abs_path().clone();
+        let name = prompt.input.trim();
+        if name.is_empty() && prompt.action != PromptAction::Delete {
+            return Ok(());
+        }
+        if prompt.action != PromptAction::Delete && !is_plain_name(name) {
+            event!(
+                Level::WARN,
+                "{name:?} is not a plain file name, not creating or renaming"
+            );
+            return Ok(());
+        }
+
+        match prompt.action {
+            PromptAction::CreateFile | PromptAction::CreateFolder => {
+                let folder = if item.is_folder() {
+                    if let FilesystemItem::Folder { open: false, .. } = item {
+                        item.toggle_folder(&mut self.notifications)?;
+                    }
+                    path
+                } else {
+                    path.parent().map(PathBuf::from).unwrap_or(path)
+                };
+                let path = folder.join(name);
+                event!(Level::INFO, "Creating {}", path.display());
+                if prompt.action == PromptAction::CreateFile {
+                    std::fs::File::create_new(path)?;
+                } else {
+                    std::fs::create_dir(path)?;
+                }
+            }
+            PromptAction::Delete if !is_root => {
+                event!(Level::INFO, "Deleting {}", path.display());
+                if item.is_folder() {
+                    std::fs::remove_dir_all(path)?;
+                } else {
+                    std::fs::remove_file(path)?;
+                }
+            }
+            PromptAction::Rename if !is_root => {
+                let new_path = path.with_file_name(name);
+                event!(
+                    Level::INFO,
+                    "Renaming {} to {}",
+                    path.display(),
+                    new_path.display()
+                );
+                std::fs::rename(path, new_path)?;
+            }
+            PromptAction::Delete | PromptAction::Rename => {}
+        }
+
+        self.reload_filesystem_state()
+    }
+
     fn reload_filesystem_state(&mut self) -> Result<(), io::Error> {
-        self.root_file = FilesystemItem::new(PathBuf::from("."))?;
-        Ok(())
+        self.root_file.refresh(&mut self.notifications)
+    }
+
+    /// The scrollbar for the shown tree, `None` if it fits in `visible_lines`
+    fn scrollbar_state(
+        &self,
+        settings: &FileBrowserSettings,
+        visible_lines: usize,
+    ) -> Option<ScrollbarState> {
+        let total = self.root_file.len(settings, &self.gitignore);
+        (total > visible_lines).then(|| {
+            ScrollbarState::new(total)
+                .position(self.list.focused())
+                .viewport_content_length(visible_lines)
+        })
     }
 }
 
@@ -352,6 +754,10 @@ impl arcane_windows::Window for FileBrowserWindow {
         ratatui::prelude::Constraint::Percentage(25)
     }
 
+    fn wants_keyboard(&self) -> bool {
+        self.prompt.is_some()
+    }
+
     // #[errors]
     fn update(
         &mut self,
@@ -360,6 +766,14 @@ impl arcane_windows::Window for FileBrowserWindow {
         focused: bool,
         _id: arcane_windows::WindowID,
     ) -> arcane_core::Result<()> {
+        for notification in self.notifications.drain(..) {
+            events.dispatch(notification);
+        }
+
+        if !events.read::<RefreshFileBrowser>().is_empty() {
+            self.reload_filesystem_state()?;
+        }
+
         if !focused {
             return Ok(());
         }
@@ -368,34 +782,85 @@ impl arcane_windows::Window for FileBrowserWindow {
             return Ok(());
         };
 
-        for event in events.read::<MenuEvent>() {
-            match event {
-                MenuEvent::Down => {
-                    self.focused = self.focused.saturating_add(1);
+        if let Some(prompt) = &mut self.prompt {
+            let mut confirmed = None;
+            for event in events.read::<arcane_core::KeydownEvent>() {
+                match event.0.code {
+                    KeyCode::Enter => confirmed = Some(true),
+                    KeyCode::Esc => confirmed = Some(false),
+                    KeyCode::Backspace => {
+                        prompt.input.pop();
+                    }
+                    KeyCode::Char(c) if prompt.action != PromptAction::Delete => {
+                        prompt.input.push(c);
+                    }
+                    _ => {}
+                }
+            }
+            if prompt.action != PromptAction::Delete {
+                for event in events.read::<arcane_core::PasteEvent>() {
+                    prompt
+                        .input
+                        .extend(event.0.chars().filter(|c| !c.is_control()));
                 }
-                MenuEvent::Up => {
-                    self.focused = self.focused.saturating_sub(1);
+            }
+
+            if let Some(confirmed) = confirmed {
+                let prompt = self.prompt.take();
+                if let Some(prompt) = prompt.filter(|_| confirmed) {
+                    self.apply_prompt(&prompt, &settings).inspect_err(|err| {
+                        event!(Level::WARN, "{:?} failed: {err}", prompt.action);
+                    })?;
                 }
-                MenuEvent::Select => {
-                    if let Some(item) = self.root_file.get(self.focused, &settings, &self.gitignore)
-                    {
-                        match item {
-                            FilesystemItem::File { abs_path, .. } => {
-                                // TODO
-                            }
-                            FilesystemItem::Folder { .. } => {
-                                item.toggle_folder()?;
-                            }
-                        }
+            }
+        } else {
+            let actions = [
+                (
+                    !events.read::<CreateFile>().is_empty(),
+                    PromptAction::CreateFile,
+                ),
+                (
+                    !events.read::<CreateFolder>().is_empty(),
+                    PromptAction::CreateFolder,
+                ),
+                (!events.read::<Delete>().is_empty(), PromptAction::Delete),
+                (!events.read::<Rename>().is_empty(), PromptAction::Rename),
+            ];
+            let action = actions
+                .into_iter()
+                .find_map(|(triggered, action)| triggered.then_some(action));
+            if let Some(action) = action {
+                self.prompt = self.start_prompt(action, &settings);
+            }
+        }
+
+        let (reader, mut writer) = events.split();
+        for event in reader.read::<MenuEvent>() {
+            self.list
+                .set_len(self.root_file.len(&settings, &self.gitignore));
+            if self.list.handle(event) || *event != MenuEvent::Select {
+                continue;
+            }
+            if let Some(item) = self
+                .root_file
+                .get(self.list.focused(), &settings, &self.gitignore)
+            {
+                match item {
+                    FilesystemItem::File { abs_path, .. } => {
+                        event!(Level::DEBUG, "Opening {}", abs_path.display());
+                        writer.dispatch(OpenFile {
+                            path: abs_path.clone(),
+                        });
+                    }
+                    FilesystemItem::Folder { .. } => {
+                        item.toggle_folder(&mut self.notifications)?;
                     }
                 }
-                _ => {}
             }
         }
 
-        self.focused = self
-            .focused
-            .clamp(0, self.root_file.len(&settings, &self.gitignore) - 1);
+        self.list
+            .set_len(self.root_file.len(&settings, &self.gitignore));
 
         Ok(())
     }
@@ -410,32 +875,62 @@ impl arcane_windows::Window for FileBrowserWindow {
             return;
         };
 
-        let mut lines = self
+        let area = if let Some(prompt) = &self.prompt {
+            let [area, prompt_area] = ratatui::layout::Layout::vertical([
+                ratatui::layout::Constraint::Fill(1),
+                ratatui::layout::Constraint::Length(1),
+            ])
+            .areas(area);
+            let line = match prompt.action {
+                PromptAction::CreateFile => Line::from(format!("New file: {}_", prompt.input)),
+                PromptAction::CreateFolder => Line::from(format!("New folder: {}_", prompt.input)),
+                PromptAction::Rename => Line::from(format!("Rename to: {}_", prompt.input)),
+                PromptAction::Delete => Line::from("Delete? enter to confirm, esc to cancel").red(),
+            };
+            frame.render_widget(line.on_dark_gray(), prompt_area);
+            area
+        } else {
+            area
+        };
+
+        let lines = self
             .root_file
             .render_tree(0, &settings, &self.gitignore, String::from(""));
-        if let Some(line) = lines.get_mut(self.focused) {
-            *line = line.clone().underlined()
-        }
 
         let visible_lines = area.height as usize;
-        if lines.len() > visible_lines {
-            let target_focused = visible_lines / 2;
-            let max_scroll = lines.len().saturating_sub(visible_lines);
-            let scroll = self.focused.saturating_sub(target_focused);
-            let scroll = scroll.clamp(0, max_scroll);
-            lines = lines.iter().skip(scroll).cloned().collect();
-        }
+        let scrollbar_state = self.scrollbar_state(&settings, visible_lines);
+        let text_area = if scrollbar_state.is_some() {
+            ratatui::prelude::Rect {
+                width: area.width.saturating_sub(1),
+                ..area
+            }
+        } else {
+            area
+        };
+        self.list.render(
+            frame,
+            text_area,
+            lines,
+            Style::new().add_modifier(Modifier::UNDERLINED),
+        );
 
-        let text = Text::from(lines);
-        frame.render_widget(text, area);
+        if let Some(mut scrollbar_state) = scrollbar_state {
+            frame.render_stateful_widget(
+                Scrollbar::new(ScrollbarOrientation::VerticalRight),
+                area,
+                &mut scrollbar_state,
+            );
+        }
     }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 struct FileBrowserSettings {
     show_hidden_files: bool,
     show_hidden_folders: bool,
     show_ignored: bool,
+    show_metadata: bool,
 }
 
 impl Default for FileBrowserSettings {
@@ -444,6 +939,7 @@ impl Default for FileBrowserSettings {
             show_hidden_files: true,
             show_hidden_folders: true,
             show_ignored: false,
+            show_metadata: false,
         }
     }
 }
@@ -458,16 +954,459 @@ impl arcane_settings::PluginSettings for FileBrowserSettings {
         Box::new([
             arcane_settings::SettingsValueCommon {
                 name: "Show hidden files",
+                section: None,
                 value: arcane_settings::SettingsValue::Toogle(&mut self.show_hidden_files),
             },
             arcane_settings::SettingsValueCommon {
                 name: "Show hidden folders",
+                section: None,
                 value: arcane_settings::SettingsValue::Toogle(&mut self.show_hidden_folders),
             },
             arcane_settings::SettingsValueCommon {
                 name: "Show ignored files",
+                section: None,
                 value: arcane_settings::SettingsValue::Toogle(&mut self.show_ignored),
             },
+            arcane_settings::SettingsValueCommon {
+                name: "Show file metadata",
+                section: None,
+                value: arcane_settings::SettingsValue::Toogle(&mut self.show_metadata),
+            },
         ])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use arcane_core::{EventManager, KeydownEvent, Plugin, PluginStore};
+    use arcane_keybindings::MenuEvent;
+    use arcane_settings::SettingsPlugin;
+    use arcane_windows::{Window, WindowEvent};
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::widgets::ScrollbarState;
+
+    use super::{
+        style,
+        CreateFile,
+        CreateFolder,
+        Delete,
+        FileBrowserPlugin,
+        FileBrowserSettings,
+        FileBrowserWindow,
+        FilesystemItem,
+        OpenFile,
+        OpenFileBrowserAt,
+        RefreshFileBrowser,
+        FALLBACK_ICON_COLOR,
+    };
+
+    fn temp_dir(name: &str, files: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in files {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+        dir
+    }
+
+    fn plugins() -> PluginStore {
+        let plugins = PluginStore::new();
+        plugins
+            .get_mut::<SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(FileBrowserSettings::default()));
+        plugins
+    }
+
+    fn send(
+        window: &mut FileBrowserWindow,
+        events: &mut EventManager,
+        plugins: &PluginStore,
+        event: MenuEvent,
+    ) {
+        events.dispatch(event);
+        events.swap_buffers();
+        window.update(events, plugins, true, 0).unwrap();
+    }
+
+    #[test]
+    fn bottom_jumps_to_last() {
+        let dir = temp_dir("arcane_file_browser_bottom", &["a.txt", "b.txt", "c.txt"]);
+        let plugins = plugins();
+        let mut events = EventManager::new();
+        let mut window = FileBrowserWindow::with_root(dir.clone()).unwrap();
+
+        send(&mut window, &mut events, &plugins, MenuEvent::Select);
+        send(&mut window, &mut events, &plugins, MenuEvent::Bottom);
+        assert_eq!(window.list.focused(), 3);
+
+        send(&mut window, &mut events, &plugins, MenuEvent::Top);
+        assert_eq!(window.list.focused(), 0);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn open_file() {
+        let dir = temp_dir("arcane_file_browser_open_file", &["a.txt", "b.txt"]);
+        let plugins = plugins();
+        let mut events = EventManager::new();
+        let mut window = FileBrowserWindow::with_root(dir.clone()).unwrap();
+
+        send(&mut window, &mut events, &plugins, MenuEvent::Select);
+        send(&mut window, &mut events, &plugins, MenuEvent::Down);
+        send(&mut window, &mut events, &plugins, MenuEvent::Down);
+        send(&mut window, &mut events, &plugins, MenuEvent::Select);
+
+        events.swap_buffers();
+        assert_eq!(
+            events.read::<OpenFile>(),
+            &[OpenFile {
+                path: dir.join("b.txt").canonicalize().unwrap()
+            }]
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn refresh_keeps_open_folders() {
+        let dir = temp_dir("arcane_file_browser_refresh", &[]);
+        std::fs::create_dir(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("sub").join("a.txt"), "").unwrap();
+        let plugins = plugins();
+        let mut events = EventManager::new();
+        let mut window = FileBrowserWindow::with_root(dir.clone()).unwrap();
+
+        send(&mut window, &mut events, &plugins, MenuEvent::Select);
+        send(&mut window, &mut events, &plugins, MenuEvent::Down);
+        send(&mut window, &mut events, &plugins, MenuEvent::Select);
+
+        std::fs::write(dir.join("sub").join("b.txt"), "").unwrap();
+        events.dispatch(RefreshFileBrowser);
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+
+        let FilesystemItem::Folder { children, .. } = &window.root_file else {
+            panic!("root is not a folder");
+        };
+        let Some(FilesystemItem::Folder { children, open, .. }) = children.first() else {
+            panic!("sub folder missing");
+        };
+        assert!(open);
+        let names = children
+            .iter()
+            .map(FilesystemItem::name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["a.txt", "b.txt"]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn with_root() {
+        let dir = temp_dir("arcane_file_browser_with_root", &["a.txt"]);
+        std::fs::create_dir(dir.join("nested")).unwrap();
+
+        let window = FileBrowserWindow::with_root(dir.join("nested")).unwrap();
+        assert_eq!(window.name(), "Folder: nested");
+
+        assert!(FileBrowserWindow::with_root(dir.join("a.txt")).is_err());
+        assert!(FileBrowserWindow::with_root(dir.join("missing")).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn open_file_browser_at() {
+        let dir = temp_dir("arcane_file_browser_open_at", &[]);
+        let plugins = plugins();
+        let mut events = EventManager::new();
+        let mut plugin = FileBrowserPlugin;
+
+        events.dispatch(OpenFileBrowserAt(dir.clone()));
+        events.swap_buffers();
+        plugin.update(&mut events, &plugins).unwrap();
+        events.swap_buffers();
+
+        let [WindowEvent::CreateWindow(window)] = events.read::<WindowEvent>() else {
+            panic!("expected a single window to be created");
+        };
+        assert_eq!(window.name(), "Folder: arcane_file_browser_open_at");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn show_metadata() {
+        let dir = temp_dir("arcane_file_browser_metadata", &[]);
+        std::fs::write(dir.join("a.txt"), [0; 2048]).unwrap();
+        let plugins = plugins();
+        let mut events = EventManager::new();
+        let mut window = FileBrowserWindow::with_root(dir.clone()).unwrap();
+
+        send(&mut window, &mut events, &plugins, MenuEvent::Select);
+
+        let settings = FileBrowserSettings {
+            show_metadata: true,
+            ..FileBrowserSettings::default()
+        };
+        let lines = window
+            .root_file
+            .render_tree(0, &settings, &window.gitignore, String::new())
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert!(lines[0].ends_with("1 item"));
+        assert!(lines[1].contains("a.txt  2.0 KiB, "));
+
+        let lines = window.root_file.render_tree(
+            0,
+            &FileBrowserSettings::default(),
+            &window.gitignore,
+            String::new(),
+        );
+        assert!(lines[1].to_string().ends_with("a.txt"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn type_keys(
+        window: &mut FileBrowserWindow,
+        events: &mut EventManager,
+        plugins: &PluginStore,
+        keys: impl IntoIterator<Item = KeyCode>,
+    ) {
+        for key in keys {
+            events.dispatch(KeydownEvent(KeyEvent::new(key, KeyModifiers::NONE)));
+        }
+        events.swap_buffers();
+        window.update(events, plugins, true, 0).unwrap();
+    }
+
+    fn shown_names(window: &FileBrowserWindow) -> Vec<String> {
+        window
+            .root_file
+            .render_tree(
+                0,
+                &FileBrowserSettings::default(),
+                &window.gitignore,
+                String::new(),
+            )
+            .iter()
+            .skip(1)
+            .map(|line| line.to_string().rsplit(' ').next().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn create_and_delete() {
+        let dir = temp_dir("arcane_file_browser_create_delete", &["a.txt"]);
+        let plugins = plugins();
+        let mut events = EventManager::new();
+        let mut window = FileBrowserWindow::with_root(dir.clone()).unwrap();
+        send(&mut window, &mut events, &plugins, MenuEvent::Select);
+
+        events.dispatch(CreateFile);
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        assert!(window.prompt.is_some());
+        type_keys(
+            &mut window,
+            &mut events,
+            &plugins,
+            "b.txt".chars().map(KeyCode::Char).chain([KeyCode::Enter]),
+        );
+        assert!(window.prompt.is_none());
+        assert!(dir.join("b.txt").is_file());
+        assert_eq!(shown_names(&window), ["a.txt", "b.txt"]);
+
+        events.dispatch(CreateFolder);
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        type_keys(
+            &mut window,
+            &mut events,
+            &plugins,
+            "sub".chars().map(KeyCode::Char).chain([KeyCode::Enter]),
+        );
+        assert!(dir.join("sub").is_dir());
+
+        send(&mut window, &mut events, &plugins, MenuEvent::Down);
+        send(&mut window, &mut events, &plugins, MenuEvent::Down);
+        events.dispatch(Delete);
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        type_keys(&mut window, &mut events, &plugins, [KeyCode::Enter]);
+        assert!(!dir.join("b.txt").exists());
+        assert_eq!(shown_names(&window), ["a.txt", "sub"]);
+
+        assert!(!window.wants_keyboard());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn plain_names() {
+        assert!(super::is_plain_name("a.txt"));
+        assert!(super::is_plain_name(".hidden"));
+        assert!(!super::is_plain_name(".."));
+        assert!(!super::is_plain_name("."));
+        assert!(!super::is_plain_name("../a.txt"));
+        assert!(!super::is_plain_name("sub/a.txt"));
+        assert!(!super::is_plain_name("/tmp/a.txt"));
+    }
+
+    #[test]
+    fn create_outside_folder_rejected() {
+        let dir = temp_dir("arcane_file_browser_create_outside", &["a.txt"]);
+        let outside = std::env::temp_dir().join("arcane_file_browser_outside.txt");
+        let _ = std::fs::remove_file(&outside);
+        let plugins = plugins();
+        let mut events = EventManager::new();
+        let mut window = FileBrowserWindow::with_root(dir.clone()).unwrap();
+
+        events.dispatch(CreateFile);
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        type_keys(
+            &mut window,
+            &mut events,
+            &plugins,
+            "../arcane_file_browser_outside.txt"
+                .chars()
+                .map(KeyCode::Char)
+                .chain([KeyCode::Enter]),
+        );
+        assert!(window.prompt.is_none());
+        assert!(!outside.exists());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn cancel_and_root_guard() {
+        let dir = temp_dir("arcane_file_browser_cancel", &["a.txt"]);
+        let plugins = plugins();
+        let mut events = EventManager::new();
+        let mut window = FileBrowserWindow::with_root(dir.clone()).unwrap();
+
+        events.dispatch(Delete);
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        assert!(window.prompt.is_none());
+        assert!(dir.exists());
+
+        send(&mut window, &mut events, &plugins, MenuEvent::Select);
+        send(&mut window, &mut events, &plugins, MenuEvent::Down);
+        events.dispatch(Delete);
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        assert!(window.prompt.is_some());
+        type_keys(&mut window, &mut events, &plugins, [KeyCode::Esc]);
+        assert!(window.prompt.is_none());
+        assert!(dir.join("a.txt").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn scrollbar() {
+        let files = (0..10).map(|i| format!("{i}.txt")).collect::<Vec<_>>();
+        let files = files.iter().map(String::as_str).collect::<Vec<_>>();
+        let dir = temp_dir("arcane_file_browser_scrollbar", &files);
+        let plugins = plugins();
+        let mut events = EventManager::new();
+        let mut window = FileBrowserWindow::with_root(dir.clone()).unwrap();
+        let settings = FileBrowserSettings::default();
+
+        assert_eq!(window.scrollbar_state(&settings, 5), None);
+
+        send(&mut window, &mut events, &plugins, MenuEvent::Select);
+        for _ in 0..7 {
+            send(&mut window, &mut events, &plugins, MenuEvent::Down);
+        }
+        assert_eq!(
+            window.scrollbar_state(&settings, 5),
+            Some(
+                ScrollbarState::new(11)
+                    .position(7)
+                    .viewport_content_length(5)
+            )
+        );
+        assert_eq!(window.scrollbar_state(&settings, 11), None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn icon_color_parsed_once() {
+        let dir = temp_dir("arcane_file_browser_icon_cache", &["a.rs"]);
+
+        let mut item = FilesystemItem::new(dir.join("a.rs"), &mut Vec::new()).unwrap();
+        let FilesystemItem::File { icon_color, .. } = &mut item else {
+            panic!("a.rs is not a file");
+        };
+        assert!(matches!(icon_color, style::Color::Rgb { .. }));
+
+        // Rendering uses the cached color, instead of parsing the devicon again
+        *icon_color = style::Color::Rgb { r: 1, g: 2, b: 3 };
+        let line = item.render(String::new(), &FileBrowserSettings::default());
+        assert_eq!(
+            line.spans[2].style.fg,
+            Some(ratatui::style::Color::Rgb(1, 2, 3))
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn icon_color() {
+        let mut notifications = Vec::new();
+        assert_eq!(
+            super::icon_color("#e34c26", &mut notifications),
+            style::Color::Rgb {
+                r: 0xe3,
+                g: 0x4c,
+                b: 0x26
+            }
+        );
+        assert!(notifications.is_empty());
+
+        assert_eq!(
+            super::icon_color("#e34", &mut notifications),
+            FALLBACK_ICON_COLOR
+        );
+        assert_eq!(
+            super::icon_color("#e34", &mut notifications),
+            FALLBACK_ICON_COLOR
+        );
+        assert_eq!(notifications.len(), 1);
+
+        assert_eq!(
+            super::icon_color("#e34c2g", &mut notifications),
+            FALLBACK_ICON_COLOR
+        );
+        assert_eq!(
+            super::icon_color("e34c26", &mut notifications),
+            FALLBACK_ICON_COLOR
+        );
+        assert_eq!(
+            super::icon_color("#é34c2", &mut notifications),
+            FALLBACK_ICON_COLOR
+        );
+        assert_eq!(notifications.len(), 4);
+    }
+
+    #[test]
+    fn format_size() {
+        assert_eq!(super::format_size(12), "12 B");
+        assert_eq!(super::format_size(1536), "1.5 KiB");
+        assert_eq!(super::format_size(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
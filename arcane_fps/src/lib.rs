@@ -1,10 +1,25 @@
 //! Plugin to show application FPS
 
-use arcane_core::{DeltaTimeEvent, EventManager, Plugin, PluginStore, Result};
-use arcane_keybindings::{BindResult, RegisterKeybind};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use arcane_core::{
+    event,
+    DeltaTimeEvent,
+    EventManager,
+    Level,
+    NotificationEvent,
+    Plugin,
+    PluginStore,
+    Result,
+    Timer,
+};
+use arcane_keybindings::{BindResult, KeyBind, KeyCode, KeyModifiers, RegisterKeybind};
 use error_mancer::errors;
 use ratatui::layout::{Constraint, Layout};
-use ratatui::style::Stylize;
+use ratatui::style::{Color, Stylize};
 use ratatui::text::ToLine;
 use ratatui::widgets::{Block, BorderType, Clear, Sparkline};
 use serde::{Deserialize, Serialize};
@@ -16,19 +31,196 @@ pub struct ToggleFps;
 #[typetag::serde]
 impl BindResult for ToggleFps {}
 
+/// Write the frame times recorded while profiling to a CSV file.
+///
+/// Each row is the time since the editor started and the length of the frame, both in seconds.
+#[derive(Debug, Clone)]
+pub struct DumpFrameTimes {
+    /// The file to write, overwritten if it exists
+    pub path: PathBuf,
+}
+
+impl DumpFrameTimes {
+    /// Dump the frame times to a timestamped file in the data directory
+    pub fn timestamped() -> Self {
+        Self {
+            path: arcane_core::timestamped_data_file("frame-times", "csv"),
+        }
+    }
+}
+
+/// Write the recorded frame times to a timestamped file, see `DumpFrameTimes::timestamped`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+struct SaveFrameTimes;
+
+#[typetag::serde]
+impl BindResult for SaveFrameTimes {}
+
+/// Settings for the fps graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct FpsSettings {
+    /// How many samples to keep, also the width of the graph
+    history_length: i32,
+    /// Seconds between samples
+    sample_interval: f64,
+    /// The fps considered smooth, 0 for no target
+    target_fps: i32,
+    /// Record every frame time for `DumpFrameTimes`
+    profiling: bool,
+    /// How many frame times are kept while profiling
+    profile_length: i32,
+}
+
+impl Default for FpsSettings {
+    fn default() -> Self {
+        Self {
+            history_length: 18,
+            sample_interval: 0.2,
+            target_fps: 0,
+            profiling: false,
+            profile_length: 10_000,
+        }
+    }
+}
+
+#[typetag::serde]
+impl arcane_settings::PluginSettings for FpsSettings {
+    fn name(&self) -> &'static str {
+        "FPS"
+    }
+
+    fn values(&mut self) -> Box<[arcane_settings::SettingsValueCommon]> {
+        let profiling = self.profiling;
+        let mut options = vec![
+            arcane_settings::SettingsValueCommon {
+                name: "history_length",
+                section: None,
+                value: arcane_settings::SettingsValue::Integer {
+                    value: &mut self.history_length,
+                    min: 1,
+                    max: 200,
+                    step: 1,
+                },
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "sample_interval",
+                section: None,
+                value: arcane_settings::SettingsValue::Float {
+                    value: &mut self.sample_interval,
+                    min: 0.05,
+                    max: 2.0,
+                    step: 0.05,
+                },
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "target_fps",
+                section: None,
+                value: arcane_settings::SettingsValue::Integer {
+                    value: &mut self.target_fps,
+                    min: 0,
+                    max: 240,
+                    step: 5,
+                },
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "profiling",
+                section: None,
+                value: arcane_settings::SettingsValue::Toogle(&mut self.profiling),
+            },
+        ];
+        if profiling {
+            options.push(arcane_settings::SettingsValueCommon {
+                name: "profile_length",
+                section: None,
+                value: arcane_settings::SettingsValue::Integer {
+                    value: &mut self.profile_length,
+                    min: 100,
+                    max: 1_000_000,
+                    step: 1000,
+                },
+            });
+        }
+        options.into_boxed_slice()
+    }
+}
+
+/// Summary of the recorded fps history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FpsStats {
+    /// The lowest sample
+    min: u64,
+    /// The highest sample
+    max: u64,
+    /// The mean of all samples
+    average: u64,
+}
+
+impl FpsStats {
+    /// Calculate the stats of a history, `None` if it is empty
+    fn new(history: &[u64]) -> Option<Self> {
+        let min = *history.iter().min()?;
+        let max = *history.iter().max()?;
+        let sum = history.iter().map(|fps| u128::from(*fps)).sum::<u128>();
+        let average = sum
+            .checked_div(history.len().try_into().ok()?)?
+            .try_into()
+            .ok()?;
+        Some(Self { min, max, average })
+    }
+}
+
+/// The color of the fps graph, red below the target, yellow within 20% above it, green otherwise
+fn fps_color(fps: u64, target: Option<u64>) -> Color {
+    let Some(target) = target else {
+        return Color::Yellow;
+    };
+
+    if fps < target {
+        Color::Red
+    } else if u128::from(fps) * 5 < u128::from(target) * 6 {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
+}
+
+/// Write frame times as CSV rows of the time since start and the frame length, in seconds
+fn write_frame_times(
+    frame_times: &VecDeque<(Duration, Duration)>,
+    mut writer: impl Write,
+) -> std::io::Result<()> {
+    writeln!(writer, "timestamp,delta")?;
+    for (timestamp, delta) in frame_times {
+        writeln!(
+            writer,
+            "{:.6},{:.6}",
+            timestamp.as_secs_f64(),
+            delta.as_secs_f64()
+        )?;
+    }
+    writer.flush()
+}
+
 /// Record fps
 #[derive(Debug)]
 pub struct FpsPlugin {
     /// The last recorded fps
     fps: u64,
-    /// The fps history sampled at 0.2s
+    /// The fps history sampled every `FpsSettings::sample_interval`
     fps_history: Vec<u64>,
+    /// The max amount of samples in `fps_history`
+    history_length: usize,
     /// The last SPF
     last_delta: f64,
-    /// How long since the last fps sample
-    last_recording: f64,
+    /// Fires when the next fps sample should be recorded
+    sample_timer: Timer,
     /// Should the fps popup be shown?
     show_fps: bool,
+    /// The total time of all frames so far
+    elapsed: Duration,
+    /// The time since start and length of recent frames, recorded while profiling
+    frame_times: VecDeque<(Duration, Duration)>,
 }
 
 arcane_core::register_plugin!(FpsPlugin);
@@ -39,44 +231,115 @@ impl Plugin for FpsPlugin {
         Self {
             fps: 0,
             fps_history: Vec::new(),
+            history_length: 0,
             last_delta: 0.0,
-            last_recording: 0.0,
+            sample_timer: Timer::new(Duration::ZERO),
             show_fps: false,
+            elapsed: Duration::ZERO,
+            frame_times: VecDeque::new(),
         }
     }
 
     #[errors]
     fn on_load(&mut self, events: &mut EventManager) -> Result<()> {
         events.ensure_event::<ToggleFps>();
+        events.ensure_event::<DumpFrameTimes>();
+        events.ensure_event::<SaveFrameTimes>();
         events.dispatch(RegisterKeybind::chord([], ToggleFps));
+        events.dispatch(RegisterKeybind::chord(
+            [
+                KeyBind {
+                    modifiers: KeyModifiers::CONTROL,
+                    key: KeyCode::Char('p'),
+                },
+                KeyBind {
+                    modifiers: KeyModifiers::CONTROL,
+                    key: KeyCode::Char('f'),
+                },
+            ],
+            SaveFrameTimes,
+        ));
+        events.dispatch(arcane_settings::RegisterSettings(Box::new(
+            FpsSettings::default(),
+        )));
 
         Ok(())
     }
 
     #[errors]
-    fn update(&mut self, events: &mut EventManager, _plugins: &PluginStore) -> Result<()> {
+    fn update(&mut self, events: &mut EventManager, plugins: &PluginStore) -> Result<()> {
         for _ in events.read::<ToggleFps>() {
             self.show_fps = !self.show_fps;
         }
 
+        let settings = arcane_settings::get_settings::<FpsSettings>(plugins)
+            .map(|settings| settings.clone())
+            .unwrap_or_default();
+        self.history_length = usize::try_from(settings.history_length)
+            .unwrap_or_default()
+            .max(1);
+        let overflow = self.fps_history.len().saturating_sub(self.history_length);
+        self.fps_history.drain(..overflow);
+
+        for event in events.read::<DeltaTimeEvent>() {
+            self.elapsed = self.elapsed.saturating_add(event.0);
+            if settings.profiling {
+                self.frame_times.push_back((self.elapsed, event.0));
+            }
+        }
+        let profile_length = usize::try_from(settings.profile_length).unwrap_or_default();
+        let overflow = self.frame_times.len().saturating_sub(profile_length);
+        self.frame_times.drain(..overflow);
+
+        let (reader, mut writer) = events.split();
+        for _ in reader.read::<SaveFrameTimes>() {
+            writer.dispatch(DumpFrameTimes::timestamped());
+        }
+        for event in reader.read::<DumpFrameTimes>() {
+            event!(
+                Level::INFO,
+                "Writing {} frame times to {:?}",
+                self.frame_times.len(),
+                event.path
+            );
+            let written = event
+                .path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::File::create(&event.path))
+                .and_then(|file| {
+                    write_frame_times(&self.frame_times, std::io::BufWriter::new(file))
+                });
+            if let Err(err) = written {
+                event!(Level::ERROR, "Failed to write frame times: {err}");
+                writer.dispatch(NotificationEvent {
+                    level: Level::ERROR,
+                    message: format!("Failed to write frame times to {:?}: {err}", event.path),
+                });
+            }
+        }
+
         if !self.show_fps {
             return Ok(());
         }
 
+        self.sample_timer.set_interval(
+            Duration::try_from_secs_f64(settings.sample_interval).unwrap_or_default(),
+        );
+        let mut record = false;
         for event in events.read::<DeltaTimeEvent>() {
+            record |= self.sample_timer.tick(event.0);
             self.fps = (1_000_000_000_u128)
                 .checked_div(event.0.as_nanos())
                 .unwrap_or_default()
                 .try_into()
                 .unwrap_or(self.fps);
             self.last_delta = event.0.as_secs_f64();
-            self.last_recording += event.0.as_secs_f64();
         }
 
-        if self.last_recording > 0.2 {
-            self.last_recording = 0.0;
+        if record {
             self.fps_history.push(self.fps);
-            if self.fps_history.len() > 18 {
+            if self.fps_history.len() > self.history_length {
                 self.fps_history.remove(0);
             }
         }
@@ -92,20 +355,27 @@ impl Plugin for FpsPlugin {
         &self,
         frame: &mut ratatui::Frame,
         area: ratatui::prelude::Rect,
-        _plugins: &PluginStore,
+        plugins: &PluginStore,
     ) {
         if !self.show_fps {
             return;
         }
 
+        let target = arcane_settings::get_settings::<FpsSettings>(plugins)
+            .and_then(|settings| u64::try_from(settings.target_fps).ok())
+            .filter(|target| *target > 0);
+
         let area =
-            Layout::vertical([Constraint::Fill(1), Constraint::Length(5)]).areas::<2>(area)[1];
-        let area =
-            Layout::horizontal([Constraint::Fill(1), Constraint::Length(20)]).areas::<2>(area)[1];
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(6)]).areas::<2>(area)[1];
+        let width = u16::try_from(self.history_length)
+            .unwrap_or(u16::MAX)
+            .saturating_add(2);
+        let area = Layout::horizontal([Constraint::Fill(1), Constraint::Length(width)])
+            .areas::<2>(area)[1];
 
         let spark_line = Sparkline::default()
             .data(self.fps_history.as_slice())
-            .yellow();
+            .fg(fps_color(self.fps, target));
         let title = format!("{}", self.fps);
         let bottom = format!("{}", self.last_delta);
         let border = Block::bordered()
@@ -114,9 +384,136 @@ impl Plugin for FpsPlugin {
             .title_bottom(bottom.to_line().right_aligned().blue())
             .title_bottom("SPF")
             .border_type(BorderType::Rounded);
-        let spark_line = spark_line.block(border);
+        let inner_area = border.inner(area);
+        let [stats_area, spark_line_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(inner_area);
+        let stats = FpsStats::new(&self.fps_history).map_or_else(String::new, |stats| {
+            format!("⌄{} ~{} ⌃{}", stats.min, stats.average, stats.max)
+        });
 
         frame.render_widget(Clear, area);
-        frame.render_widget(spark_line, area);
+        frame.render_widget(border, area);
+        frame.render_widget(stats.to_line().centered().dark_gray(), stats_area);
+        frame.render_widget(spark_line, spark_line_area);
+    }
+
+    fn plugin_name(&self) -> &'static str {
+        "FPS"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use arcane_core::{DeltaTimeEvent, EventManager, Plugin, PluginStore};
+    use arcane_settings::SettingsPlugin;
+    use ratatui::style::Color;
+
+    use super::{fps_color, DumpFrameTimes, FpsPlugin, FpsSettings, FpsStats, SaveFrameTimes};
+
+    #[test]
+    fn history_length_trims() {
+        let plugins = PluginStore::new();
+        plugins
+            .get_mut::<SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(FpsSettings {
+                history_length: 5,
+                ..FpsSettings::default()
+            }));
+        let mut events = EventManager::new();
+        let mut fps = FpsPlugin::new();
+        fps.fps_history = (0..18).collect();
+
+        fps.update(&mut events, &plugins).unwrap();
+
+        assert_eq!(fps.fps_history, vec![13, 14, 15, 16, 17]);
+    }
+
+    #[test]
+    fn dump_frame_times() {
+        let plugins = PluginStore::new();
+        plugins
+            .get_mut::<SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(FpsSettings {
+                profiling: true,
+                profile_length: 3,
+                ..FpsSettings::default()
+            }));
+        let mut events = EventManager::new();
+        let mut fps = FpsPlugin::new();
+
+        for millis in [10, 20, 30, 40] {
+            events.dispatch(DeltaTimeEvent(Duration::from_millis(millis)));
+            events.swap_buffers();
+            fps.update(&mut events, &plugins).unwrap();
+        }
+
+        let dir = std::env::temp_dir().join("arcane_dump_frame_times");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("frame_times.csv");
+        events.dispatch(DumpFrameTimes { path: path.clone() });
+        events.swap_buffers();
+        fps.update(&mut events, &plugins).unwrap();
+
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("timestamp,delta"));
+        let rows = lines
+            .map(|line| {
+                let (timestamp, delta) = line.split_once(',').unwrap();
+                (
+                    timestamp.parse::<f64>().unwrap(),
+                    delta.parse::<f64>().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![(0.03, 0.02), (0.06, 0.03), (0.1, 0.04)]);
+    }
+
+    #[test]
+    fn save_frame_times_dumps() {
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        let mut fps = FpsPlugin::new();
+
+        events.dispatch(SaveFrameTimes);
+        events.swap_buffers();
+        fps.update(&mut events, &plugins).unwrap();
+        events.swap_buffers();
+
+        let dumps = events.read::<DumpFrameTimes>();
+        assert_eq!(dumps.len(), 1);
+        assert!(dumps.iter().all(|dump| dump
+            .path
+            .extension()
+            .is_some_and(|extension| extension == "csv")));
+    }
+
+    #[test]
+    fn stats() {
+        assert_eq!(
+            FpsStats::new(&[30, 60, 45, 45]),
+            Some(FpsStats {
+                min: 30,
+                max: 60,
+                average: 45,
+            })
+        );
+        assert_eq!(FpsStats::new(&[]), None);
+    }
+
+    #[test]
+    fn color() {
+        assert_eq!(fps_color(10, None), Color::Yellow);
+        assert_eq!(fps_color(50, Some(60)), Color::Red);
+        assert_eq!(fps_color(60, Some(60)), Color::Yellow);
+        assert_eq!(fps_color(71, Some(60)), Color::Yellow);
+        assert_eq!(fps_color(72, Some(60)), Color::Green);
     }
 }
@@ -3,11 +3,15 @@
 #![feature(trait_upcasting)]
 
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::create_dir_all;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
 
 use arcane_anymap::dyn_clone;
-use arcane_core::{event, Level, Result};
+use arcane_core::{event, Level, Persistence, Result};
+use arcane_settings::{read_versioned, versioned_from_value, Versioned};
 pub use crossterm::event::{KeyCode, KeyModifiers, ModifierKeyCode};
 use derive_more::derive::Debug;
 use error_mancer::errors;
@@ -70,6 +74,25 @@ impl Ord for KeyBind {
 }
 
 impl KeyBind {
+    /// Lowercase alphabetic characters and drop the shift modifier from them,
+    /// used for case insensitive matching.
+    #[must_use]
+    pub fn normalized(&self) -> Self {
+        match self.key {
+            KeyCode::Char(c) if c.is_alphabetic() => Self {
+                modifiers: self.modifiers.difference(KeyModifiers::SHIFT),
+                key: KeyCode::Char(c.to_lowercase().next().unwrap_or(c)),
+            },
+            _ => *self,
+        }
+    }
+
+    /// Checks if two keybinds match, treating alphabetic characters as case insensitive when
+    /// shift is the only differing modifier.
+    pub fn matches(&self, other: &Self) -> bool {
+        self == other || self.normalized() == other.normalized()
+    }
+
     /// Get a string version of the keybind
     pub fn render(&self) -> String {
         if self.modifiers.is_empty() {
@@ -78,6 +101,61 @@ impl KeyBind {
             format!("{}+{}", self.modifiers, self.key)
         }
     }
+
+    /// Get a string version of the keybind in emacs notation, like `C-x` or `M-S-<left>`.
+    ///
+    /// Alt and meta are both shown as `M-`, and shift is left out for characters since the
+    /// character itself already shows it.
+    pub fn render_emacs(&self) -> String {
+        let mut modifiers = self.modifiers;
+        let key = match self.key {
+            KeyCode::Char(' ') => String::from("SPC"),
+            KeyCode::Char(c) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                c.to_string()
+            }
+            KeyCode::Enter => String::from("RET"),
+            KeyCode::Tab => String::from("TAB"),
+            KeyCode::BackTab => {
+                modifiers.insert(KeyModifiers::SHIFT);
+                String::from("TAB")
+            }
+            KeyCode::Esc => String::from("ESC"),
+            key => format!("<{}>", key.to_string().to_lowercase().replace(' ', "")),
+        };
+
+        let prefixes = [
+            (KeyModifiers::CONTROL, "C-"),
+            (KeyModifiers::HYPER, "H-"),
+            (KeyModifiers::ALT | KeyModifiers::META, "M-"),
+            (KeyModifiers::SHIFT, "S-"),
+            (KeyModifiers::SUPER, "s-"),
+        ];
+        prefixes
+            .into_iter()
+            .filter(|(modifier, _)| modifiers.intersects(*modifier))
+            .map(|(_, prefix)| prefix)
+            .chain([key.as_str()])
+            .collect()
+    }
+
+    /// Get a string version of the keybind in the given notation
+    pub fn render_as(&self, notation: KeyNotation) -> String {
+        match notation {
+            KeyNotation::Default => self.render(),
+            KeyNotation::Emacs => self.render_emacs(),
+        }
+    }
+}
+
+/// How keybinds are written out when shown to the user
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyNotation {
+    /// The notation used by `KeyBind::render`, like `CONTROL+x`
+    #[default]
+    Default,
+    /// The notation used by `KeyBind::render_emacs`, like `C-x`
+    Emacs,
 }
 
 /// Stores a list of keys
@@ -88,14 +166,148 @@ pub struct Chord {
 }
 
 impl Chord {
+    /// This chord followed by `keys`
+    fn followed_by(&self, keys: &[KeyBind]) -> Self {
+        Self {
+            keys: self.keys.iter().chain(keys).copied().collect(),
+        }
+    }
+
     /// Render a human readable version of the binding
     pub fn render(&self) -> String {
+        self.render_as(KeyNotation::Default)
+    }
+
+    /// Render a human readable version of the binding in the given notation
+    pub fn render_as(&self, notation: KeyNotation) -> String {
         self.keys
             .iter()
-            .map(KeyBind::render)
+            .map(|key| key.render_as(notation))
             .intersperse(String::from(" "))
             .collect::<String>()
     }
+
+    /// Parse a space separated list of keybinds, like `"ctrl+p ctrl+k"`
+    ///
+    /// # Errors
+    /// If any of the keybinds are invalid, or the string is empty
+    pub fn parse(chord: &str) -> std::result::Result<Self, ParseChordError> {
+        let keys = chord
+            .split_whitespace()
+            .map(KeyBind::from_str)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        if keys.is_empty() {
+            return Err(ParseChordError::Empty);
+        }
+        Ok(Self {
+            keys: keys.into_boxed_slice(),
+        })
+    }
+}
+
+/// Error from parsing a keybind string
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseChordError {
+    /// The string contained no keys
+    Empty,
+    /// A modifier name was not recognized
+    UnknownModifier(String),
+    /// A key name was not recognized
+    UnknownKey(String),
+}
+
+impl std::fmt::Display for ParseChordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "No keys in keybind"),
+            Self::UnknownModifier(modifier) => write!(f, "Unknown modifier {modifier:?}"),
+            Self::UnknownKey(key) => write!(f, "Unknown key {key:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseChordError {}
+
+/// Parse the name of a modifier
+fn parse_modifier(modifier: &str) -> std::result::Result<KeyModifiers, ParseChordError> {
+    match modifier.to_lowercase().as_str() {
+        "ctrl" | "control" => Ok(KeyModifiers::CONTROL),
+        "alt" | "option" => Ok(KeyModifiers::ALT),
+        "shift" => Ok(KeyModifiers::SHIFT),
+        "super" | "command" | "windows" => Ok(KeyModifiers::SUPER),
+        "meta" => Ok(KeyModifiers::META),
+        "hyper" => Ok(KeyModifiers::HYPER),
+        _ => Err(ParseChordError::UnknownModifier(String::from(modifier))),
+    }
+}
+
+/// Parse the name of a key, single characters are taken as is.
+fn parse_key(key: &str) -> std::result::Result<KeyCode, ParseChordError> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+
+    let name = key.to_lowercase();
+    let code = match name.as_str() {
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "del" | "delete" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "space" => KeyCode::Char(' '),
+        "menu" => KeyCode::Menu,
+        "pause" => KeyCode::Pause,
+        _ => name
+            .strip_prefix('f')
+            .and_then(|number| number.parse().ok())
+            .map(KeyCode::F)
+            .ok_or_else(|| ParseChordError::UnknownKey(String::from(key)))?,
+    };
+    Ok(code)
+}
+
+impl FromStr for KeyBind {
+    type Err = ParseChordError;
+
+    /// Parse a keybind in the form `modifier+modifier+key`, like `"ctrl+shift+a"`
+    fn from_str(keybind: &str) -> std::result::Result<Self, Self::Err> {
+        let (modifiers, key) = if let Some(modifiers) = keybind.strip_suffix("++") {
+            (Some(modifiers), "+")
+        } else if keybind == "+" {
+            (None, keybind)
+        } else {
+            match keybind.rsplit_once('+') {
+                Some((modifiers, key)) => (Some(modifiers), key),
+                None => (None, keybind),
+            }
+        };
+
+        if key.is_empty() {
+            return Err(ParseChordError::Empty);
+        }
+
+        let modifiers = modifiers
+            .into_iter()
+            .flat_map(|modifiers| modifiers.split('+'))
+            .map(parse_modifier)
+            .try_fold(KeyModifiers::NONE, |all, modifier| Ok(all | modifier?))?;
+
+        Ok(Self {
+            modifiers,
+            key: parse_key(key)?,
+        })
+    }
 }
 
 /// Trait that implements everything a event needs to be dispatched by the keybinding system
@@ -105,19 +317,90 @@ pub trait BindResult: arcane_core::RawEvent + dyn_clone::DynClone + std::fmt::De
 /// How is a keybinding event stored
 pub type KeyBindEvent = Box<dyn BindResult>;
 
+/// Dispatch a `KeyBindEvent` without knowing its concrete type
+///
+/// The queue for the concrete event has to exsist, so the plugin reading it should call
+/// `ensure_event` in its `on_load`, otherwise the event is dropped with a warning.
+pub trait DispatchBindResult {
+    /// Clone the event and dispatch it into its queue
+    fn dispatch_bind_result(&mut self, event: &KeyBindEvent);
+}
+
+impl DispatchBindResult for arcane_core::EventWriter<'_> {
+    fn dispatch_bind_result(&mut self, event: &KeyBindEvent) {
+        let event = dyn_clone::clone_box(&**event);
+        self.dispatch_raw(event as Box<dyn arcane_core::RawEvent>);
+    }
+}
+
+/// An action that does nothing, binding it to a chord shadows every other action on that chord.
+///
+/// Used to explicitly unbind a default keybind, which would otherwise be bound again on the next
+/// start.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoOp;
+
+#[typetag::serde]
+impl BindResult for NoOp {}
+
+/// Is the action `NoOp`
+fn is_no_op(action: &KeyBindEvent) -> bool {
+    (&**action as &dyn std::any::Any).is::<NoOp>()
+}
+
+/// Dispatch the actions of a matched chord, nothing is dispatched if one of them is `NoOp`
+fn emit_actions(writer: &mut arcane_core::EventWriter<'_>, actions: &[KeyBindEvent]) {
+    if actions.iter().any(is_no_op) {
+        event!(Level::DEBUG, "Chord shadowed by NoOp");
+        return;
+    }
+    for action in actions {
+        event!(Level::DEBUG, "Emitting {action:?}");
+        writer.dispatch_bind_result(action);
+    }
+}
+
+/// The keys pressed so far of a chord, and the possible next keys with their action names
+pub type PendingChord = (Vec<KeyBind>, Vec<(KeyBind, String)>);
+
 /// Set the keybind
 pub struct RegisterKeybind {
     /// The actual keybind
     pub bind: Chord,
-    /// The event to dispatch on this event
-    pub event: KeyBindEvent,
+    /// The events to dispatch on this keybind, in order
+    pub events: Vec<KeyBindEvent>,
+    /// Allow the chord to contain bare modifier presses, like double tapping shift
+    ///
+    /// Binds containing them are rejected otherwise, as a modifier press usually just leads into
+    /// another key.
+    pub allow_modifier_binds: bool,
+    /// `bind` is relative to the leader chord, see `KeybindPlugin::leader`
+    pub leader: bool,
+}
+
+/// Identifies a single bound action
+///
+/// Multiple actions can share a debug representation, so this is used to target a specific one
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ActionId {
+    /// The chord the action is bound to
+    pub chord: Chord,
+    /// The index of the action in the actions bound to the chord
+    pub index: usize,
 }
 
 /// Rebind a Keybind
+#[derive(Clone, Debug)]
 pub struct RebindKeybind {
     /// The actual keybind
     pub bind: Chord,
-    /// The event to dispatch on this event
+    /// The action to move to the new keybind
+    pub action: ActionId,
+}
+
+/// Remove a Keybind
+pub struct UnregisterKeybind {
+    /// The event to remove, matched against its debug representation
     pub event: String,
 }
 
@@ -131,7 +414,9 @@ impl RegisterKeybind {
             bind: Chord {
                 keys: Box::new([key]),
             },
-            event: Box::new(event),
+            events: vec![Box::new(event)],
+            allow_modifier_binds: false,
+            leader: false,
         }
     }
 
@@ -144,9 +429,43 @@ impl RegisterKeybind {
             bind: Chord {
                 keys: keys.into_iter().collect::<Vec<_>>().into_boxed_slice(),
             },
-            event: Box::new(event),
+            events: vec![Box::new(event)],
+            allow_modifier_binds: false,
+            leader: false,
+        }
+    }
+
+    /// Shortcut for a keybinding pressed after the leader chord
+    ///
+    /// It is not bound while no leader is set, and moves along when the leader changes.
+    pub fn with_leader<E>(suffix: impl IntoIterator<Item = KeyBind>, event: E) -> Self
+    where
+        E: BindResult + 'static,
+    {
+        Self {
+            leader: true,
+            ..Self::chord(suffix, event)
+        }
+    }
+
+    /// Shortcut for a keybinding that dispatches multiple events
+    pub fn multi(keys: impl IntoIterator<Item = KeyBind>, events: Vec<KeyBindEvent>) -> Self {
+        Self {
+            bind: Chord {
+                keys: keys.into_iter().collect::<Vec<_>>().into_boxed_slice(),
+            },
+            events,
+            allow_modifier_binds: false,
+            leader: false,
         }
     }
+
+    /// Allow the chord to contain bare modifier presses
+    #[must_use]
+    pub fn allow_modifier_binds(mut self) -> Self {
+        self.allow_modifier_binds = true;
+        self
+    }
 }
 
 /// This holds a immutable trie tree, and a mutable incremental search of it
@@ -160,16 +479,29 @@ struct TrieHolder {
 
 impl TrieHolder {
     /// Create the trie tree from the hashmap
-    fn from_raw(raw: &HashMap<Chord, Vec<KeyBindEvent>>) -> Self {
-        let mut builder = TrieBuilder::new();
+    ///
+    /// If `case_insensitive` is set the keys are normalized with `KeyBind::normalized`, and lookups
+    /// should be normalized the same way.
+    fn from_raw(raw: &HashMap<Chord, Vec<KeyBindEvent>>, case_insensitive: bool) -> Self {
+        let mut normalized: HashMap<Vec<KeyBind>, Vec<KeyBindEvent>> = HashMap::new();
         for (chord, event) in raw {
             if chord.keys.is_empty() {
                 continue;
             }
-            builder.push(
-                chord.keys.clone(),
-                event.iter().map(|e| dyn_clone::clone_box(&**e)).collect(),
-            );
+            let keys = if case_insensitive {
+                chord.keys.iter().map(KeyBind::normalized).collect()
+            } else {
+                chord.keys.to_vec()
+            };
+            normalized
+                .entry(keys)
+                .or_default()
+                .extend(event.iter().map(|e| dyn_clone::clone_box(&**e)));
+        }
+
+        let mut builder = TrieBuilder::new();
+        for (keys, events) in normalized {
+            builder.push(keys, events);
         }
 
         TrieHolderBuilder {
@@ -205,10 +537,377 @@ pub struct KeybindPlugin {
     trie: TrieHolder,
     /// Should keybindings be emmitted
     pub enabled: bool,
+    /// The sources currently locking the keybindings
+    locks: HashSet<&'static str>,
+    /// How long the current chord prefix has been waiting for the next key
+    chord_elapsed: Duration,
+    /// Is the trie built for case insensitive matching
+    case_insensitive: bool,
+    /// Where `keybinds.json` is loaded from and saved to
+    persistence: Persistence,
+    /// The leader setting `leader` was parsed from
+    leader_setting: String,
+    /// The chord binds registered with `RegisterKeybind::with_leader` are prefixed with
+    leader: Option<Chord>,
+    /// The binds registered relative to the leader, to move them when it changes
+    leader_binds: Vec<(Box<[KeyBind]>, KeyBindEvent)>,
+    /// How keybinds are shown to the user
+    notation: KeyNotation,
+    /// How many times the trie was rebuilt
+    #[cfg(test)]
+    trie_builds: usize,
+}
+
+/// Settings for keybindings
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct KeybindSettings {
+    /// How many milliseconds a chord prefix is kept before being reset, 0 to never reset
+    chord_timeout: i32,
+    /// Match character keybinds regardless of case
+    case_insensitive: bool,
+    /// The chord leader binds are pressed after, empty for none
+    leader: String,
+    /// Show keybinds in the `Default` or `Emacs` notation
+    notation: String,
+    /// When a bind is also the start of a longer chord, wait for the next key (`Greedy`), or fire
+    /// the shorter bind right away (`Immediate`)
+    chord_resolution: String,
+}
+
+impl Default for KeybindSettings {
+    fn default() -> Self {
+        Self {
+            chord_timeout: 1000,
+            case_insensitive: false,
+            leader: String::new(),
+            notation: String::from("Default"),
+            chord_resolution: String::from("Greedy"),
+        }
+    }
+}
+
+#[typetag::serde]
+impl arcane_settings::PluginSettings for KeybindSettings {
+    fn name(&self) -> &'static str {
+        "Keybindings"
+    }
+
+    fn values(&mut self) -> Box<[arcane_settings::SettingsValueCommon]> {
+        Box::new([
+            arcane_settings::SettingsValueCommon {
+                name: "chord_timeout",
+                section: None,
+                value: arcane_settings::SettingsValue::Integer {
+                    value: &mut self.chord_timeout,
+                    min: 0,
+                    max: 5000,
+                    step: 100,
+                },
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "case_insensitive",
+                section: None,
+                value: arcane_settings::SettingsValue::Toogle(&mut self.case_insensitive),
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "leader",
+                section: None,
+                value: arcane_settings::SettingsValue::Text(&mut self.leader),
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "notation",
+                section: None,
+                value: arcane_settings::SettingsValue::Selection(
+                    &mut self.notation,
+                    &["Default", "Emacs"],
+                ),
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "chord_resolution",
+                section: None,
+                value: arcane_settings::SettingsValue::Selection(
+                    &mut self.chord_resolution,
+                    &["Greedy", "Immediate"],
+                ),
+            },
+        ])
+    }
+
+    fn description(&self, value: &'static str) -> Option<&'static str> {
+        match value {
+            "chord_timeout" => Some("Milliseconds before a partial chord is dropped, 0 to disable"),
+            "case_insensitive" => Some("Match character keys regardless of case or shift"),
+            "leader" => {
+                Some("Chord leader binds are pressed after, like \"space\", empty for none")
+            }
+            "chord_resolution" => Some(
+                "Greedy waits to see if a longer chord is typed, Immediate fires the shortest bind",
+            ),
+            "notation" => Some("Show keys like \"CONTROL+x\" or in emacs style like \"C-x\""),
+            _ => None,
+        }
+    }
 }
 
 arcane_core::register_plugin!(KeybindPlugin);
 
+/// Save the keybinds to `keybinds.json` in the given directory.
+///
+/// The bindings are first written to a temporary file in the same directory, which is then
+/// renamed over the target. So a failed or interrupted write never corrupts the existing file.
+#[errors(std::io::Error, serde_json::Error)]
+fn save_bindings(
+    bindings: &HashMap<Chord, Vec<KeyBindEvent>>,
+    config_dir: &Path,
+) -> std::result::Result<(), _> {
+    create_dir_all(config_dir)?;
+
+    let keybinds_path = config_dir.join("keybinds.json");
+    let temp_path = config_dir.join("keybinds.json.tmp");
+    event!(Level::INFO, "Saving keybinds to {keybinds_path:?}");
+
+    let file = std::fs::File::create(&temp_path)?;
+    let bindings = bindings.iter().collect::<Vec<_>>();
+    if let Err(err) = serde_json::ser::to_writer_pretty(&file, &Versioned::current(bindings)) {
+        std::fs::remove_file(&temp_path)?;
+        return Err(err.into());
+    }
+    file.sync_all()?;
+
+    std::fs::rename(temp_path, keybinds_path)?;
+    Ok(())
+}
+
+/// Upgrade the bindings list from an older format version to the current one
+fn migrate_bindings(file: Versioned<serde_json::Value>) -> serde_json::Value {
+    // Version 1 is the current format, add a step here for each version bump
+    file.data
+}
+
+/// Load the keybinds from `keybinds.json` in the given directory.
+///
+/// Returns `None` if the file does not exist, actions that fail to deserialize are skipped.
+#[errors(serde_json::Error)]
+fn load_bindings(
+    config_dir: &Path,
+) -> std::result::Result<Option<HashMap<Chord, Vec<KeyBindEvent>>>, _> {
+    let Ok(file) = std::fs::File::open(config_dir.join("keybinds.json")) else {
+        return Ok(None);
+    };
+
+    let file = read_versioned(file)?;
+    Ok(Some(parse_bindings(migrate_bindings(file))?))
+}
+
+/// Parse a list of chords and the actions bound to them, as saved in `keybinds.json`.
+///
+/// Actions that fail to deserialize are skipped.
+fn parse_bindings(
+    data: serde_json::Value,
+) -> serde_json::Result<HashMap<Chord, Vec<KeyBindEvent>>> {
+    let data: Vec<(Chord, serde_json::Value)> = serde_json::from_value(data)?;
+    event!(Level::DEBUG, "loading {} keybinds", data.len());
+    let data = data.into_iter().filter_map(|(chord, action)| {
+        let action = serde_json::from_value(action).ok();
+        if action.is_none() {
+            event!(Level::ERROR, "Invalid action in keybindings file!");
+        }
+        action.map(|action| (chord, action))
+    });
+    Ok(data.collect())
+}
+
+impl KeybindPlugin {
+    /// Are keybindings currently emitted, `false` while locked by `LockKeybindings`
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// How keybinds should be shown to the user
+    pub fn notation(&self) -> KeyNotation {
+        self.notation
+    }
+
+    /// The chord leader binds are pressed after, `None` while leader binds are unbound
+    pub fn leader(&self) -> Option<&Chord> {
+        self.leader.as_ref()
+    }
+
+    /// Move the leader binds from the current leader to `leader`.
+    ///
+    /// Binds the user moved away from the old leader are left where they are.
+    fn change_leader(&mut self, leader: Option<Chord>) {
+        for (suffix, action) in &self.leader_binds {
+            let name = format!("{action:?}");
+            if let Some(old) = &self.leader {
+                let Some(actions) = self.raw_bindings.get_mut(&old.followed_by(suffix)) else {
+                    continue;
+                };
+                let Some(index) = actions
+                    .iter()
+                    .position(|existing| format!("{existing:?}") == name)
+                else {
+                    continue;
+                };
+                actions.remove(index);
+            } else if self
+                .raw_bindings
+                .values()
+                .flatten()
+                .any(|existing| format!("{existing:?}") == name)
+            {
+                continue;
+            }
+
+            if let Some(leader) = &leader {
+                self.raw_bindings
+                    .entry(leader.followed_by(suffix))
+                    .or_default()
+                    .push(dyn_clone::clone_box(&**action));
+            }
+        }
+        self.leader = leader;
+    }
+
+    /// Create the plugin with the given persistence, `new` persists to the config directory
+    pub fn with_persistence(persistence: Persistence) -> Self {
+        Self {
+            persistence,
+            ..<Self as arcane_core::Plugin>::new()
+        }
+    }
+
+    /// Replace the bindings with the ones saved on disk, returning if any were loaded.
+    ///
+    /// The disk wins for actions bound in the file, actions only registered this session keep
+    /// their current binding. The trie is not rebuilt.
+    fn reload_bindings(&mut self) -> std::result::Result<bool, LoadBindingsError> {
+        let Some(config_dir) = self.persistence.dir() else {
+            return Ok(false);
+        };
+        let Some(bindings) = load_bindings(config_dir)? else {
+            return Ok(false);
+        };
+
+        self.merge_bindings(bindings);
+        event!(Level::DEBUG, "Loaded {} keybinds", self.raw_bindings.len());
+        Ok(true)
+    }
+
+    /// Rebuild the trie from `raw_bindings`
+    fn rebuild_trie(&mut self) {
+        event!(Level::TRACE, "Rebuilding keybind trie");
+        self.trie = TrieHolder::from_raw(&self.raw_bindings, self.case_insensitive);
+        #[cfg(test)]
+        {
+            self.trie_builds = self.trie_builds.wrapping_add(1);
+        }
+    }
+
+    /// Replace the current bindings with `bindings`.
+    ///
+    /// `bindings` wins for the actions bound in it, actions only in the current bindings keep
+    /// their chord.
+    fn merge_bindings(&mut self, mut bindings: HashMap<Chord, Vec<KeyBindEvent>>) {
+        let incoming = bindings
+            .values()
+            .flatten()
+            .map(|action| format!("{action:?}"))
+            .collect::<Vec<_>>();
+        for (chord, actions) in self.raw_bindings.drain() {
+            for action in actions {
+                if !incoming.contains(&format!("{action:?}")) {
+                    bindings.entry(chord.clone()).or_default().push(action);
+                }
+            }
+        }
+        self.raw_bindings = bindings;
+    }
+
+    /// Every binding in the same format as `keybinds.json`, to be loaded with `ImportKeybinds`
+    ///
+    /// # Errors
+    /// If a bound action fails to serialize
+    pub fn export(&self) -> serde_json::Result<serde_json::Value> {
+        let bindings = self.raw_bindings.iter().collect::<Vec<_>>();
+        serde_json::to_value(Versioned::current(bindings))
+    }
+
+    /// Apply bindings exported with `export`, returning if the bindings were changed
+    fn import_bindings(&mut self, import: &ImportKeybinds) -> bool {
+        let bindings = versioned_from_value(import.bindings.clone())
+            .and_then(|file| parse_bindings(migrate_bindings(file)));
+        let bindings = match bindings {
+            Ok(bindings) => bindings,
+            Err(err) => {
+                event!(Level::ERROR, "Invalid keybinds import: {err}");
+                return false;
+            }
+        };
+
+        event!(
+            Level::INFO,
+            "Importing {} keybinds, merging: {}",
+            bindings.len(),
+            import.merge
+        );
+        if import.merge {
+            self.merge_bindings(bindings);
+        } else {
+            self.raw_bindings = bindings;
+        }
+        true
+    }
+
+    /// Every bound action with the chord that triggers it, sorted by the action name.
+    ///
+    /// The action names are the debug representations used by `TriggerAction`
+    pub fn all_actions(&self) -> Vec<(Chord, String)> {
+        let mut actions = self
+            .raw_bindings
+            .iter()
+            .flat_map(|(chord, actions)| {
+                actions
+                    .iter()
+                    .map(move |action| (chord.clone(), format!("{action:?}")))
+            })
+            .collect::<Vec<_>>();
+        actions.sort_by(|(chord1, action1), (chord2, action2)| {
+            action1
+                .cmp(action2)
+                .then_with(|| chord1.keys.cmp(&chord2.keys))
+        });
+        actions
+    }
+
+    /// Get the keys pressed so far of the chord in progress, and the possible next keys together
+    /// with the actions they lead to.
+    ///
+    /// Returns `None` when no chord is in progress
+    pub fn pending_chord(&self) -> Option<PendingChord> {
+        let search = self.trie.borrow_search();
+        if search.prefix_len() == 0 {
+            return None;
+        }
+
+        let prefix: Vec<KeyBind> = search.prefix();
+        let continuations = self
+            .trie
+            .borrow_bindings_tree()
+            .postfix_search::<Vec<KeyBind>, _>(&prefix)
+            .filter_map(|(postfix, actions)| Some((*postfix.first()?, actions)))
+            .flat_map(|(key, actions)| {
+                actions
+                    .iter()
+                    .map(move |action| (key, format!("{action:?}")))
+            })
+            .collect();
+
+        Some((prefix, continuations))
+    }
+}
+
 /// Generic events to move around a menu
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 #[non_exhaustive]
@@ -231,26 +930,109 @@ pub enum MenuEvent {
     /// alt select something
     #[debug("Menu::Alt Select")]
     AltSelect,
+    /// Move to the first element
+    #[debug("Menu::Top")]
+    Top,
+    /// Move to the last element
+    #[debug("Menu::Bottom")]
+    Bottom,
+    /// Move up by `MENU_PAGE_SIZE` elements
+    #[debug("Menu::PageUp")]
+    PageUp,
+    /// Move down by `MENU_PAGE_SIZE` elements
+    #[debug("Menu::PageDown")]
+    PageDown,
 }
 
+/// How many elements `MenuEvent::PageUp` and `MenuEvent::PageDown` move
+pub const MENU_PAGE_SIZE: usize = 10;
+
 #[typetag::serde]
 impl BindResult for MenuEvent {}
 
-/// Disable or Enable keybindings
+/// A action was registered to a chord that already had a different action bound to it.
+///
+/// Both actions stay bound and will fire together.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct KeybindConflict {
+    /// The chord both actions are bound to
+    pub chord: Chord,
+    /// The action that was already bound
+    pub existing: String,
+    /// The newly registered action
+    pub incoming: String,
+}
+
+/// A chord was started, but abandoned because the next key did not continue it
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ChordAborted {
+    /// The keys that had been pressed before the chord was abandoned
+    pub prefix: Vec<KeyBind>,
+}
+
+/// Dispatch a bound action as if its keybind was pressed
+pub struct TriggerAction {
+    /// The action to dispatch, matched against its debug representation
+    pub event: String,
+}
+
+/// Reload the keybinds from `keybinds.json`
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct LockKeybindings(pub bool);
+pub struct ReloadKeybinds;
+
+#[typetag::serde]
+impl BindResult for ReloadKeybinds {}
+
+/// Load bindings produced by `KeybindPlugin::export`
+#[derive(Clone, Debug)]
+pub struct ImportKeybinds {
+    /// The exported bindings
+    pub bindings: serde_json::Value,
+    /// Keep actions missing from `bindings` at their current chord instead of unbinding them
+    pub merge: bool,
+}
+
+/// Lock or unlock keybindings for `source`.
+///
+/// Keybindings stay locked as long as any source has them locked, so a source unlocking them does
+/// not undo the lock of another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LockKeybindings {
+    /// What is locking the keybindings, like the name of the plugin
+    pub source: &'static str,
+    /// Lock or unlock the keybindings
+    pub locked: bool,
+}
+
+/// Keybindings were locked or unlocked, `true` when they are now locked
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeybindsLockStateChanged(pub bool);
 
 impl arcane_core::Plugin for KeybindPlugin {
     fn new() -> Self {
         Self {
             raw_bindings: HashMap::new(),
-            trie: TrieHolder::from_raw(&HashMap::new()),
+            trie: TrieHolder::from_raw(&HashMap::new(), false),
             enabled: true,
+            locks: HashSet::new(),
+            chord_elapsed: Duration::ZERO,
+            case_insensitive: false,
+            persistence: Persistence::config(),
+            leader_setting: String::new(),
+            leader: None,
+            leader_binds: Vec::new(),
+            notation: KeyNotation::Default,
+            #[cfg(test)]
+            trie_builds: 0,
         }
     }
 
-    #[errors(serde_json::Error)]
+    #[errors(LoadBindingsError)]
     fn on_load(&mut self, events: &mut arcane_core::EventManager) -> Result<()> {
+        events.dispatch(arcane_settings::RegisterSettings(Box::new(
+            KeybindSettings::default(),
+        )));
+
         events.ensure_event::<MenuEvent>();
         events.dispatch(RegisterKeybind::single_key(
             KeyBind {
@@ -294,68 +1076,184 @@ impl arcane_core::Plugin for KeybindPlugin {
             },
             MenuEvent::AltSelect,
         ));
+        events.dispatch(RegisterKeybind::single_key(
+            KeyBind {
+                modifiers: KeyModifiers::NONE,
+                key: KeyCode::Char('g'),
+            },
+            MenuEvent::Top,
+        ));
+        events.dispatch(RegisterKeybind::single_key(
+            KeyBind {
+                modifiers: KeyModifiers::SHIFT,
+                key: KeyCode::Char('G'),
+            },
+            MenuEvent::Bottom,
+        ));
+        events.dispatch(RegisterKeybind::single_key(
+            KeyBind {
+                modifiers: KeyModifiers::CONTROL,
+                key: KeyCode::Char('u'),
+            },
+            MenuEvent::PageUp,
+        ));
+        events.dispatch(RegisterKeybind::single_key(
+            KeyBind {
+                modifiers: KeyModifiers::CONTROL,
+                key: KeyCode::Char('d'),
+            },
+            MenuEvent::PageDown,
+        ));
 
-        if let Some(project_directory) = arcane_core::project_dirs() {
-            let config_path = project_directory.config_dir().join("keybinds.json");
-            if let Ok(file) = std::fs::File::open(&config_path) {
-                let data: Vec<(Chord, serde_json::Value)> = serde_json::from_reader(file)?;
-                event!(Level::DEBUG, "loading {} keybinds", data.len());
-                let data = data.into_iter().filter_map(|(chord, action)| {
-                    let action = serde_json::from_value(action).ok();
-                    if action.is_none() {
-                        event!(Level::ERROR, "Invalid action in keybindings file!");
-                    }
-                    action.map(|action| (chord, action))
-                });
-                self.raw_bindings = data.collect();
-                event!(Level::DEBUG, "Loaded {} keybinds", self.raw_bindings.len());
-                self.trie = TrieHolder::from_raw(&self.raw_bindings);
-            }
+        events.ensure_event::<ReloadKeybinds>();
+        // So triggering `NoOp` through `TriggerAction` is not reported as a missing queue
+        events.ensure_event::<NoOp>();
+        if self.reload_bindings()? {
+            self.rebuild_trie();
         }
 
         Ok(())
     }
 
-    #[errors(std::io::Error, serde_json::Error)]
+    #[errors(SaveBindingsError, LoadBindingsError)]
     fn update(
         &mut self,
         events: &mut arcane_core::EventManager,
-        _plugins: &arcane_core::PluginStore,
+        plugins: &arcane_core::PluginStore,
     ) -> Result<()> {
+        let settings = arcane_settings::get_settings::<KeybindSettings>(plugins)
+            .map(|settings| settings.clone())
+            .unwrap_or_default();
+
+        // The trie is rebuilt at most once per frame, after every change has been applied
+        let mut trie_outdated = false;
+        if !events.read::<ReloadKeybinds>().is_empty() {
+            event!(Level::INFO, "Reloading keybinds");
+            trie_outdated |= self.reload_bindings()?;
+        }
+
         let mut bindings_modified = false;
+        for event in events.read::<ImportKeybinds>() {
+            bindings_modified |= self.import_bindings(event);
+        }
+
+        if settings.leader != self.leader_setting {
+            self.leader_setting.clone_from(&settings.leader);
+            let leader = if settings.leader.trim().is_empty() {
+                None
+            } else {
+                Chord::parse(&settings.leader)
+                    .inspect_err(|err| {
+                        event!(Level::WARN, "Invalid leader {:?}: {err}", settings.leader);
+                    })
+                    .ok()
+            };
+            if leader != self.leader {
+                event!(Level::DEBUG, "Changing leader to {:?}", settings.leader);
+                self.change_leader(leader);
+                bindings_modified = true;
+            }
+        }
+
         // Very important to remove first
-        for event in events.read::<RegisterKeybind>() {
-            if self
-                .raw_bindings
-                .iter()
-                .flat_map(|(_, actions)| actions.iter())
-                .any(|action| format!("{action:?}") == format!("{:?}", event.event))
+        let (reader, mut writer) = events.split();
+        for event in reader.read::<RegisterKeybind>() {
+            if !event.allow_modifier_binds && event.bind.keys.iter().any(KeyBind::is_only_modifiers)
             {
-                event!(Level::TRACE, "Keybind {:?} already exists", event.event);
+                event!(
+                    Level::WARN,
+                    "Keybind {} contains bare modifiers without allowing them",
+                    event.bind.render()
+                );
+                continue;
+            }
+
+            if event.leader {
+                for action in &event.events {
+                    let name = format!("{action:?}");
+                    if !self
+                        .leader_binds
+                        .iter()
+                        .any(|(_, existing)| format!("{existing:?}") == name)
+                    {
+                        self.leader_binds
+                            .push((event.bind.keys.clone(), dyn_clone::clone_box(&**action)));
+                    }
+                }
+            }
+            let bind = if event.leader {
+                let Some(leader) = &self.leader else {
+                    event!(
+                        Level::DEBUG,
+                        "No leader set, not binding {}",
+                        event.bind.render()
+                    );
+                    continue;
+                };
+                leader.followed_by(&event.bind.keys)
             } else {
-                event!(Level::DEBUG, "Registering keybind: {}", event.bind.render());
-                let action = dyn_clone::clone_box(&*event.event);
-                self.raw_bindings
-                    .entry(event.bind.clone())
-                    .or_default()
-                    .push(action);
-                bindings_modified = true;
+                event.bind.clone()
+            };
+
+            let existing_actions = self
+                .raw_bindings
+                .get(&bind)
+                .map(|actions| {
+                    actions
+                        .iter()
+                        .map(|action| format!("{action:?}"))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            for action in &event.events {
+                // Any number of chords can be shadowed
+                if !is_no_op(action)
+                    && self
+                        .raw_bindings
+                        .values()
+                        .flatten()
+                        .any(|existing| format!("{existing:?}") == format!("{action:?}"))
+                {
+                    event!(Level::TRACE, "Keybind {action:?} already exists");
+                } else {
+                    event!(Level::DEBUG, "Registering keybind: {}", bind.render());
+                    for existing in &existing_actions {
+                        event!(
+                            Level::WARN,
+                            "Keybind conflict on {}: {existing} and {action:?}",
+                            bind.render()
+                        );
+                        writer.dispatch(KeybindConflict {
+                            chord: bind.clone(),
+                            existing: existing.clone(),
+                            incoming: format!("{action:?}"),
+                        });
+                    }
+
+                    let action = dyn_clone::clone_box(&**action);
+                    self.raw_bindings
+                        .entry(bind.clone())
+                        .or_default()
+                        .push(action);
+                    bindings_modified = true;
+                }
             }
         }
         for event in events.read::<RebindKeybind>() {
             event!(
                 Level::DEBUG,
-                "Rebinding keybind: {} to {}",
-                event.event,
+                "Rebinding keybind: {} #{} to {}",
+                event.action.chord.render(),
+                event.action.index,
                 event.bind.render()
             );
 
-            let action = self.raw_bindings.values_mut().find_map(|actions| {
-                let index = actions
-                    .iter()
-                    .position(|action| format!("{action:?}") == event.event)?;
-                Some(actions.remove(index))
-            });
+            let action = self
+                .raw_bindings
+                .get_mut(&event.action.chord)
+                .filter(|actions| event.action.index < actions.len())
+                .map(|actions| actions.remove(event.action.index));
 
             if let Some(action) = action {
                 self.raw_bindings
@@ -367,50 +1265,133 @@ impl arcane_core::Plugin for KeybindPlugin {
                 event!(Level::ERROR, "Keybind not found");
             }
         }
+        for event in events.read::<UnregisterKeybind>() {
+            event!(Level::DEBUG, "Unregistering keybind: {}", event.event);
+
+            self.leader_binds
+                .retain(|(_, action)| format!("{action:?}") != event.event);
+            for actions in self.raw_bindings.values_mut() {
+                let before = actions.len();
+                actions.retain(|action| format!("{action:?}") != event.event);
+                if actions.len() != before {
+                    bindings_modified = true;
+                }
+            }
+        }
+
+        self.notation = match settings.notation.as_str() {
+            "Emacs" => KeyNotation::Emacs,
+            _ => KeyNotation::Default,
+        };
+
+        if settings.case_insensitive != self.case_insensitive {
+            self.case_insensitive = settings.case_insensitive;
+            trie_outdated = true;
+        }
 
         if bindings_modified {
             self.raw_bindings.retain(|_, actions| !actions.is_empty());
-            self.trie = TrieHolder::from_raw(&self.raw_bindings);
-
-            if let Some(project_directory) = arcane_core::project_dirs() {
-                let config_dir = project_directory.config_dir();
-                create_dir_all(config_dir)?;
+            trie_outdated = true;
 
-                let keybinds_path = config_dir.join("keybinds.json");
-                event!(Level::INFO, "Saving keybinds to {keybinds_path:?}");
-                let file = std::fs::File::create(keybinds_path)?;
-                let bindings = self.raw_bindings.iter().collect::<Vec<_>>();
-                serde_json::ser::to_writer_pretty(file, &bindings)?;
+            if let Some(config_dir) = self.persistence.dir() {
+                save_bindings(&self.raw_bindings, config_dir)?;
             }
         }
 
-        for event in events.read::<LockKeybindings>() {
-            self.enabled = !event.0;
+        if trie_outdated {
+            self.rebuild_trie();
         }
 
         let (reader, mut writer) = events.split();
-        if self.enabled {
-            for event in reader.read::<arcane_core::KeydownEvent>() {
-                let keybind = KeyBind {
-                    modifiers: event.0.modifiers,
-                    key: event.0.code,
-                };
-                if keybind.is_only_modifiers() {
-                    continue;
+        for event in reader.read::<TriggerAction>() {
+            let action = self
+                .raw_bindings
+                .values()
+                .flatten()
+                .find(|action| format!("{action:?}") == event.event);
+            if let Some(action) = action {
+                event!(Level::DEBUG, "Triggering {action:?}");
+                writer.dispatch_bind_result(action);
+            } else {
+                event!(
+                    Level::WARN,
+                    "Tried to trigger unknown action {}",
+                    event.event
+                );
+            }
+        }
+
+        let was_enabled = self.enabled;
+        for event in events.read::<LockKeybindings>() {
+            if event.locked {
+                self.locks.insert(event.source);
+            } else {
+                self.locks.remove(event.source);
+            }
+        }
+        self.enabled = self.locks.is_empty();
+        if self.enabled != was_enabled {
+            event!(Level::DEBUG, "Keybindings enabled: {}", self.enabled);
+            events.dispatch(KeybindsLockStateChanged(!self.enabled));
+        }
+
+        if self.trie.borrow_search().prefix_len() > 0 {
+            let timeout =
+                Duration::from_millis(settings.chord_timeout.try_into().unwrap_or_default());
+
+            for event in events.read::<arcane_core::DeltaTimeEvent>() {
+                self.chord_elapsed = self.chord_elapsed.saturating_add(event.0);
+            }
+            if !timeout.is_zero() && self.chord_elapsed >= timeout {
+                // The pending keys can be a complete bind that was waiting for a longer chord
+                if let Some(actions) = self.trie.get_match() {
+                    event!(Level::TRACE, "Chord timed out on a match");
+                    emit_actions(&mut events.split().1, actions);
                 }
+                event!(Level::TRACE, "Chord timed out, clearing search");
+                self.trie.clear();
+            }
+        } else {
+            self.chord_elapsed = Duration::ZERO;
+        }
+
+        let immediate = settings.chord_resolution == "Immediate";
+        let (reader, mut writer) = events.split();
+        if self.enabled {
+            for event in reader.read::<arcane_core::KeydownEvent>() {
+                let keybind = KeyBind {
+                    modifiers: event.0.modifiers,
+                    key: event.0.code,
+                };
+                let keybind = if self.case_insensitive {
+                    keybind.normalized()
+                } else {
+                    keybind
+                };
+                self.chord_elapsed = Duration::ZERO;
 
                 loop {
                     event!(Level::TRACE, "Chekcing: {}", keybind.render());
                     let depth = self.trie.borrow_search().prefix_len();
                     event!(Level::TRACE, "Current Search depth: {}", depth);
-                    match self.trie.search(&keybind) {
+                    let answer = match self.trie.search(&keybind) {
+                        // Don't wait for the longer chords this is a prefix of
+                        Some(Answer::PrefixAndMatch) if immediate => Some(Answer::Match),
+                        answer => answer,
+                    };
+                    match answer {
+                        // Bare modifiers are only seen by binds that asked for them
+                        None if keybind.is_only_modifiers() => break,
                         None => {
                             event!(Level::TRACE, "No match for {}", keybind.render());
                             if let Some(events) = self.trie.get_match() {
-                                for event in events {
-                                    event!(Level::DEBUG, "Emitting {event:?}");
-                                    let event = dyn_clone::clone_box(&**event);
-                                    writer.dispatch_raw(event as Box<dyn arcane_core::RawEvent>);
+                                emit_actions(&mut writer, events);
+                            } else if depth > 0 {
+                                let prefix: Vec<KeyBind> = self.trie.borrow_search().prefix();
+                                // Holding a modifier to type a key is not abandoning a chord
+                                if !prefix.iter().all(KeyBind::is_only_modifiers) {
+                                    event!(Level::TRACE, "Chord aborted");
+                                    writer.dispatch(ChordAborted { prefix });
                                 }
                             }
 
@@ -427,56 +1408,965 @@ impl arcane_core::Plugin for KeybindPlugin {
                         Some(Answer::Match) => {
                             event!(Level::TRACE, "Match for {}", keybind.render());
                             if let Some(events) = self.trie.get_match() {
-                                for event in events {
-                                    event!(Level::DEBUG, "Emitting {event:?}");
-                                    let event = dyn_clone::clone_box(&**event);
-                                    writer.dispatch_raw(event as Box<dyn arcane_core::RawEvent>);
-                                }
+                                emit_actions(&mut writer, events);
                             }
 
-                            event!(Level::TRACE, "Clearing search");
-                            self.trie.clear();
-                            break;
-                        }
-                        Some(Answer::Prefix | Answer::PrefixAndMatch) => {
-                            event!(Level::TRACE, "Prefix match for {}", keybind.render());
-                            break;
-                        }
-                    }
-                }
-            }
+                            event!(Level::TRACE, "Clearing search");
+                            self.trie.clear();
+                            break;
+                        }
+                        Some(Answer::Prefix | Answer::PrefixAndMatch) => {
+                            event!(Level::TRACE, "Prefix match for {}", keybind.render());
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn plugin_name(&self) -> &'static str {
+        "Keybindings"
+    }
+
+    fn can_disable(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use arcane_core::{
+        DeltaTimeEvent,
+        EventManager,
+        KeydownEvent,
+        Persistence,
+        Plugin,
+        StateManager,
+    };
+    use crossterm::event::{
+        KeyCode,
+        KeyEvent,
+        KeyEventKind,
+        KeyEventState,
+        KeyModifiers,
+        ModifierKeyCode,
+    };
+
+    use super::{
+        load_bindings,
+        save_bindings,
+        ActionId,
+        BindResult,
+        Chord,
+        ChordAborted,
+        Deserialize,
+        DispatchBindResult,
+        ImportKeybinds,
+        KeyBind,
+        KeyBindEvent,
+        KeyNotation,
+        KeybindConflict,
+        KeybindPlugin,
+        KeybindSettings,
+        KeybindsLockStateChanged,
+        LockKeybindings,
+        MenuEvent,
+        NoOp,
+        ParseChordError,
+        RebindKeybind,
+        RegisterKeybind,
+        ReloadKeybinds,
+        Serialize,
+        TriggerAction,
+        UnregisterKeybind,
+        Versioned,
+    };
+
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    enum TestEvent {
+        Bar,
+        Foo,
+    }
+
+    #[typetag::serde]
+    impl BindResult for TestEvent {}
+
+    #[test]
+    fn single_key() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.events.ensure_event::<TestEvent>();
+        state.events.dispatch(RegisterKeybind::single_key(
+            super::KeyBind {
+                modifiers: KeyModifiers::NONE,
+                key: KeyCode::Up,
+            },
+            TestEvent::Foo,
+        ));
+
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        state.events.dispatch(KeydownEvent(KeyEvent {
+            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Up,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }));
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        state.events.swap_buffers();
+        assert_eq!(state.events.read::<TestEvent>(), &[TestEvent::Foo]);
+    }
+
+    #[test]
+    fn single_key_miss_first() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.events.ensure_event::<TestEvent>();
+        state.events.dispatch(RegisterKeybind::single_key(
+            super::KeyBind {
+                modifiers: KeyModifiers::NONE,
+                key: KeyCode::Up,
+            },
+            TestEvent::Foo,
+        ));
+
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        state.events.dispatch(KeydownEvent(KeyEvent {
+            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Down,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }));
+        state.events.swap_buffers();
+        state.update().unwrap();
+        state.events.swap_buffers();
+        assert_eq!(state.events.read::<TestEvent>(), &[]);
+
+        state.events.dispatch(KeydownEvent(KeyEvent {
+            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Up,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }));
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        state.events.swap_buffers();
+        assert_eq!(state.events.read::<TestEvent>(), &[TestEvent::Foo]);
+    }
+
+    #[test]
+    fn unregister_single_key() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.events.ensure_event::<TestEvent>();
+        state.events.dispatch(RegisterKeybind::single_key(
+            super::KeyBind {
+                modifiers: KeyModifiers::NONE,
+                key: KeyCode::Up,
+            },
+            TestEvent::Foo,
+        ));
+
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        state.events.dispatch(UnregisterKeybind {
+            event: format!("{:?}", TestEvent::Foo),
+        });
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        state.events.dispatch(KeydownEvent(KeyEvent {
+            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Up,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }));
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        state.events.swap_buffers();
+        assert_eq!(state.events.read::<TestEvent>(), &[]);
+    }
+
+    #[test]
+    fn parse_keybind() {
+        assert_eq!(
+            "ctrl+shift+a".parse::<KeyBind>(),
+            Ok(KeyBind {
+                modifiers: KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+                key: KeyCode::Char('a'),
+            })
+        );
+        assert_eq!(
+            "enter".parse::<KeyBind>(),
+            Ok(KeyBind {
+                modifiers: KeyModifiers::NONE,
+                key: KeyCode::Enter,
+            })
+        );
+        assert_eq!(
+            "alt+F12".parse::<KeyBind>(),
+            Ok(KeyBind {
+                modifiers: KeyModifiers::ALT,
+                key: KeyCode::F(12),
+            })
+        );
+        assert_eq!(
+            "ctrl++".parse::<KeyBind>(),
+            Ok(KeyBind {
+                modifiers: KeyModifiers::CONTROL,
+                key: KeyCode::Char('+'),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_chord() {
+        let chord = Chord::parse("ctrl+p ctrl+k").unwrap();
+        assert_eq!(
+            &*chord.keys,
+            &[
+                KeyBind {
+                    modifiers: KeyModifiers::CONTROL,
+                    key: KeyCode::Char('p'),
+                },
+                KeyBind {
+                    modifiers: KeyModifiers::CONTROL,
+                    key: KeyCode::Char('k'),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Chord::parse(""), Err(ParseChordError::Empty));
+        assert_eq!(
+            Chord::parse("ctrl+a foo+b"),
+            Err(ParseChordError::UnknownModifier(String::from("foo")))
+        );
+        assert_eq!(
+            Chord::parse("ctrl+nope"),
+            Err(ParseChordError::UnknownKey(String::from("nope")))
+        );
+    }
+
+    #[test]
+    fn parse_render_round_trip() {
+        for text in [
+            "ctrl+p ctrl+k",
+            "enter",
+            "alt+left",
+            "h j k l",
+            "shift+f5 esc",
+        ] {
+            let chord = Chord::parse(text).unwrap();
+            assert_eq!(Chord::parse(&chord.render()), Ok(chord));
+        }
+    }
+
+    #[test]
+    fn render_emacs() {
+        for (text, expected) in [
+            ("ctrl+h", "C-h"),
+            ("alt+x", "M-x"),
+            ("ctrl+alt+x", "C-M-x"),
+            ("shift+tab", "S-TAB"),
+            ("backtab", "S-TAB"),
+            ("shift+G", "G"),
+            ("ctrl+shift+left", "C-S-<left>"),
+            ("ctrl+space", "C-SPC"),
+            ("super+enter", "s-RET"),
+            ("f5", "<f5>"),
+        ] {
+            let keybind = text.parse::<KeyBind>().unwrap();
+            assert_eq!(keybind.render_emacs(), expected, "{text}");
+        }
+
+        let chord = Chord::parse("ctrl+x ctrl+s").unwrap();
+        assert_eq!(chord.render_as(KeyNotation::Emacs), "C-x C-s");
+        assert_eq!(chord.render_as(KeyNotation::Default), chord.render());
+    }
+
+    #[test]
+    fn multi() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.events.ensure_event::<TestEvent>();
+        state.events.dispatch(RegisterKeybind::multi(
+            [super::KeyBind {
+                modifiers: KeyModifiers::NONE,
+                key: KeyCode::Up,
+            }],
+            vec![Box::new(TestEvent::Foo), Box::new(TestEvent::Bar)],
+        ));
+
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        state.events.dispatch(KeydownEvent(KeyEvent {
+            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Up,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }));
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        state.events.swap_buffers();
+        assert_eq!(
+            state.events.read::<TestEvent>(),
+            &[TestEvent::Foo, TestEvent::Bar]
+        );
+    }
+
+    #[test]
+    fn conflict() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.events.ensure_event::<TestEvent>();
+        state.events.dispatch(RegisterKeybind::single_key(
+            super::KeyBind {
+                modifiers: KeyModifiers::NONE,
+                key: KeyCode::Up,
+            },
+            TestEvent::Foo,
+        ));
+        state.events.dispatch(RegisterKeybind::single_key(
+            super::KeyBind {
+                modifiers: KeyModifiers::NONE,
+                key: KeyCode::Up,
+            },
+            TestEvent::Bar,
+        ));
+
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        state.events.swap_buffers();
+        assert_eq!(
+            state.events.read::<KeybindConflict>(),
+            &[KeybindConflict {
+                chord: Chord {
+                    keys: Box::new([super::KeyBind {
+                        modifiers: KeyModifiers::NONE,
+                        key: KeyCode::Up,
+                    }]),
+                },
+                existing: String::from("Foo"),
+                incoming: String::from("Bar"),
+            }]
+        );
+    }
+
+    #[test]
+    fn keybind_matches() {
+        let lower = KeyBind {
+            modifiers: KeyModifiers::NONE,
+            key: KeyCode::Char('h'),
+        };
+        let upper = KeyBind {
+            modifiers: KeyModifiers::SHIFT,
+            key: KeyCode::Char('H'),
+        };
+        let caps_lock = KeyBind {
+            modifiers: KeyModifiers::NONE,
+            key: KeyCode::Char('H'),
+        };
+        let control = KeyBind {
+            modifiers: KeyModifiers::CONTROL,
+            key: KeyCode::Char('h'),
+        };
+
+        assert!(lower.matches(&upper));
+        assert!(lower.matches(&caps_lock));
+        assert!(!lower.matches(&control));
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.events.ensure_event::<TestEvent>();
+        state
+            .events
+            .dispatch(arcane_settings::RegisterSettings(Box::new(
+                KeybindSettings {
+                    case_insensitive: true,
+                    ..KeybindSettings::default()
+                },
+            )));
+        state.events.dispatch(RegisterKeybind::single_key(
+            super::KeyBind {
+                modifiers: KeyModifiers::NONE,
+                key: KeyCode::Char('h'),
+            },
+            TestEvent::Foo,
+        ));
+
+        state.events.swap_buffers();
+        state.update().unwrap();
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        state.events.dispatch(KeydownEvent(KeyEvent {
+            modifiers: KeyModifiers::SHIFT,
+            code: KeyCode::Char('H'),
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }));
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        state.events.swap_buffers();
+        assert_eq!(state.events.read::<TestEvent>(), &[TestEvent::Foo]);
+    }
+
+    #[derive(Clone, Debug, Deserialize)]
+    struct UnserializableEvent;
+
+    impl Serialize for UnserializableEvent {
+        fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("can not be serialized"))
+        }
+    }
+
+    #[typetag::serde]
+    impl BindResult for UnserializableEvent {}
+
+    #[test]
+    fn failed_save_keeps_old_file() {
+        let config_dir = std::env::temp_dir().join("arcane_failed_save_keeps_old_file");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let keybinds_path = config_dir.join("keybinds.json");
+        std::fs::write(&keybinds_path, "[]").unwrap();
+
+        let mut plugin = KeybindPlugin::with_persistence(Persistence::Memory);
+        plugin.raw_bindings.insert(
+            Chord {
+                keys: Box::new([KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Up,
+                }]),
+            },
+            vec![Box::new(UnserializableEvent)],
+        );
+
+        assert!(save_bindings(&plugin.raw_bindings, &config_dir).is_err());
+        assert_eq!(std::fs::read_to_string(&keybinds_path).unwrap(), "[]");
+        assert!(!config_dir.join("keybinds.json.tmp").exists());
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    /// Load `contents` as a keybinds file from a fresh directory
+    fn load_from(name: &str, contents: &str) -> HashMap<Chord, Vec<KeyBindEvent>> {
+        let config_dir = std::env::temp_dir().join(name);
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(config_dir.join("keybinds.json"), contents).unwrap();
+        let bindings = load_bindings(&config_dir).unwrap().unwrap();
+        std::fs::remove_dir_all(config_dir).unwrap();
+        bindings
+    }
+
+    #[test]
+    fn load_versioned_keybinds() {
+        let mut bindings = HashMap::new();
+        bindings.insert(
+            Chord {
+                keys: Box::new([KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Up,
+                }]),
+            },
+            vec![Box::new(TestEvent::Foo) as KeyBindEvent],
+        );
+        let contents =
+            serde_json::to_string(&Versioned::current(bindings.iter().collect::<Vec<_>>()))
+                .unwrap();
+        assert!(contents.starts_with(r#"{"version":1,"data":["#));
+
+        let loaded = load_from("arcane_load_versioned_keybinds", &contents);
+        assert_eq!(format!("{loaded:?}"), format!("{bindings:?}"));
+    }
+
+    #[test]
+    fn load_legacy_keybinds() {
+        let mut bindings = HashMap::new();
+        bindings.insert(
+            Chord {
+                keys: Box::new([KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Up,
+                }]),
+            },
+            vec![Box::new(TestEvent::Foo) as KeyBindEvent],
+        );
+        let contents = serde_json::to_string(&bindings.iter().collect::<Vec<_>>()).unwrap();
+
+        let loaded = load_from("arcane_load_legacy_keybinds", &contents);
+        assert_eq!(format!("{loaded:?}"), format!("{bindings:?}"));
+    }
+
+    #[test]
+    fn lock_state_changed_once() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.on_load().unwrap();
+
+        let mut changes = Vec::new();
+        for locked in [true, true, false] {
+            state.events.dispatch(LockKeybindings {
+                source: "test",
+                locked,
+            });
+            state.events.swap_buffers();
+            state.update().unwrap();
+            state.events.swap_buffers();
+            changes.extend_from_slice(state.events.read::<KeybindsLockStateChanged>());
+        }
+
+        assert_eq!(
+            changes,
+            [
+                KeybindsLockStateChanged(true),
+                KeybindsLockStateChanged(false)
+            ]
+        );
+        assert!(state.plugins.get::<KeybindPlugin>().unwrap().is_enabled());
+    }
+
+    #[test]
+    fn lock_held_by_each_source() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.on_load().unwrap();
+
+        for (source, locked, enabled) in [
+            ("window", true, false),
+            ("confirm", true, false),
+            ("confirm", false, false),
+            ("window", false, true),
+        ] {
+            state.events.dispatch(LockKeybindings { source, locked });
+            state.events.swap_buffers();
+            state.update().unwrap();
+            assert_eq!(
+                state.plugins.get::<KeybindPlugin>().unwrap().is_enabled(),
+                enabled
+            );
+        }
+    }
+
+    #[test]
+    fn memory_persistence_writes_nothing() {
+        /// Register a keybind with the given persistence, which saves the bindings
+        fn register(persistence: Persistence) -> StateManager {
+            let mut state = StateManager::with_persistence(Persistence::Memory);
+            state
+                .plugins
+                .insert(KeybindPlugin::with_persistence(persistence));
+            state.on_load().unwrap();
+            state.events.ensure_event::<TestEvent>();
+            state.events.dispatch(RegisterKeybind::single_key(
+                KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Up,
+                },
+                TestEvent::Foo,
+            ));
+            state.events.swap_buffers();
+            state.update().unwrap();
+            state
+        }
+
+        let config_dir = std::env::temp_dir().join("arcane_memory_persistence_keybinds");
+        let _ = std::fs::remove_dir_all(&config_dir);
+        register(Persistence::Disk(config_dir.clone()));
+        assert!(config_dir.join("keybinds.json").exists());
+
+        std::fs::remove_dir_all(&config_dir).unwrap();
+        std::fs::create_dir(&config_dir).unwrap();
+        let state = register(Persistence::Memory);
+        assert!(!state
+            .plugins
+            .get::<KeybindPlugin>()
+            .unwrap()
+            .raw_bindings
+            .is_empty());
+        assert!(std::fs::read_dir(&config_dir).unwrap().next().is_none());
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[test]
+    fn reload_keybinds() {
+        let config_dir = std::env::temp_dir().join("arcane_reload_keybinds");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(config_dir.join("keybinds.json"), "[]").unwrap();
+
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Disk(
+                config_dir.clone(),
+            )));
+        state.on_load().unwrap();
+        state.events.ensure_event::<TestEvent>();
+        state.events.dispatch(RegisterKeybind::single_key(
+            KeyBind {
+                modifiers: KeyModifiers::NONE,
+                key: KeyCode::Up,
+            },
+            TestEvent::Foo,
+        ));
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        let mut on_disk: HashMap<Chord, Vec<KeyBindEvent>> = HashMap::new();
+        on_disk.insert(
+            Chord {
+                keys: Box::new([KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Down,
+                }]),
+            },
+            vec![Box::new(TestEvent::Bar)],
+        );
+        save_bindings(&on_disk, &config_dir).unwrap();
+
+        state.events.dispatch(ReloadKeybinds);
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        for key in [KeyCode::Down, KeyCode::Up] {
+            state.events.dispatch(KeydownEvent(KeyEvent {
+                modifiers: KeyModifiers::NONE,
+                code: key,
+                kind: KeyEventKind::Press,
+                state: KeyEventState::NONE,
+            }));
+        }
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        state.events.swap_buffers();
+        assert_eq!(
+            state.events.read::<TestEvent>(),
+            &[TestEvent::Bar, TestEvent::Foo]
+        );
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[test]
+    fn export_import() {
+        let up = KeyBind {
+            modifiers: KeyModifiers::NONE,
+            key: KeyCode::Up,
+        };
+        let down = KeyBind {
+            modifiers: KeyModifiers::NONE,
+            key: KeyCode::Down,
+        };
+
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.events.ensure_event::<TestEvent>();
+        state
+            .events
+            .dispatch(RegisterKeybind::single_key(up.clone(), TestEvent::Foo));
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        let exported = state
+            .plugins
+            .get::<KeybindPlugin>()
+            .unwrap()
+            .export()
+            .unwrap();
+        state.events.dispatch(ImportKeybinds {
+            bindings: serde_json::json!([]),
+            merge: false,
+        });
+        state.events.swap_buffers();
+        state.update().unwrap();
+        assert_eq!(press_keys(&mut state, &[up.clone()]), &[]);
+
+        state
+            .events
+            .dispatch(RegisterKeybind::single_key(down.clone(), TestEvent::Bar));
+        state.events.dispatch(ImportKeybinds {
+            bindings: exported.clone(),
+            merge: true,
+        });
+        state.events.swap_buffers();
+        state.update().unwrap();
+        assert_eq!(
+            press_keys(&mut state, &[up.clone(), down.clone()]),
+            &[TestEvent::Foo, TestEvent::Bar]
+        );
+
+        state.events.dispatch(ImportKeybinds {
+            bindings: exported,
+            merge: false,
+        });
+        state.events.swap_buffers();
+        state.update().unwrap();
+        assert_eq!(press_keys(&mut state, &[up, down]), &[TestEvent::Foo]);
+    }
+
+    #[test]
+    fn one_trie_rebuild_per_frame() {
+        let key = |key| KeyBind {
+            modifiers: KeyModifiers::NONE,
+            key,
+        };
+
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.events.ensure_event::<TestEvent>();
+        state.events.dispatch(RegisterKeybind::single_key(
+            key(KeyCode::Up),
+            TestEvent::Foo,
+        ));
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        let builds_before = state.plugins.get::<KeybindPlugin>().unwrap().trie_builds;
+        state.events.dispatch(RegisterKeybind::single_key(
+            key(KeyCode::Down),
+            TestEvent::Bar,
+        ));
+        state.events.dispatch(RebindKeybind {
+            bind: Chord {
+                keys: Box::new([key(KeyCode::Left)]),
+            },
+            action: ActionId {
+                chord: Chord {
+                    keys: Box::new([key(KeyCode::Up)]),
+                },
+                index: 0,
+            },
+        });
+        state.events.dispatch(UnregisterKeybind {
+            event: String::from("Foo"),
+        });
+        state.events.swap_buffers();
+        state.update().unwrap();
+        assert_eq!(
+            state.plugins.get::<KeybindPlugin>().unwrap().trie_builds,
+            builds_before + 1
+        );
+
+        assert_eq!(
+            press_keys(
+                &mut state,
+                &[key(KeyCode::Up), key(KeyCode::Left), key(KeyCode::Down)]
+            ),
+            &[TestEvent::Bar]
+        );
+    }
+
+    #[test]
+    fn no_op_shadows_chord() {
+        let h = KeyBind {
+            modifiers: KeyModifiers::NONE,
+            key: KeyCode::Char('h'),
+        };
+
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.on_load().unwrap();
+        state
+            .events
+            .dispatch(RegisterKeybind::single_key(h.clone(), NoOp));
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        for key in [h.clone(), "j".parse().unwrap()] {
+            state
+                .events
+                .dispatch(KeydownEvent(KeyEvent::new(key.key, key.modifiers)));
         }
+        state.events.swap_buffers();
+        state.update().unwrap();
+        state.events.swap_buffers();
+        assert_eq!(state.events.read::<MenuEvent>(), &[MenuEvent::Down]);
 
-        Ok(())
+        state.events.dispatch(UnregisterKeybind {
+            event: String::from("NoOp"),
+        });
+        state.events.swap_buffers();
+        state.update().unwrap();
+        state
+            .events
+            .dispatch(KeydownEvent(KeyEvent::new(h.key, h.modifiers)));
+        state.events.swap_buffers();
+        state.update().unwrap();
+        state.events.swap_buffers();
+        assert_eq!(state.events.read::<MenuEvent>(), &[MenuEvent::Left]);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use arcane_core::{KeydownEvent, Plugin, StateManager};
-    use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+    #[test]
+    fn trigger_action() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.events.ensure_event::<TestEvent>();
+        state.events.dispatch(RegisterKeybind::chord(
+            [
+                KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Up,
+                },
+                KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Down,
+                },
+            ],
+            TestEvent::Foo,
+        ));
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        let actions = state.plugins.get::<KeybindPlugin>().unwrap().all_actions();
+        assert!(actions
+            .iter()
+            .any(|(chord, action)| action == "Foo" && chord.keys.len() == 2));
 
-    use super::{BindResult, Deserialize, KeybindPlugin, RegisterKeybind, Serialize};
+        state.events.dispatch(TriggerAction {
+            event: String::from("Foo"),
+        });
+        state.events.swap_buffers();
+        state.update().unwrap();
 
-    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
-    enum TestEvent {
-        Bar,
-        Foo,
+        state.events.swap_buffers();
+        assert_eq!(state.events.read::<TestEvent>(), &[TestEvent::Foo]);
     }
 
-    #[typetag::serde]
-    impl BindResult for TestEvent {}
+    #[test]
+    fn dispatch_bind_result() {
+        let mut events = EventManager::new();
+        events.ensure_event::<MenuEvent>();
+        let event: KeyBindEvent = Box::new(MenuEvent::Down);
+
+        let (_, mut writer) = events.split();
+        writer.dispatch_bind_result(&event);
+        events.swap_buffers();
+
+        assert_eq!(events.read::<MenuEvent>(), &[MenuEvent::Down]);
+    }
 
     #[test]
-    fn single_key() {
-        let mut state = StateManager::new();
-        state.plugins.insert(KeybindPlugin::new());
-        state.events.ensure_event::<TestEvent>();
-        state.events.dispatch(RegisterKeybind::single_key(
-            super::KeyBind {
+    fn rebind_targets_action() {
+        let up = Chord {
+            keys: Box::new([KeyBind {
                 modifiers: KeyModifiers::NONE,
                 key: KeyCode::Up,
+            }]),
+        };
+        let down = Chord {
+            keys: Box::new([KeyBind {
+                modifiers: KeyModifiers::NONE,
+                key: KeyCode::Down,
+            }]),
+        };
+        let left = Chord {
+            keys: Box::new([KeyBind {
+                modifiers: KeyModifiers::NONE,
+                key: KeyCode::Left,
+            }]),
+        };
+
+        let mut plugin = KeybindPlugin::with_persistence(Persistence::Memory);
+        plugin
+            .raw_bindings
+            .insert(up.clone(), vec![Box::new(TestEvent::Foo)]);
+        plugin.raw_bindings.insert(
+            down.clone(),
+            vec![Box::new(TestEvent::Bar), Box::new(TestEvent::Foo)],
+        );
+
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state.plugins.insert(plugin);
+        state.events.dispatch(RebindKeybind {
+            bind: left.clone(),
+            action: ActionId {
+                chord: down.clone(),
+                index: 1,
             },
+        });
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        let plugin = state.plugins.get::<KeybindPlugin>().unwrap();
+        let actions = |chord: &Chord| {
+            plugin.raw_bindings.get(chord).map(|actions| {
+                actions
+                    .iter()
+                    .map(|action| format!("{action:?}"))
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(actions(&up), Some(vec![String::from("Foo")]));
+        assert_eq!(actions(&down), Some(vec![String::from("Bar")]));
+        assert_eq!(actions(&left), Some(vec![String::from("Foo")]));
+    }
+
+    #[test]
+    fn chord() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.events.ensure_event::<TestEvent>();
+        state.events.dispatch(RegisterKeybind::chord(
+            [
+                super::KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Up,
+                },
+                super::KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Down,
+                },
+            ],
             TestEvent::Foo,
         ));
 
@@ -492,26 +2382,155 @@ mod tests {
         state.events.swap_buffers();
         state.update().unwrap();
 
+        state.events.dispatch(KeydownEvent(KeyEvent {
+            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Down,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }));
+        state.events.swap_buffers();
+        state.update().unwrap();
+
         state.events.swap_buffers();
         assert_eq!(state.events.read::<TestEvent>(), &[TestEvent::Foo]);
     }
 
     #[test]
-    fn single_key_miss_first() {
-        let mut state = StateManager::new();
-        state.plugins.insert(KeybindPlugin::new());
+    fn pending_chord() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
         state.events.ensure_event::<TestEvent>();
-        state.events.dispatch(RegisterKeybind::single_key(
-            super::KeyBind {
-                modifiers: KeyModifiers::NONE,
-                key: KeyCode::Up,
-            },
+        state.events.dispatch(RegisterKeybind::chord(
+            [
+                super::KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Up,
+                },
+                super::KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Down,
+                },
+            ],
+            TestEvent::Foo,
+        ));
+
+        state.events.swap_buffers();
+        state.update().unwrap();
+        assert_eq!(
+            state
+                .plugins
+                .get::<KeybindPlugin>()
+                .unwrap()
+                .pending_chord(),
+            None
+        );
+
+        state.events.dispatch(KeydownEvent(KeyEvent {
+            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Up,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }));
+        state.events.swap_buffers();
+        state.update().unwrap();
+        assert_eq!(
+            state
+                .plugins
+                .get::<KeybindPlugin>()
+                .unwrap()
+                .pending_chord(),
+            Some((
+                vec![super::KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Up,
+                }],
+                vec![(
+                    super::KeyBind {
+                        modifiers: KeyModifiers::NONE,
+                        key: KeyCode::Down,
+                    },
+                    String::from("Foo")
+                )]
+            ))
+        );
+
+        state.events.dispatch(KeydownEvent(KeyEvent {
+            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Down,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }));
+        state.events.swap_buffers();
+        state.update().unwrap();
+        assert_eq!(
+            state
+                .plugins
+                .get::<KeybindPlugin>()
+                .unwrap()
+                .pending_chord(),
+            None
+        );
+    }
+
+    #[test]
+    fn chord_timeout() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.events.ensure_event::<TestEvent>();
+        state.events.dispatch(RegisterKeybind::chord(
+            [
+                super::KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Up,
+                },
+                super::KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Down,
+                },
+            ],
             TestEvent::Foo,
         ));
 
         state.events.swap_buffers();
         state.update().unwrap();
 
+        state.events.dispatch(KeydownEvent(KeyEvent {
+            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Up,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }));
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        state
+            .events
+            .dispatch(DeltaTimeEvent(Duration::from_millis(600)));
+        state.events.swap_buffers();
+        state.update().unwrap();
+        assert!(state
+            .plugins
+            .get::<KeybindPlugin>()
+            .unwrap()
+            .pending_chord()
+            .is_some());
+
+        state
+            .events
+            .dispatch(DeltaTimeEvent(Duration::from_millis(600)));
+        state.events.swap_buffers();
+        state.update().unwrap();
+        assert!(state
+            .plugins
+            .get::<KeybindPlugin>()
+            .unwrap()
+            .pending_chord()
+            .is_none());
+
         state.events.dispatch(KeydownEvent(KeyEvent {
             modifiers: KeyModifiers::NONE,
             code: KeyCode::Down,
@@ -520,8 +2539,34 @@ mod tests {
         }));
         state.events.swap_buffers();
         state.update().unwrap();
+
         state.events.swap_buffers();
         assert_eq!(state.events.read::<TestEvent>(), &[]);
+    }
+
+    #[test]
+    fn chord_timeout_fires_match() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.events.ensure_event::<TestEvent>();
+        let up = super::KeyBind {
+            modifiers: KeyModifiers::NONE,
+            key: KeyCode::Up,
+        };
+        let down = super::KeyBind {
+            modifiers: KeyModifiers::NONE,
+            key: KeyCode::Down,
+        };
+        state
+            .events
+            .dispatch(RegisterKeybind::single_key(up, TestEvent::Bar));
+        state
+            .events
+            .dispatch(RegisterKeybind::chord([up, down], TestEvent::Foo));
+        state.events.swap_buffers();
+        state.update().unwrap();
 
         state.events.dispatch(KeydownEvent(KeyEvent {
             modifiers: KeyModifiers::NONE,
@@ -531,15 +2576,30 @@ mod tests {
         }));
         state.events.swap_buffers();
         state.update().unwrap();
+        state.events.swap_buffers();
+        assert_eq!(state.events.read::<TestEvent>(), &[]);
 
+        state
+            .events
+            .dispatch(DeltaTimeEvent(Duration::from_millis(1200)));
         state.events.swap_buffers();
-        assert_eq!(state.events.read::<TestEvent>(), &[TestEvent::Foo]);
+        state.update().unwrap();
+        state.events.swap_buffers();
+        assert_eq!(state.events.read::<TestEvent>(), &[TestEvent::Bar]);
+        assert!(state
+            .plugins
+            .get::<KeybindPlugin>()
+            .unwrap()
+            .pending_chord()
+            .is_none());
     }
 
     #[test]
-    fn chord() {
-        let mut state = StateManager::new();
-        state.plugins.insert(KeybindPlugin::new());
+    fn chord_aborted() {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
         state.events.ensure_event::<TestEvent>();
         state.events.dispatch(RegisterKeybind::chord(
             [
@@ -558,6 +2618,17 @@ mod tests {
         state.events.swap_buffers();
         state.update().unwrap();
 
+        state.events.dispatch(KeydownEvent(KeyEvent {
+            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Left,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }));
+        state.events.swap_buffers();
+        state.update().unwrap();
+        state.events.swap_buffers();
+        assert_eq!(state.events.read::<ChordAborted>(), &[]);
+
         state.events.dispatch(KeydownEvent(KeyEvent {
             modifiers: KeyModifiers::NONE,
             code: KeyCode::Up,
@@ -569,7 +2640,7 @@ mod tests {
 
         state.events.dispatch(KeydownEvent(KeyEvent {
             modifiers: KeyModifiers::NONE,
-            code: KeyCode::Down,
+            code: KeyCode::Left,
             kind: KeyEventKind::Press,
             state: KeyEventState::NONE,
         }));
@@ -577,13 +2648,23 @@ mod tests {
         state.update().unwrap();
 
         state.events.swap_buffers();
-        assert_eq!(state.events.read::<TestEvent>(), &[TestEvent::Foo]);
+        assert_eq!(
+            state.events.read::<ChordAborted>(),
+            &[ChordAborted {
+                prefix: vec![super::KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Up,
+                }],
+            }]
+        );
     }
 
     #[test]
     fn chord_missed() {
-        let mut state = StateManager::new();
-        state.plugins.insert(KeybindPlugin::new());
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
         state.events.ensure_event::<TestEvent>();
         state.events.dispatch(RegisterKeybind::chord(
             [
@@ -637,8 +2718,10 @@ mod tests {
 
     #[test]
     fn chord_with_prefix_last() {
-        let mut state = StateManager::new();
-        state.plugins.insert(KeybindPlugin::new());
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
         state.events.ensure_event::<TestEvent>();
         state.events.dispatch(RegisterKeybind::chord(
             [
@@ -690,8 +2773,10 @@ mod tests {
 
     #[test]
     fn chord_with_prefix_prefix() {
-        let mut state = StateManager::new();
-        state.plugins.insert(KeybindPlugin::new());
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
         state.events.ensure_event::<TestEvent>();
         state.events.dispatch(RegisterKeybind::chord(
             [
@@ -741,10 +2826,74 @@ mod tests {
         assert_eq!(state.events.read::<TestEvent>(), &[TestEvent::Foo]);
     }
 
+    #[test]
+    fn chord_resolution() {
+        let up = super::KeyBind {
+            modifiers: KeyModifiers::NONE,
+            key: KeyCode::Up,
+        };
+        let down = super::KeyBind {
+            modifiers: KeyModifiers::NONE,
+            key: KeyCode::Down,
+        };
+        let left = super::KeyBind {
+            modifiers: KeyModifiers::NONE,
+            key: KeyCode::Left,
+        };
+
+        for (resolution, keys, expected) in [
+            ("Greedy", [up, down], [TestEvent::Bar].as_slice()),
+            ("Greedy", [up, left], &[TestEvent::Foo]),
+            ("Immediate", [up, down], &[TestEvent::Foo]),
+            ("Immediate", [up, left], &[TestEvent::Foo]),
+        ] {
+            let mut state = StateManager::with_persistence(Persistence::Memory);
+            state
+                .plugins
+                .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+            state
+                .events
+                .dispatch(arcane_settings::RegisterSettings(Box::new(
+                    KeybindSettings {
+                        chord_resolution: String::from(resolution),
+                        ..KeybindSettings::default()
+                    },
+                )));
+            state.events.ensure_event::<TestEvent>();
+            state
+                .events
+                .dispatch(RegisterKeybind::chord([up, down], TestEvent::Bar));
+            state
+                .events
+                .dispatch(RegisterKeybind::chord([up], TestEvent::Foo));
+            state.events.swap_buffers();
+            state.update().unwrap();
+            state.events.swap_buffers();
+            state.update().unwrap();
+
+            assert_eq!(
+                press_keys(&mut state, &keys[..1]),
+                if resolution == "Immediate" {
+                    &[TestEvent::Foo][..]
+                } else {
+                    &[]
+                },
+                "{resolution}"
+            );
+            let mut dispatched = press_keys(&mut state, &keys[1..]);
+            if resolution == "Immediate" {
+                dispatched.insert(0, TestEvent::Foo);
+            }
+            assert_eq!(dispatched, expected, "{resolution} {keys:?}");
+        }
+    }
+
     #[test]
     fn activeate_chord_then_non_bind() {
-        let mut state = StateManager::new();
-        state.plugins.insert(KeybindPlugin::new());
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
         state.events.ensure_event::<TestEvent>();
         state.events.dispatch(RegisterKeybind::chord(
             [
@@ -796,8 +2945,10 @@ mod tests {
 
     #[test]
     fn chord_interrupted_by_single_match() {
-        let mut state = StateManager::new();
-        state.plugins.insert(KeybindPlugin::new());
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
         state.events.ensure_event::<TestEvent>();
         state.events.dispatch(RegisterKeybind::chord(
             [
@@ -846,8 +2997,10 @@ mod tests {
 
     #[test]
     fn chord_interrupted_by_new_chord_match() {
-        let mut state = StateManager::new();
-        state.plugins.insert(KeybindPlugin::new());
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
         state.events.ensure_event::<TestEvent>();
         state.events.dispatch(RegisterKeybind::chord(
             [
@@ -912,8 +3065,10 @@ mod tests {
 
     #[test]
     fn chord_duplicate_keys() {
-        let mut state = StateManager::new();
-        state.plugins.insert(KeybindPlugin::new());
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
         state.events.ensure_event::<TestEvent>();
         state.events.dispatch(RegisterKeybind::chord(
             [
@@ -952,4 +3107,146 @@ mod tests {
         state.events.swap_buffers();
         assert_eq!(state.events.read::<TestEvent>(), &[TestEvent::Bar]);
     }
+
+    /// The keybind crossterm reports for pressing left shift on its own
+    const SHIFT: super::KeyBind = super::KeyBind {
+        modifiers: KeyModifiers::SHIFT,
+        key: KeyCode::Modifier(ModifierKeyCode::LeftShift),
+    };
+
+    /// Press each key in its own frame, returning the `TestEvent`s dispatched
+    fn press_keys(state: &mut StateManager, keys: &[super::KeyBind]) -> Vec<TestEvent> {
+        let mut dispatched = Vec::new();
+        for key in keys {
+            state
+                .events
+                .dispatch(KeydownEvent(KeyEvent::new(key.key, key.modifiers)));
+            state.events.swap_buffers();
+            state.update().unwrap();
+            state.events.swap_buffers();
+            dispatched.extend_from_slice(state.events.read::<TestEvent>());
+        }
+        dispatched
+    }
+
+    /// A state with double tapping shift bound to `TestEvent::Foo`
+    fn double_shift_state(allow_modifier_binds: bool) -> StateManager {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.events.ensure_event::<TestEvent>();
+        let register = RegisterKeybind::chord([SHIFT, SHIFT], TestEvent::Foo);
+        state.events.dispatch(if allow_modifier_binds {
+            register.allow_modifier_binds()
+        } else {
+            register
+        });
+        state.events.swap_buffers();
+        state.update().unwrap();
+        state
+    }
+
+    /// A state with `leader` set and `w` bound to `TestEvent::Foo` after it
+    fn leader_state(leader: &str) -> StateManager {
+        let mut state = StateManager::with_persistence(Persistence::Memory);
+        state
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        state.events.ensure_event::<TestEvent>();
+        state
+            .events
+            .dispatch(arcane_settings::RegisterSettings(Box::new(
+                KeybindSettings {
+                    leader: String::from(leader),
+                    ..KeybindSettings::default()
+                },
+            )));
+        state.events.dispatch(RegisterKeybind::with_leader(
+            ["w".parse::<super::KeyBind>().unwrap()],
+            TestEvent::Foo,
+        ));
+        state.events.swap_buffers();
+        state.update().unwrap();
+        state.events.swap_buffers();
+        state.update().unwrap();
+        state
+    }
+
+    #[test]
+    fn leader_bind() {
+        let mut state = leader_state("space");
+        let keys = Chord::parse("space w").unwrap();
+        assert_eq!(press_keys(&mut state, &keys.keys), [TestEvent::Foo]);
+    }
+
+    #[test]
+    fn leader_bind_without_leader() {
+        let mut state = leader_state("");
+        let w = "w".parse::<super::KeyBind>().unwrap();
+        assert_eq!(press_keys(&mut state, &[w]), []);
+        assert!(state
+            .plugins
+            .get::<KeybindPlugin>()
+            .unwrap()
+            .raw_bindings
+            .is_empty());
+    }
+
+    #[test]
+    fn change_leader() {
+        let mut state = leader_state("space");
+        state
+            .plugins
+            .get_mut::<arcane_settings::SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(KeybindSettings {
+                leader: String::from("ctrl+x"),
+                ..KeybindSettings::default()
+            }));
+        state.update().unwrap();
+
+        let old = Chord::parse("space w").unwrap();
+        assert_eq!(press_keys(&mut state, &old.keys), []);
+        let new = Chord::parse("ctrl+x w").unwrap();
+        assert_eq!(press_keys(&mut state, &new.keys), [TestEvent::Foo]);
+    }
+
+    #[test]
+    fn double_shift_chord() {
+        let mut state = double_shift_state(true);
+        assert_eq!(press_keys(&mut state, &[SHIFT, SHIFT]), [TestEvent::Foo]);
+    }
+
+    #[test]
+    fn modifier_binds_need_opt_in() {
+        let mut state = double_shift_state(false);
+        assert!(state
+            .plugins
+            .get::<KeybindPlugin>()
+            .unwrap()
+            .raw_bindings
+            .is_empty());
+        assert_eq!(press_keys(&mut state, &[SHIFT, SHIFT]), []);
+    }
+
+    #[test]
+    fn modifiers_do_not_break_chords() {
+        let mut state = double_shift_state(true);
+        let up = super::KeyBind {
+            modifiers: KeyModifiers::NONE,
+            key: KeyCode::Up,
+        };
+        state
+            .events
+            .dispatch(RegisterKeybind::chord([up, up], TestEvent::Bar));
+        state.events.swap_buffers();
+        state.update().unwrap();
+
+        assert_eq!(press_keys(&mut state, &[up, SHIFT, up]), [TestEvent::Bar]);
+        assert_eq!(press_keys(&mut state, &[SHIFT, up, up]), [TestEvent::Bar]);
+        state.events.swap_buffers();
+        assert_eq!(state.events.read::<ChordAborted>(), &[]);
+    }
 }
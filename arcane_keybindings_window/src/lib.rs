@@ -3,12 +3,13 @@
 
 use arcane_core::Result;
 use arcane_keybindings::{
+    ActionId,
     Chord,
     KeyBind,
     KeyCode,
     KeyModifiers,
+    KeyNotation,
     KeybindPlugin,
-    LockKeybindings,
     MenuEvent,
     RebindKeybind,
     RegisterKeybind,
@@ -20,8 +21,10 @@ use nucleo_matcher::{Matcher, Utf32Str};
 use ratatui::layout::{Constraint, Layout};
 use ratatui::style::{Color, Stylize};
 use ratatui::text::Line;
-use ratatui::widgets::{Row, Table};
+use ratatui::widgets::{Paragraph, Row, Table, Wrap};
 use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 /// The key sequence to mark the end of a key chord
 const CHORD_END: KeyBind = KeyBind {
@@ -29,6 +32,12 @@ const CHORD_END: KeyBind = KeyBind {
     key: KeyCode::Esc,
 };
 
+/// The key to abort recording a key chord
+const CHORD_CANCEL: KeyBind = KeyBind {
+    modifiers: KeyModifiers::NONE,
+    key: KeyCode::Esc,
+};
+
 /// Open the keybinding menu
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct OpenKeybindings;
@@ -81,13 +90,28 @@ impl arcane_core::Plugin for KeybindingWindowPlugin {
 
         Ok(())
     }
+
+    fn plugin_name(&self) -> &'static str {
+        "Keybindings Window"
+    }
+}
+
+/// A rebind onto a chord already used by other actions, waiting for confirmation
+#[derive(Clone)]
+struct PendingRebind {
+    /// The rebind to dispatch once confirmed
+    rebind: RebindKeybind,
+    /// The names of the actions already bound to the chord
+    conflicts: Vec<String>,
+    /// The keybind confirming the rebind
+    confirm_key: String,
 }
 
 /// A window to see and configure keybindings
 #[derive(Clone, Default)]
 struct KeybindWindow {
-    /// The keys visible in the window
-    visible_keys: Vec<(String, String)>,
+    /// The actions visible in the window, with their names
+    visible_keys: Vec<(ActionId, String)>,
     /// The fuzzy matcher
     fuzzy_matcher: Matcher,
     /// The search bar input
@@ -100,6 +124,51 @@ struct KeybindWindow {
     cursor_blink: f32,
     /// The currently being recorded keybind
     recording: Vec<KeyBind>,
+    /// A conflicting rebind waiting for `MenuEvent::AltSelect`
+    pending_rebind: Option<PendingRebind>,
+    /// How keybinds are shown, from the keybind settings
+    notation: KeyNotation,
+}
+
+impl KeybindWindow {
+    /// The end of the search input that fits in `width` columns.
+    ///
+    /// Whole graphemes are dropped from the start so the cursor stays in view.
+    fn visible_search(&self, width: usize) -> &str {
+        self.search
+            .grapheme_indices(true)
+            .map(|(index, _)| &self.search[index..])
+            .find(|tail| tail.width() <= width)
+            .unwrap_or_default()
+    }
+
+    /// The full name of the action in the focused row.
+    ///
+    /// The table column truncates long names, so this is shown in full below the table.
+    fn focused_action(&self) -> Option<&str> {
+        let index = self.focused_element.checked_sub(1)?;
+        self.visible_keys
+            .get(index)
+            .map(|(_, action)| action.as_str())
+    }
+}
+
+/// The names of the actions, other than the one being rebound, already bound to the target chord
+fn conflicting_actions(keybinds: &KeybindPlugin, rebind: &RebindKeybind) -> Vec<String> {
+    keybinds
+        .raw_bindings
+        .get(&rebind.bind)
+        .map(|actions| {
+            actions
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| {
+                    rebind.bind != rebind.action.chord || *index != rebind.action.index
+                })
+                .map(|(_, action)| format!("{action:?}"))
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 impl Window for KeybindWindow {
@@ -107,6 +176,10 @@ impl Window for KeybindWindow {
         String::from("Keybinds")
     }
 
+    fn wants_keyboard(&self) -> bool {
+        self.element_selected
+    }
+
     #[errors()]
     fn update(
         &mut self,
@@ -118,17 +191,26 @@ impl Window for KeybindWindow {
         let Some(keybinds) = plugins.get::<KeybindPlugin>() else {
             return Ok(());
         };
+        self.notation = keybinds.notation();
+        let notation = self.notation;
 
         if focused {
+            let was_selected = self.element_selected;
             let (reader, mut writer) = events.split();
             for event in reader.read::<MenuEvent>() {
+                if let Some(pending) = self.pending_rebind.take() {
+                    if *event == MenuEvent::AltSelect {
+                        writer.dispatch(pending.rebind);
+                        continue;
+                    }
+                }
+
                 match event {
                     MenuEvent::Select => {
                         if self.focused_element == 0 {
                             self.element_selected = !self.element_selected;
-                        } else if !self.element_selected {
+                        } else {
                             self.element_selected = true;
-                            writer.dispatch(arcane_keybindings::LockKeybindings(true));
                         }
                     }
                     MenuEvent::Down if !self.element_selected => {
@@ -137,6 +219,23 @@ impl Window for KeybindWindow {
                     MenuEvent::Up if !self.element_selected => {
                         self.focused_element = self.focused_element.saturating_sub(1);
                     }
+                    MenuEvent::Top if !self.element_selected => {
+                        self.focused_element = 0;
+                    }
+                    MenuEvent::Bottom if !self.element_selected => {
+                        self.focused_element = self.visible_keys.len();
+                    }
+                    MenuEvent::PageUp if !self.element_selected => {
+                        self.focused_element = self
+                            .focused_element
+                            .saturating_sub(arcane_keybindings::MENU_PAGE_SIZE);
+                    }
+                    MenuEvent::PageDown if !self.element_selected => {
+                        self.focused_element = self
+                            .focused_element
+                            .saturating_add(arcane_keybindings::MENU_PAGE_SIZE)
+                            .min(self.visible_keys.len());
+                    }
                     _ => (),
                 }
             }
@@ -151,23 +250,47 @@ impl Window for KeybindWindow {
                     if keybind.is_only_modifiers() {
                         continue;
                     }
-                    if keybind == CHORD_END {
+                    if keybind == CHORD_CANCEL {
+                        self.recording.clear();
+                        self.element_selected = false;
+                    } else if keybind == CHORD_END {
                         let chord = Chord {
                             keys: std::mem::take(&mut self.recording).into_boxed_slice(),
                         };
 
-                        if let Some((_, action)) = self
+                        // Ending a empty recording would bind the action to nothing
+                        let action = self
                             .visible_keys
                             .get(self.focused_element.saturating_sub(1))
-                        {
-                            writer.dispatch(RebindKeybind {
+                            .filter(|_| !chord.keys.is_empty());
+                        if let Some((action, _)) = action {
+                            let rebind = RebindKeybind {
                                 bind: chord,
-                                event: action.clone(),
-                            });
+                                action: action.clone(),
+                            };
+                            let conflicts = conflicting_actions(&keybinds, &rebind);
+                            if conflicts.is_empty() {
+                                writer.dispatch(rebind);
+                            } else {
+                                let confirm_key = keybinds
+                                    .all_actions()
+                                    .into_iter()
+                                    .find(|(_, action)| {
+                                        *action == format!("{:?}", MenuEvent::AltSelect)
+                                    })
+                                    .map_or_else(
+                                        || String::from("AltSelect"),
+                                        |(chord, _)| chord.render_as(notation),
+                                    );
+                                self.pending_rebind = Some(PendingRebind {
+                                    rebind,
+                                    conflicts,
+                                    confirm_key,
+                                });
+                            }
                         }
 
                         self.element_selected = false;
-                        writer.dispatch(LockKeybindings(false));
                     } else {
                         self.recording.push(keybind);
                     }
@@ -177,16 +300,29 @@ impl Window for KeybindWindow {
             if self.focused_element == 0 && self.element_selected {
                 for event in events.read::<arcane_core::KeydownEvent>() {
                     match event.0.code {
+                        // Keybinds are locked while typing, so the search bar handles its own exit
+                        KeyCode::Enter | KeyCode::Esc if was_selected => {
+                            self.element_selected = false;
+                            break;
+                        }
                         KeyCode::Char(c) => {
                             self.search.push(c);
                         }
                         KeyCode::Backspace => {
-                            self.search.pop();
+                            if let Some((index, _)) = self.search.grapheme_indices(true).next_back()
+                            {
+                                self.search.truncate(index);
+                            }
                         }
                         _ => {}
                     }
                 }
 
+                for event in events.read::<arcane_core::PasteEvent>() {
+                    self.search
+                        .extend(event.0.chars().filter(|c| !c.is_control()));
+                }
+
                 for event in events.read::<arcane_core::DeltaTimeEvent>() {
                     self.cursor_blink += event.0.as_secs_f32();
                     self.cursor_blink %= 1.0;
@@ -197,8 +333,17 @@ impl Window for KeybindWindow {
         let mut rows = keybinds
             .raw_bindings
             .iter()
-            .flat_map(|(key, action)| action.iter().map(move |action| (key, action)))
-            .map(|(key, action)| (key.render(), format!("{action:?}")))
+            .flat_map(|(chord, actions)| {
+                actions.iter().enumerate().map(|(index, action)| {
+                    (
+                        ActionId {
+                            chord: chord.clone(),
+                            index,
+                        },
+                        format!("{action:?}"),
+                    )
+                })
+            })
             .collect::<Vec<_>>();
 
         if self.search.is_empty() {
@@ -211,11 +356,16 @@ impl Window for KeybindWindow {
                 AtomKind::Fuzzy,
             );
             let mut what_is_this_for = Vec::new();
-            rows.sort_by_key(|(_, value)| {
-                pattern.score(
+            rows.sort_by_cached_key(|(id, value)| {
+                let action = pattern.score(
                     Utf32Str::new(value, &mut what_is_this_for),
                     &mut self.fuzzy_matcher,
-                )
+                );
+                let chord = pattern.score(
+                    Utf32Str::new(&id.chord.render_as(notation), &mut what_is_this_for),
+                    &mut self.fuzzy_matcher,
+                );
+                action.max(chord)
             });
             rows.reverse();
         }
@@ -228,9 +378,28 @@ impl Window for KeybindWindow {
         &self,
         frame: &mut ratatui::Frame,
         area: ratatui::prelude::Rect,
-        _plugins: &arcane_core::PluginStore,
+        plugins: &arcane_core::PluginStore,
     ) {
-        let area = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas::<2>(area);
+        let recording = self.focused_element != 0 && self.element_selected;
+        let header_height = if recording || self.pending_rebind.is_some() {
+            1
+        } else {
+            0
+        };
+        let detail = self.focused_action();
+        let detail_height = detail.map_or(0, |action| {
+            let lines = Line::from(action)
+                .width()
+                .div_ceil(usize::from(area.width.max(1)));
+            u16::try_from(lines).unwrap_or(u16::MAX)
+        });
+        let area = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(header_height),
+            Constraint::Fill(1),
+            Constraint::Length(detail_height),
+        ])
+        .areas::<4>(area);
 
         let cursor =
             if self.focused_element == 0 && self.element_selected && self.cursor_blink > 0.5 {
@@ -243,16 +412,49 @@ impl Window for KeybindWindow {
         } else if self.focused_element == 0 {
             Color::Black
         } else {
-            Color::Rgb(20, 20, 40)
+            arcane_theme::color(plugins, "selection_bg")
         };
-        let text = Line::from(vec![self.search.clone().into(), "_".fg(cursor)]).bg(background);
+        let search = self.visible_search(usize::from(area[0].width).saturating_sub(1));
+        let text = Line::from(vec![search.into(), "_".fg(cursor)]).bg(background);
         frame.render_widget(text, area[0]);
 
+        if recording {
+            let keys = if self.recording.is_empty() {
+                String::from("...")
+            } else {
+                self.recording
+                    .iter()
+                    .map(|key| key.render_as(self.notation))
+                    .intersperse(" ".into())
+                    .collect::<String>()
+            };
+            let header = Line::from(vec![
+                "Recording: ".into(),
+                keys.yellow(),
+                format!(
+                    " ({} to save, {} to cancel)",
+                    CHORD_END.render_as(self.notation),
+                    CHORD_CANCEL.render_as(self.notation)
+                )
+                .dark_gray(),
+            ]);
+            frame.render_widget(header, area[1]);
+        } else if let Some(pending) = &self.pending_rebind {
+            let warning = Line::from(format!(
+                "{} is already bound to {}, press {} to bind anyway",
+                pending.rebind.bind.render_as(self.notation),
+                pending.conflicts.join(", "),
+                pending.confirm_key
+            ))
+            .light_red();
+            frame.render_widget(warning, area[1]);
+        }
+
         let rows = self
             .visible_keys
             .iter()
             .enumerate()
-            .map(|(i, (key, action))| {
+            .map(|(i, (id, action))| {
                 let background =
                     if i.saturating_add(1) == self.focused_element && self.element_selected {
                         Color::DarkGray
@@ -262,18 +464,328 @@ impl Window for KeybindWindow {
                         Color::Reset
                     };
                 let key = if self.element_selected && i.saturating_add(1) == self.focused_element {
-                    self.recording
-                        .iter()
-                        .map(KeyBind::render)
-                        .intersperse(" ".into())
-                        .collect::<String>()
+                    Line::from(
+                        self.recording
+                            .iter()
+                            .map(|key| key.render_as(self.notation))
+                            .intersperse(" ".into())
+                            .collect::<String>(),
+                    )
+                    .yellow()
                 } else {
-                    key.clone()
+                    Line::from(id.chord.render_as(self.notation))
                 };
-                Row::new([key, action.clone()]).bg(background)
+                Row::new([key, Line::from(action.clone())]).bg(background)
             });
 
         let table = Table::new(rows, [Constraint::Fill(1), Constraint::Fill(1)]);
-        frame.render_widget(table, area[1]);
+        frame.render_widget(table, area[2]);
+
+        if let Some(action) = detail {
+            let detail = Paragraph::new(action)
+                .wrap(Wrap { trim: false })
+                .dark_gray();
+            frame.render_widget(detail, area[3]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arcane_core::{
+        EventManager,
+        KeydownEvent,
+        PasteEvent,
+        Persistence,
+        PluginStore,
+        StateManager,
+    };
+    use arcane_keybindings::{
+        ActionId,
+        Chord,
+        KeyBind,
+        KeyCode,
+        KeyModifiers,
+        KeybindPlugin,
+        MenuEvent,
+        RebindKeybind,
+    };
+    use arcane_windows::{Window, WindowEvent, WindowPlugin};
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::KeyEvent;
+    use ratatui::Terminal;
+
+    use super::{KeybindWindow, CHORD_END};
+
+    #[test]
+    fn paste_into_search() {
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        let mut window = KeybindWindow {
+            search: String::from("window "),
+            element_selected: true,
+            ..KeybindWindow::default()
+        };
+
+        events.dispatch(PasteEvent(String::from("new\ntab")));
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+
+        assert_eq!(window.search, "window newtab");
+    }
+
+    #[test]
+    fn search_wants_keyboard() {
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        let mut window = KeybindWindow::default();
+        assert!(!window.wants_keyboard());
+
+        events.dispatch(MenuEvent::Select);
+        events.dispatch(KeydownEvent(KeyEvent::from(KeyCode::Enter)));
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        assert!(window.wants_keyboard());
+
+        for key in [KeyCode::Char('t'), KeyCode::Char('a'), KeyCode::Enter] {
+            events.dispatch(KeydownEvent(KeyEvent::from(key)));
+        }
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        assert_eq!(window.search, "ta");
+        assert!(!window.wants_keyboard());
+    }
+
+    #[test]
+    fn typing_in_search_locks_keybinds() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states
+            .plugins
+            .insert(KeybindPlugin::with_persistence(Persistence::Memory));
+        states
+            .plugins
+            .insert(WindowPlugin::with_persistence(Persistence::Memory));
+        states.on_load().unwrap();
+
+        states.events.dispatch(WindowEvent::CreateWindow(
+            Box::new(KeybindWindow::default()),
+        ));
+        states.events.swap_buffers();
+        states.update().unwrap();
+        assert!(states.plugins.get::<KeybindPlugin>().unwrap().is_enabled());
+
+        // Select the search bar, the window manager locks the keybinds on a later frame
+        states.events.dispatch(MenuEvent::Select);
+        for _ in 0..3 {
+            states.events.swap_buffers();
+            states.update().unwrap();
+        }
+        assert!(!states.plugins.get::<KeybindPlugin>().unwrap().is_enabled());
+    }
+
+    #[test]
+    fn backspace_removes_grapheme() {
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        let mut window = KeybindWindow {
+            search: String::from("tab👨\u{200d}👩\u{200d}👧🇳🇴e\u{301}"),
+            element_selected: true,
+            ..KeybindWindow::default()
+        };
+
+        for expected in [
+            "tab👨\u{200d}👩\u{200d}👧🇳🇴",
+            "tab👨\u{200d}👩\u{200d}👧",
+            "tab",
+        ] {
+            events.dispatch(KeydownEvent(KeyEvent::from(KeyCode::Backspace)));
+            events.swap_buffers();
+            window.update(&mut events, &plugins, true, 0).unwrap();
+            events.swap_buffers();
+            assert_eq!(window.search, expected);
+        }
+    }
+
+    #[test]
+    fn visible_search_keeps_cursor_in_view() {
+        let window = KeybindWindow {
+            search: String::from("ab漢字"),
+            ..KeybindWindow::default()
+        };
+
+        assert_eq!(window.visible_search(6), "ab漢字");
+        assert_eq!(window.visible_search(5), "b漢字");
+        assert_eq!(window.visible_search(4), "漢字");
+        assert_eq!(window.visible_search(3), "字");
+        assert_eq!(window.visible_search(1), "");
+    }
+
+    #[test]
+    fn cancel_recording() {
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        let mut window = KeybindWindow {
+            focused_element: 1,
+            element_selected: true,
+            ..KeybindWindow::default()
+        };
+
+        events.dispatch(KeydownEvent(KeyEvent::new(
+            KeyCode::Char('a'),
+            KeyModifiers::NONE,
+        )));
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        assert_eq!(window.recording.len(), 1);
+
+        events.dispatch(KeydownEvent(KeyEvent::new(
+            KeyCode::Esc,
+            KeyModifiers::NONE,
+        )));
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        events.swap_buffers();
+
+        assert!(window.recording.is_empty());
+        assert!(!window.element_selected);
+        assert!(!window.wants_keyboard());
+        assert!(events.read::<RebindKeybind>().is_empty());
+    }
+
+    fn render(window: &KeybindWindow) -> String {
+        let plugins = PluginStore::new();
+        let mut terminal = Terminal::new(TestBackend::new(60, 5)).unwrap();
+        terminal
+            .draw(|frame| window.draw(frame, frame.area(), &plugins))
+            .unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn recording_preview() {
+        let stored = Chord::parse("ctrl+x").unwrap().render();
+        let mut window = KeybindWindow {
+            visible_keys: vec![(
+                ActionId {
+                    chord: Chord::parse("ctrl+x").unwrap(),
+                    index: 0,
+                },
+                String::from("Foo"),
+            )],
+            focused_element: 1,
+            ..KeybindWindow::default()
+        };
+        let screen = render(&window);
+        assert!(screen.contains(&stored));
+        assert!(!screen.contains("Recording"));
+
+        window.element_selected = true;
+        window.recording = vec![KeyBind {
+            modifiers: KeyModifiers::NONE,
+            key: KeyCode::Char('q'),
+        }];
+        let screen = render(&window);
+        assert!(screen.contains("Recording: q"));
+        assert!(screen.contains(&format!("{} to save", CHORD_END.render())));
+        assert!(!screen.contains(&stored));
+    }
+
+    #[test]
+    fn focused_action_shown_in_full() {
+        let action = "Window::Split(Direction::Horizontal, Size::Percent(50))";
+        let mut window = KeybindWindow {
+            visible_keys: vec![(
+                ActionId {
+                    chord: Chord::parse("ctrl+x").unwrap(),
+                    index: 0,
+                },
+                String::from(action),
+            )],
+            ..KeybindWindow::default()
+        };
+        assert_eq!(window.focused_action(), None);
+        assert!(!render(&window).contains(action));
+
+        window.focused_element = 1;
+        assert_eq!(window.focused_action(), Some(action));
+        assert!(render(&window).contains(action));
+    }
+
+    #[test]
+    fn search_matches_chords() {
+        let plugins = PluginStore::new();
+        {
+            let mut keybinds = plugins.get_mut::<KeybindPlugin>().unwrap();
+            keybinds.raw_bindings.insert(
+                Chord::parse("ctrl+x").unwrap(),
+                vec![Box::new(MenuEvent::Up)],
+            );
+            keybinds
+                .raw_bindings
+                .insert(Chord::parse("q").unwrap(), vec![Box::new(MenuEvent::Down)]);
+        }
+
+        let mut events = EventManager::new();
+        let mut window = KeybindWindow::default();
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        assert_eq!(window.visible_keys[0].1, "Menu::Down");
+
+        window.search = String::from("ctrl");
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        assert_eq!(window.visible_keys[0].1, "Menu::Up");
+    }
+
+    #[test]
+    fn conflicting_rebind_needs_confirmation() {
+        let plugins = PluginStore::new();
+        let taken = Chord::parse("ctrl+x").unwrap();
+        let own = Chord::parse("ctrl+y").unwrap();
+        {
+            let mut keybinds = plugins.get_mut::<KeybindPlugin>().unwrap();
+            keybinds
+                .raw_bindings
+                .insert(taken.clone(), vec![Box::new(MenuEvent::Up)]);
+            keybinds
+                .raw_bindings
+                .insert(own.clone(), vec![Box::new(MenuEvent::Down)]);
+        }
+
+        let mut events = EventManager::new();
+        let mut window = KeybindWindow::default();
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        assert_eq!(window.visible_keys[0].1, "Menu::Down");
+
+        window.focused_element = 1;
+        window.element_selected = true;
+        for key in taken.keys.iter().chain([&CHORD_END]) {
+            events.dispatch(KeydownEvent(KeyEvent::new(key.key, key.modifiers)));
+        }
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        events.swap_buffers();
+
+        assert!(events.read::<RebindKeybind>().is_empty());
+        assert!(window.pending_rebind.is_some());
+        assert!(render(&window).contains("already bound to Menu::Up"));
+
+        events.dispatch(MenuEvent::AltSelect);
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        events.swap_buffers();
+
+        let rebinds = events.read::<RebindKeybind>();
+        assert_eq!(rebinds.len(), 1);
+        assert!(rebinds
+            .iter()
+            .all(|rebind| rebind.bind == taken && rebind.action.chord == own));
+        assert!(window.pending_rebind.is_none());
     }
 }
@@ -3,10 +3,70 @@
 
 use std::any::Any;
 use std::cell::Ref;
+use std::collections::HashMap;
+use std::path::Path;
 
 use arcane_anymap::{dyn_clone, AnyMap};
-use arcane_core::{event, project_dirs, Level, Result};
+use arcane_core::{event, Level, NotificationEvent, Persistence, Result};
 use error_mancer::errors;
+use serde::{Deserialize, Serialize};
+
+/// The format version written to config files by this build
+pub const CONFIG_VERSION: u32 = 1;
+
+/// A config file tagged with the version of the format it was written in
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    /// The format version of `data`
+    pub version: u32,
+    /// The actual contents of the file
+    pub data: T,
+}
+
+impl<T> Versioned<T> {
+    /// Tag data with the current format version
+    pub fn current(data: T) -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            data,
+        }
+    }
+}
+
+/// A config file as found on disk, files from before versioning have no envelope
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConfigFile {
+    /// A file wrapped in a version envelope
+    Versioned(Versioned<serde_json::Value>),
+    /// An unversioned file, which is the same format as version 1
+    Legacy(serde_json::Value),
+}
+
+/// Read a config file, treating unversioned files as version 1
+pub fn read_versioned(
+    reader: impl std::io::Read,
+) -> serde_json::Result<Versioned<serde_json::Value>> {
+    versioned_from_value(serde_json::from_reader(reader)?)
+}
+
+/// Interpret already parsed config data, treating unversioned data as version 1
+pub fn versioned_from_value(
+    value: serde_json::Value,
+) -> serde_json::Result<Versioned<serde_json::Value>> {
+    let file = match serde_json::from_value(value)? {
+        ConfigFile::Versioned(file) => file,
+        ConfigFile::Legacy(data) => Versioned { version: 1, data },
+    };
+    if file.version > CONFIG_VERSION {
+        event!(
+            Level::WARN,
+            "Config file is version {}, newer than the supported {CONFIG_VERSION}",
+            file.version
+        );
+    }
+    Ok(file)
+}
 
 /// The value in enum
 #[derive(Debug)]
@@ -22,10 +82,23 @@ pub enum SettingsValue<'v> {
         /// Step
         step: i32,
     },
+    /// A fractional value
+    Float {
+        /// The actual value
+        value: &'v mut f64,
+        /// Minimum value
+        min: f64,
+        /// Maximum value
+        max: f64,
+        /// Step
+        step: f64,
+    },
     /// Multiple Kinds of Values
     Selection(&'v mut String, &'static [&'static str]),
     /// A toogle
     Toogle(&'v mut bool),
+    /// Free form text
+    Text(&'v mut String),
 }
 
 /// Common metadata for settings
@@ -33,6 +106,8 @@ pub enum SettingsValue<'v> {
 pub struct SettingsValueCommon<'v> {
     /// The name of the settings
     pub name: &'static str,
+    /// The section the value is grouped under in the settings window, `None` for no section
+    pub section: Option<&'static str>,
     /// A mutable reference to the data that needs changing
     pub value: SettingsValue<'v>,
 }
@@ -80,6 +155,20 @@ impl SettingsValueCommon<'_> {
                     *value = value.saturating_add(step).min(max).max(min);
                 }
             }
+            SettingsValue::Float {
+                value,
+                min,
+                max,
+                step,
+            } => {
+                if alt_mode {
+                    *value = (*value - step).min(max).max(min);
+                } else {
+                    *value = (*value + step).min(max).max(min);
+                }
+            }
+            // Text is edited in place by the settings window
+            SettingsValue::Text(_) => {}
         }
     }
 }
@@ -91,6 +180,10 @@ pub trait PluginSettings: Any + dyn_clone::DynClone {
     fn name(&self) -> &'static str;
     /// The values in the settings menu
     fn values(&mut self) -> Box<[SettingsValueCommon]>;
+    /// Help text for the value with the given name, shown when it is selected
+    fn description(&self, _value: &'static str) -> Option<&'static str> {
+        None
+    }
 }
 
 impl arcane_anymap::Downcast for dyn PluginSettings {
@@ -117,15 +210,38 @@ impl<P: PluginSettings> arcane_anymap::IntoBoxed<dyn PluginSettings> for (P,) {
 /// Register a new settings, thing
 pub struct RegisterSettings(pub Box<dyn PluginSettings>);
 
+/// Restore the settings of a plugin to the values it was registered with
+#[derive(Clone, Debug)]
+pub struct ResetSettings {
+    /// The name of the plugin settings, as returned by `PluginSettings::name`
+    pub plugin: &'static str,
+}
+
 /// The settings management plugin
 pub struct SettingsPlugin {
     /// The settings for each plugin
     pub settings: AnyMap<dyn PluginSettings>,
+    /// The default settings each plugin registered with
+    defaults: HashMap<&'static str, Box<dyn PluginSettings>>,
+    /// Settings loaded for plugins that never registered have been reported
+    ///
+    /// They are kept as they are and written back on save, so they survive a run without the plugin.
+    checked_orphans: bool,
+    /// Where `config.json` is loaded from and saved to
+    persistence: Persistence,
 }
 
 arcane_core::register_plugin!(SettingsPlugin);
 
 impl SettingsPlugin {
+    /// Create the plugin with the given persistence, `new` persists to the config directory
+    pub fn with_persistence(persistence: Persistence) -> Self {
+        Self {
+            persistence,
+            ..<Self as arcane_core::Plugin>::new()
+        }
+    }
+
     /// Get a settings object from the plugin
     pub fn get<S: PluginSettings>(&self) -> Option<&S> {
         self.settings.get::<S>()
@@ -156,31 +272,63 @@ pub fn get_settings<S: PluginSettings>(store: &arcane_core::PluginStore) -> Opti
 #[derive(Clone, Debug)]
 pub struct SaveSettings;
 
+/// Upgrade the settings list from an older format version to the current one
+fn migrate_settings(file: Versioned<serde_json::Value>) -> serde_json::Value {
+    // Version 1 is the current format, add a step here for each version bump
+    file.data
+}
+
+/// Load the settings entries from the given config file.
+///
+/// Returns `None` if the file does not exist, entries that fail to deserialize are skipped
+/// and the user is notified about them.
+#[errors(serde_json::Error)]
+fn load_settings(
+    config_path: &Path,
+    events: &mut arcane_core::EventManager,
+) -> std::result::Result<Option<Vec<Box<dyn PluginSettings>>>, _> {
+    let Ok(file) = std::fs::File::open(config_path) else {
+        return Ok(None);
+    };
+
+    let file = read_versioned(file)?;
+    let data: Vec<serde_json::Value> = serde_json::from_value(migrate_settings(file))?;
+    event!(Level::DEBUG, "loading {} settings", data.len());
+    let data = data
+        .into_iter()
+        .filter_map(|value| match serde_json::from_value(value) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                event!(Level::ERROR, "Invalid settings entry: {err}");
+                events.dispatch(NotificationEvent {
+                    level: Level::ERROR,
+                    message: format!("Skipped invalid settings entry in {config_path:?}: {err}"),
+                });
+                None
+            }
+        })
+        .collect();
+    Ok(Some(data))
+}
+
 #[errors]
 impl arcane_core::Plugin for SettingsPlugin {
     fn new() -> Self {
         Self {
             settings: AnyMap::new(),
+            defaults: HashMap::new(),
+            checked_orphans: false,
+            persistence: Persistence::config(),
         }
     }
 
-    #[errors(serde_json::Error)]
-    fn on_load(&mut self, _events: &mut arcane_core::EventManager) -> Result<()> {
-        if let Some(project_directory) = project_dirs() {
-            let config_path = project_directory.config_dir().join("config.json");
-            if let Ok(file) = std::fs::File::open(&config_path) {
-                // let data: Vec<Box<dyn PluginSettings>> = serde_json::de::from_reader(file)?;
-                // for value in data {
-                //     self.settings.insert_raw(value);
-                // }
-                let data: Vec<serde_json::Value> = serde_json::from_reader(file)?;
-                event!(Level::DEBUG, "loading {} settings", data.len());
+    #[errors(LoadSettingsError)]
+    fn on_load(&mut self, events: &mut arcane_core::EventManager) -> Result<()> {
+        if let Some(config_dir) = self.persistence.dir() {
+            let config_path = config_dir.join("config.json");
+            if let Some(data) = load_settings(&config_path, events)? {
                 for value in data {
-                    if let Ok(value) = serde_json::from_value(value) {
-                        self.settings.insert_raw(value);
-                    } else {
-                        event!(Level::ERROR, "Invalid settings entry!");
-                    }
+                    self.settings.insert_raw(value);
                 }
                 event!(Level::DEBUG, "Loaded {} settings", self.settings.len());
             }
@@ -198,13 +346,37 @@ impl arcane_core::Plugin for SettingsPlugin {
         for event in events.read::<RegisterSettings>() {
             let settings = dyn_clone::clone_box(&*event.0);
             self.settings.insert_raw_if_missing(settings);
+            self.defaults
+                .insert(event.0.name(), dyn_clone::clone_box(&*event.0));
+        }
+
+        // Plugins register their settings in `on_load`, so by the first update all have arrived
+        if !self.checked_orphans {
+            self.checked_orphans = true;
+            for settings in self.settings.iter() {
+                if !self.defaults.contains_key(settings.name()) {
+                    event!(
+                        Level::WARN,
+                        "Keeping settings for unknown plugin {}",
+                        settings.name()
+                    );
+                }
+            }
+        }
+
+        for event in events.read::<ResetSettings>() {
+            if let Some(default) = self.defaults.get(event.plugin) {
+                event!(Level::DEBUG, "Resetting {} settings", event.plugin);
+                self.settings.insert_raw(dyn_clone::clone_box(&**default));
+            } else {
+                event!(Level::WARN, "No defaults registered for {}", event.plugin);
+            }
         }
 
         if !events.read::<SaveSettings>().is_empty() {
-            let Some(project_directory) = project_dirs() else {
+            let Some(config_dir) = self.persistence.dir() else {
                 return Ok(());
             };
-            let config_dir = project_directory.config_dir();
 
             std::fs::create_dir_all(config_dir)?;
             let config_path = config_dir.join("config.json");
@@ -212,22 +384,43 @@ impl arcane_core::Plugin for SettingsPlugin {
             let file = std::fs::File::create(config_path)?;
 
             let settings = self.settings.iter().collect::<Vec<_>>();
-            serde_json::ser::to_writer_pretty(file, &settings)?;
+            serde_json::ser::to_writer_pretty(file, &Versioned::current(settings))?;
         }
 
         Ok(())
     }
+
+    fn plugin_name(&self) -> &'static str {
+        "Settings"
+    }
+
+    fn can_disable(&self) -> bool {
+        false
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{SettingsValue, SettingsValueCommon};
+    use arcane_core::{EventManager, NotificationEvent, Persistence, Plugin, PluginStore};
+    use serde::{Deserialize, Serialize};
+
+    use super::{
+        load_settings,
+        PluginSettings,
+        RegisterSettings,
+        ResetSettings,
+        SaveSettings,
+        SettingsPlugin,
+        SettingsValue,
+        SettingsValueCommon,
+    };
 
     #[test]
     fn select_one() {
         let mut value = String::from("1");
         let settings_value = SettingsValueCommon {
             name: "test",
+            section: None,
             value: SettingsValue::Selection(&mut value, &["1", "2"]),
         };
         settings_value.handle_settings_update(false);
@@ -240,6 +433,7 @@ mod tests {
         let mut value = String::from("2");
         let settings_value = SettingsValueCommon {
             name: "test",
+            section: None,
             value: SettingsValue::Selection(&mut value, &["1", "2", "3"]),
         };
         settings_value.handle_settings_update(true);
@@ -253,12 +447,14 @@ mod tests {
 
         let settings_value = SettingsValueCommon {
             name: "test",
+            section: None,
             value: SettingsValue::Selection(&mut value, &["1", "2"]),
         };
         settings_value.handle_settings_update(false);
 
         let settings_value = SettingsValueCommon {
             name: "test",
+            section: None,
             value: SettingsValue::Selection(&mut value, &["1", "2"]),
         };
         settings_value.handle_settings_update(false);
@@ -271,6 +467,7 @@ mod tests {
         let mut value = String::from("1");
         let settings_value = SettingsValueCommon {
             name: "test",
+            section: None,
             value: SettingsValue::Selection(&mut value, &["1", "2", "3"]),
         };
         settings_value.handle_settings_update(true);
@@ -284,6 +481,7 @@ mod tests {
 
         let settings_value = SettingsValueCommon {
             name: "test",
+            section: None,
             value: SettingsValue::Selection(&mut value, &["1", "2"]),
         };
         settings_value.handle_settings_update(false);
@@ -295,6 +493,7 @@ mod tests {
 
         let settings_value = SettingsValueCommon {
             name: "test",
+            section: None,
             value: SettingsValue::Toogle(&mut value),
         };
         settings_value.handle_settings_update(false);
@@ -308,6 +507,7 @@ mod tests {
 
         let settings_value = SettingsValueCommon {
             name: "test",
+            section: None,
             value: SettingsValue::Toogle(&mut value),
         };
         settings_value.handle_settings_update(false);
@@ -321,6 +521,7 @@ mod tests {
 
         let settings_value = SettingsValueCommon {
             name: "test",
+            section: None,
             value: SettingsValue::Integer {
                 value: &mut value,
                 min: 0,
@@ -339,6 +540,7 @@ mod tests {
 
         let settings_value = SettingsValueCommon {
             name: "test",
+            section: None,
             value: SettingsValue::Integer {
                 value: &mut value,
                 min: 0,
@@ -357,6 +559,7 @@ mod tests {
 
         let settings_value = SettingsValueCommon {
             name: "test",
+            section: None,
             value: SettingsValue::Integer {
                 value: &mut value,
                 min: 0,
@@ -375,6 +578,7 @@ mod tests {
 
         let settings_value = SettingsValueCommon {
             name: "test",
+            section: None,
             value: SettingsValue::Integer {
                 value: &mut value,
                 min: 0,
@@ -393,6 +597,7 @@ mod tests {
 
         let settings_value = SettingsValueCommon {
             name: "test",
+            section: None,
             value: SettingsValue::Integer {
                 value: &mut value,
                 min: 0,
@@ -404,4 +609,253 @@ mod tests {
 
         assert_eq!(value, 0);
     }
+
+    #[test]
+    fn text_unchanged() {
+        let mut value = String::from("hello");
+
+        let settings_value = SettingsValueCommon {
+            name: "test",
+            section: None,
+            value: SettingsValue::Text(&mut value),
+        };
+        settings_value.handle_settings_update(false);
+
+        assert_eq!(value, "hello");
+    }
+
+    #[test]
+    fn float() {
+        let mut value = 0.0;
+
+        let settings_value = SettingsValueCommon {
+            name: "test",
+            section: None,
+            value: SettingsValue::Float {
+                value: &mut value,
+                min: 0.0,
+                max: 1.0,
+                step: 0.25,
+            },
+        };
+        settings_value.handle_settings_update(false);
+
+        assert_eq!(value, 0.25);
+    }
+
+    #[test]
+    fn float_max() {
+        let mut value = 0.5;
+
+        let settings_value = SettingsValueCommon {
+            name: "test",
+            section: None,
+            value: SettingsValue::Float {
+                value: &mut value,
+                min: 0.0,
+                max: 1.0,
+                step: 0.75,
+            },
+        };
+        settings_value.handle_settings_update(false);
+
+        assert_eq!(value, 1.0);
+    }
+
+    #[test]
+    fn float_min() {
+        let mut value = 0.5;
+
+        let settings_value = SettingsValueCommon {
+            name: "test",
+            section: None,
+            value: SettingsValue::Float {
+                value: &mut value,
+                min: 0.0,
+                max: 1.0,
+                step: 0.75,
+            },
+        };
+        settings_value.handle_settings_update(true);
+
+        assert_eq!(value, 0.0);
+    }
+
+    #[derive(Clone, Serialize, Deserialize)]
+    struct FloatSettings {
+        speed: f64,
+    }
+
+    #[typetag::serde]
+    impl PluginSettings for FloatSettings {
+        fn name(&self) -> &'static str {
+            "Float"
+        }
+
+        fn values(&mut self) -> Box<[SettingsValueCommon]> {
+            Box::new([SettingsValueCommon {
+                name: "Speed",
+                section: None,
+                value: SettingsValue::Float {
+                    value: &mut self.speed,
+                    min: 0.0,
+                    max: 10.0,
+                    step: 0.1,
+                },
+            }])
+        }
+    }
+
+    #[test]
+    fn float_round_trip() {
+        let settings: Vec<Box<dyn PluginSettings>> = vec![Box::new(FloatSettings { speed: 1.3 })];
+        let json = serde_json::to_string(&settings).unwrap();
+
+        let mut loaded: Vec<Box<dyn PluginSettings>> = serde_json::from_str(&json).unwrap();
+        let loaded = (loaded.remove(0) as Box<dyn std::any::Any>)
+            .downcast::<FloatSettings>()
+            .unwrap();
+        assert_eq!(loaded.speed, 1.3);
+    }
+
+    #[derive(Clone, Serialize, Deserialize)]
+    struct ToggleSettings {
+        enabled: bool,
+    }
+
+    #[typetag::serde]
+    impl PluginSettings for ToggleSettings {
+        fn name(&self) -> &'static str {
+            "Toggle"
+        }
+
+        fn values(&mut self) -> Box<[SettingsValueCommon]> {
+            Box::new([SettingsValueCommon {
+                name: "Enabled",
+                section: None,
+                value: SettingsValue::Toogle(&mut self.enabled),
+            }])
+        }
+    }
+
+    #[test]
+    fn reset_to_default() {
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        let mut settings = SettingsPlugin::with_persistence(Persistence::Memory);
+
+        events.dispatch(RegisterSettings(Box::new(ToggleSettings { enabled: true })));
+        events.swap_buffers();
+        settings.update(&mut events, &plugins).unwrap();
+
+        let values = settings.sorted_settings().remove(0).values();
+        IntoIterator::into_iter(values)
+            .next()
+            .unwrap()
+            .handle_settings_update(false);
+        assert!(!settings.get::<ToggleSettings>().unwrap().enabled);
+
+        events.dispatch(ResetSettings { plugin: "Toggle" });
+        events.swap_buffers();
+        settings.update(&mut events, &plugins).unwrap();
+
+        assert!(settings.get::<ToggleSettings>().unwrap().enabled);
+    }
+
+    #[test]
+    fn orphans_kept() {
+        let config_dir = std::env::temp_dir().join("arcane_settings_orphans_kept");
+        let _ = std::fs::remove_dir_all(&config_dir);
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        let mut settings = SettingsPlugin::with_persistence(Persistence::Disk(config_dir.clone()));
+        settings
+            .settings
+            .insert_raw(Box::new(FloatSettings { speed: 1.0 }));
+        settings
+            .settings
+            .insert_raw(Box::new(ToggleSettings { enabled: false }));
+
+        events.dispatch(RegisterSettings(Box::new(ToggleSettings { enabled: true })));
+        events.dispatch(SaveSettings);
+        events.swap_buffers();
+        settings.update(&mut events, &plugins).unwrap();
+
+        assert!(settings.get::<FloatSettings>().is_some());
+        assert!(!settings.get::<ToggleSettings>().unwrap().enabled);
+        let config = std::fs::read_to_string(config_dir.join("config.json")).unwrap();
+        assert!(config.contains("FloatSettings"), "{config}");
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[test]
+    fn memory_persistence_writes_nothing() {
+        /// Register and save settings with the given persistence
+        fn save(persistence: Persistence) -> SettingsPlugin {
+            let mut settings = SettingsPlugin::with_persistence(persistence);
+            let plugins = PluginStore::new();
+            let mut events = EventManager::new();
+            settings.on_load(&mut events).unwrap();
+            events.dispatch(RegisterSettings(Box::new(ToggleSettings { enabled: true })));
+            events.dispatch(SaveSettings);
+            events.swap_buffers();
+            settings.update(&mut events, &plugins).unwrap();
+            settings
+        }
+
+        let config_dir = std::env::temp_dir().join("arcane_memory_persistence_settings");
+        let _ = std::fs::remove_dir_all(&config_dir);
+        save(Persistence::Disk(config_dir.clone()));
+        assert!(config_dir.join("config.json").exists());
+
+        std::fs::remove_dir_all(&config_dir).unwrap();
+        std::fs::create_dir(&config_dir).unwrap();
+        let settings = save(Persistence::Memory);
+        assert!(settings.get::<ToggleSettings>().is_some());
+        assert!(std::fs::read_dir(&config_dir).unwrap().next().is_none());
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    /// Load `contents` as a config file and return the speed of the `FloatSettings` in it
+    fn load_speed(name: &str, contents: &str) -> f64 {
+        let config_path = std::env::temp_dir().join(name);
+        std::fs::write(&config_path, contents).unwrap();
+        let mut loaded = load_settings(&config_path, &mut EventManager::new())
+            .unwrap()
+            .unwrap();
+        std::fs::remove_file(config_path).unwrap();
+
+        let loaded = (loaded.remove(0) as Box<dyn std::any::Any>)
+            .downcast::<FloatSettings>()
+            .unwrap();
+        loaded.speed
+    }
+
+    #[test]
+    fn load_versioned() {
+        let contents =
+            r#"{"version": 1, "data": [{"plugin": "FloatSettings", "settings": {"speed": 2.5}}]}"#;
+        assert_eq!(load_speed("arcane_load_versioned.json", contents), 2.5);
+    }
+
+    #[test]
+    fn load_legacy() {
+        let contents = r#"[{"plugin": "FloatSettings", "settings": {"speed": 2.5}}]"#;
+        assert_eq!(load_speed("arcane_load_legacy.json", contents), 2.5);
+    }
+
+    #[test]
+    fn invalid_entry_notifies() {
+        let config_path = std::env::temp_dir().join("arcane_invalid_entry_notifies.json");
+        let contents = r#"[{"plugin": "FloatSettings", "settings": {"speed": 2.5}}, {"plugin": "NoSuchSettings", "settings": {}}]"#;
+        std::fs::write(&config_path, contents).unwrap();
+        let mut events = EventManager::new();
+        let loaded = load_settings(&config_path, &mut events).unwrap().unwrap();
+        std::fs::remove_file(config_path).unwrap();
+
+        assert_eq!(loaded.len(), 1);
+        events.swap_buffers();
+        assert_eq!(events.read::<NotificationEvent>().len(), 1);
+    }
 }
@@ -1,10 +1,16 @@
 //! Manages app settings via window.
+use std::collections::HashSet;
+
 use arcane_core::Result;
 use arcane_keybindings::{KeyBind, KeyCode, KeyModifiers, MenuEvent, RegisterKeybind};
+use arcane_settings::{PluginSettings, SettingsValueCommon};
 use arcane_windows::{Window, WindowEvent};
 use error_mancer::errors;
-use ratatui::layout::{Constraint, Layout};
+use nucleo_matcher::pattern::{AtomKind, CaseMatching, Normalization, Pattern};
+use nucleo_matcher::{Matcher, Utf32Str};
+use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
 use ratatui::widgets::{Gauge, Paragraph, Tabs};
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +21,55 @@ struct OpenSettings;
 #[typetag::serde]
 impl arcane_keybindings::BindResult for OpenSettings {}
 
+/// Reset the settings of the selected plugin
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ResetSelectedSettings;
+
+#[typetag::serde]
+impl arcane_keybindings::BindResult for ResetSelectedSettings {}
+
+/// Write the settings changed in manual save mode
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SaveModifiedSettings;
+
+#[typetag::serde]
+impl arcane_keybindings::BindResult for SaveModifiedSettings {}
+
+/// Start typing a filter for the settings tabs
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct FilterSettings;
+
+#[typetag::serde]
+impl arcane_keybindings::BindResult for FilterSettings {}
+
+/// Settings for the settings window
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SettingsWindowSettings {
+    /// Only save changes when `SaveModifiedSettings` is pressed, instead of on every change
+    manual_save: bool,
+}
+
+#[typetag::serde]
+impl PluginSettings for SettingsWindowSettings {
+    fn name(&self) -> &'static str {
+        "Settings Window"
+    }
+
+    fn values(&mut self) -> Box<[SettingsValueCommon]> {
+        Box::new([SettingsValueCommon {
+            name: "manual_save",
+            section: None,
+            value: arcane_settings::SettingsValue::Toogle(&mut self.manual_save),
+        }])
+    }
+
+    fn description(&self, value: &'static str) -> Option<&'static str> {
+        (value == "manual_save")
+            .then_some("Only save changes when the save keybind is pressed, not on every change")
+    }
+}
+
 pub struct SettingsWindowPlugin;
 
 #[errors]
@@ -39,6 +94,39 @@ impl arcane_core::Plugin for SettingsWindowPlugin {
             ],
             OpenSettings,
         ));
+        events.ensure_event::<ResetSelectedSettings>();
+        events.dispatch(RegisterKeybind::single_key(
+            KeyBind {
+                modifiers: KeyModifiers::CONTROL,
+                key: KeyCode::Char('r'),
+            },
+            ResetSelectedSettings,
+        ));
+        events.ensure_event::<SaveModifiedSettings>();
+        events.dispatch(RegisterKeybind::chord(
+            [
+                KeyBind {
+                    modifiers: KeyModifiers::CONTROL,
+                    key: KeyCode::Char('p'),
+                },
+                KeyBind {
+                    modifiers: KeyModifiers::CONTROL,
+                    key: KeyCode::Char('s'),
+                },
+            ],
+            SaveModifiedSettings,
+        ));
+        events.ensure_event::<FilterSettings>();
+        events.dispatch(RegisterKeybind::single_key(
+            KeyBind {
+                modifiers: KeyModifiers::CONTROL,
+                key: KeyCode::Char('f'),
+            },
+            FilterSettings,
+        ));
+        events.dispatch(arcane_settings::RegisterSettings(Box::new(
+            SettingsWindowSettings::default(),
+        )));
 
         Ok(())
     }
@@ -56,23 +144,153 @@ impl arcane_core::Plugin for SettingsWindowPlugin {
 
         Ok(())
     }
+
+    fn plugin_name(&self) -> &'static str {
+        "Settings Window"
+    }
+}
+
+/// A row in the settings list
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Row {
+    /// The header of a section, selecting it collapses or expands the section
+    Header(&'static str),
+    /// The value at this index in `PluginSettings::values`
+    Value(usize),
 }
 
 /// The settings window
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 struct SettingsWindow {
     /// The selected tab
     selected_tab: usize,
     /// Selected row
     selected_row: usize,
+    /// The selected text value is being edited
+    editing: bool,
+    /// The timer for when to blink the cursor
+    cursor_blink: f32,
+    /// The collapsed sections, as plugin settings name and section
+    collapsed: HashSet<(&'static str, &'static str)>,
+    /// The filter is being typed
+    filtering: bool,
+    /// Only tabs fuzzy matching this are shown
+    filter: String,
+    /// The names of the tabs matching `filter`
+    matching_tabs: Vec<&'static str>,
+    /// The fuzzy matcher
+    fuzzy_matcher: Matcher,
+    /// Settings were changed in manual save mode and not saved yet
+    unsaved: bool,
 }
 
 impl SettingsWindow {
     /// Create default bs
-    const fn new() -> Self {
+    fn new() -> Self {
         Self {
             selected_tab: 0,
             selected_row: 0,
+            editing: false,
+            cursor_blink: 0.0,
+            collapsed: HashSet::new(),
+            filtering: false,
+            filter: String::new(),
+            matching_tabs: Vec::new(),
+            fuzzy_matcher: Matcher::default(),
+            unsaved: false,
+        }
+    }
+
+    /// Find the tabs matching the filter, keeping the selected tab in range
+    fn update_matching_tabs(&mut self, settings: Vec<&mut Box<dyn PluginSettings>>) {
+        let pattern = Pattern::new(
+            &self.filter,
+            CaseMatching::Smart,
+            Normalization::Smart,
+            AtomKind::Fuzzy,
+        );
+        let mut buffer = Vec::new();
+        self.matching_tabs = settings
+            .into_iter()
+            .map(|plugin| plugin.name())
+            .filter(|name| {
+                pattern
+                    .score(Utf32Str::new(name, &mut buffer), &mut self.fuzzy_matcher)
+                    .is_some()
+            })
+            .collect();
+
+        let last_tab = self.matching_tabs.len().saturating_sub(1);
+        if self.selected_tab > last_tab {
+            self.selected_tab = last_tab;
+            self.selected_row = 0;
+        }
+    }
+
+    /// The settings shown as tabs, those matching the filter
+    fn visible_tabs<'a>(
+        &self,
+        settings: Vec<&'a mut Box<dyn PluginSettings>>,
+    ) -> Vec<&'a mut Box<dyn PluginSettings>> {
+        if self.filter.is_empty() {
+            return settings;
+        }
+        settings
+            .into_iter()
+            .filter(|plugin| self.matching_tabs.contains(&plugin.name()))
+            .collect()
+    }
+
+    /// The rows to show for the values of a plugin.
+    ///
+    /// Values without a section are listed as is, the first value in a section lists the header
+    /// and every value sharing that section.
+    fn rows(&self, plugin: &'static str, values: &[SettingsValueCommon]) -> Vec<Row> {
+        let mut rows = Vec::new();
+        let mut seen = Vec::new();
+        for (index, value) in values.iter().enumerate() {
+            let Some(section) = value.section else {
+                rows.push(Row::Value(index));
+                continue;
+            };
+            if seen.contains(&section) {
+                continue;
+            }
+            seen.push(section);
+
+            rows.push(Row::Header(section));
+            if !self.collapsed.contains(&(plugin, section)) {
+                rows.extend(
+                    values
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, value)| value.section == Some(section))
+                        .map(|(index, _)| Row::Value(index)),
+                );
+            }
+        }
+        rows
+    }
+
+    /// The index of the last row in the selected tab
+    fn last_row(&self, settings: &mut arcane_settings::SettingsPlugin) -> usize {
+        self.visible_tabs(settings.sorted_settings())
+            .into_iter()
+            .nth(self.selected_tab)
+            .map(|plugin| self.rows(plugin.name(), &plugin.values()).len())
+            .unwrap_or_default()
+            .saturating_sub(1)
+    }
+
+    /// The index of the selected value, `None` when a header is selected
+    fn selected_value(
+        &self,
+        plugin: &'static str,
+        values: &[SettingsValueCommon],
+    ) -> Option<usize> {
+        match self.rows(plugin, values).get(self.selected_row) {
+            Some(Row::Value(index)) => Some(*index),
+            Some(Row::Header(_)) | None => None,
         }
     }
 }
@@ -81,7 +299,15 @@ arcane_core::register_plugin!(SettingsWindowPlugin);
 
 impl Window for SettingsWindow {
     fn name(&self) -> String {
-        String::from("Settings")
+        if self.unsaved {
+            String::from("Settings (modified)")
+        } else {
+            String::from("Settings")
+        }
+    }
+
+    fn wants_keyboard(&self) -> bool {
+        self.filtering || self.editing
     }
 
     #[errors()]
@@ -96,13 +322,44 @@ impl Window for SettingsWindow {
             return Ok(());
         }
 
+        let manual_save = arcane_settings::get_settings::<SettingsWindowSettings>(plugins)
+            .is_some_and(|settings| settings.manual_save);
         let Some(mut settings) = plugins.get_mut::<arcane_settings::SettingsPlugin>() else {
             return Ok(());
         };
 
+        if self.filtering {
+            for event in events.read::<arcane_core::KeydownEvent>() {
+                match event.0.code {
+                    KeyCode::Char(c) => self.filter.push(c),
+                    KeyCode::Backspace => {
+                        self.filter.pop();
+                    }
+                    KeyCode::Enter | KeyCode::Esc => self.filtering = false,
+                    _ => {}
+                }
+            }
+            for event in events.read::<arcane_core::DeltaTimeEvent>() {
+                self.cursor_blink += event.0.as_secs_f32();
+                self.cursor_blink %= 1.0;
+            }
+        }
+        // Checked after reading the keys, so the key pressed to start filtering is not typed
+        if !events.read::<FilterSettings>().is_empty() {
+            self.filtering = true;
+        }
+        self.update_matching_tabs(settings.sorted_settings());
+
+        // Keybindings are locked while editing, so editing is also ended by the keys directly
+        let was_editing = self.editing;
         let mut modified_settings = false;
         for event in events.read::<MenuEvent>() {
             match event {
+                MenuEvent::Select if self.editing => {
+                    self.editing = false;
+                    modified_settings = true;
+                }
+                _ if self.editing || self.filtering => (),
                 MenuEvent::Left => {
                     self.selected_tab = self.selected_tab.saturating_sub(1);
                     self.selected_row = 0;
@@ -111,32 +368,105 @@ impl Window for SettingsWindow {
                     self.selected_tab = self
                         .selected_tab
                         .saturating_add(1)
-                        .min(settings.settings.len().saturating_sub(1));
+                        .min(self.matching_tabs.len().saturating_sub(1));
                     self.selected_row = 0;
                 }
                 MenuEvent::Up => {
                     self.selected_row = self.selected_row.saturating_sub(1);
                 }
                 MenuEvent::Down => {
-                    self.selected_row = self.selected_row.saturating_add(1);
+                    self.selected_row = self
+                        .selected_row
+                        .saturating_add(1)
+                        .min(self.last_row(&mut settings));
                 }
                 MenuEvent::Select | MenuEvent::AltSelect => {
-                    let settings = settings.sorted_settings();
+                    let settings = self.visible_tabs(settings.sorted_settings());
                     let Some(select_setting) = settings.into_iter().nth(self.selected_tab) else {
                         return Ok(());
                     };
 
+                    let name = select_setting.name();
                     let values = select_setting.values();
-                    let Some(value) = IntoIterator::into_iter(values).nth(self.selected_row) else {
+                    let index = match self.rows(name, &values).get(self.selected_row) {
+                        Some(Row::Header(section)) => {
+                            let section = (name, *section);
+                            if !self.collapsed.remove(&section) {
+                                self.collapsed.insert(section);
+                            }
+                            continue;
+                        }
+                        Some(Row::Value(index)) => *index,
+                        None => return Ok(()),
+                    };
+                    let Some(value) = IntoIterator::into_iter(values).nth(index) else {
                         return Ok(());
                     };
-                    value.handle_settings_update(event == &MenuEvent::AltSelect);
-                    modified_settings = true;
+                    if matches!(value.value, arcane_settings::SettingsValue::Text(_)) {
+                        self.editing = true;
+                    } else {
+                        value.handle_settings_update(event == &MenuEvent::AltSelect);
+                        modified_settings = true;
+                    }
                 }
                 _ => (),
             }
         }
-        if modified_settings {
+
+        // Collapsing a section can leave the selection past the end
+        self.selected_row = self.selected_row.min(self.last_row(&mut settings));
+
+        if self.editing {
+            let settings = self.visible_tabs(settings.sorted_settings());
+            let Some(select_setting) = settings.into_iter().nth(self.selected_tab) else {
+                return Ok(());
+            };
+            let name = select_setting.name();
+            let values = select_setting.values();
+            let index = self.selected_value(name, &values);
+            if let Some(arcane_settings::SettingsValue::Text(text)) = index
+                .and_then(|index| IntoIterator::into_iter(values).nth(index))
+                .map(|value| value.value)
+            {
+                for event in events.read::<arcane_core::KeydownEvent>() {
+                    match event.0.code {
+                        KeyCode::Char(c) => {
+                            text.push(c);
+                        }
+                        KeyCode::Backspace => {
+                            text.pop();
+                        }
+                        KeyCode::Enter | KeyCode::Esc if was_editing => {
+                            self.editing = false;
+                            modified_settings = true;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            for event in events.read::<arcane_core::DeltaTimeEvent>() {
+                self.cursor_blink += event.0.as_secs_f32();
+                self.cursor_blink %= 1.0;
+            }
+        }
+        if !self.editing && !events.read::<ResetSelectedSettings>().is_empty() {
+            if let Some(selected) = self
+                .visible_tabs(settings.sorted_settings())
+                .into_iter()
+                .nth(self.selected_tab)
+            {
+                events.dispatch(arcane_settings::ResetSettings {
+                    plugin: selected.name(),
+                });
+                modified_settings = true;
+            }
+        }
+
+        self.unsaved |= modified_settings;
+        if self.unsaved && (!manual_save || !events.read::<SaveModifiedSettings>().is_empty()) {
+            self.unsaved = false;
             events.dispatch(arcane_settings::SaveSettings);
         }
 
@@ -149,16 +479,43 @@ impl Window for SettingsWindow {
         area: ratatui::prelude::Rect,
         plugins: &arcane_core::PluginStore,
     ) {
+        let selection_bg = arcane_theme::color(plugins, "selection_bg");
         let Some(mut settings) = plugins.get_mut::<arcane_settings::SettingsPlugin>() else {
             return;
         };
-        let plugins = settings.sorted_settings();
+        let plugins = self.visible_tabs(settings.sorted_settings());
         let names = plugins.iter().map(|plugin| plugin.name());
 
+        let filter_height = if self.filtering || !self.filter.is_empty() {
+            1
+        } else {
+            0
+        };
+        let [filter_area, area] =
+            Layout::vertical([Constraint::Length(filter_height), Constraint::Fill(1)])
+                .areas::<2>(area);
         let layout = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)])
             .spacing(1)
             .areas::<2>(area);
 
+        let cursor = if self.filtering && self.cursor_blink > 0.5 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let background = if self.filtering {
+            Color::DarkGray
+        } else {
+            Color::Black
+        };
+        let filter = Line::from(vec![
+            "/".blue(),
+            self.filter.as_str().into(),
+            "_".fg(cursor),
+        ])
+        .bg(background);
+        frame.render_widget(filter, filter_area);
+
         let tabs = Tabs::new(names)
             .select(self.selected_tab)
             .divider("|".blue())
@@ -169,23 +526,54 @@ impl Window for SettingsWindow {
         let Some(selected) = plugins.into_iter().nth(self.selected_tab) else {
             return;
         };
-        let values = selected.values();
+        let plugin = selected.name();
+        let (rows, selected_name) = {
+            let values = selected.values();
+            let selected_name = self
+                .selected_value(plugin, &values)
+                .and_then(|index| values.get(index))
+                .map(|value| value.name);
+            (self.rows(plugin, &values), selected_name)
+        };
+        let description = selected_name.and_then(|name| selected.description(name));
+        let mut values = IntoIterator::into_iter(selected.values())
+            .map(Some)
+            .collect::<Vec<_>>();
 
-        let mut constraints = vec![Constraint::Length(1); values.len()];
+        let mut constraints = vec![Constraint::Length(1); rows.len()];
         constraints.push(Constraint::Fill(1));
         let layout = Layout::vertical(constraints).spacing(1).split(layout[1]);
 
-        for (index, (value, area)) in IntoIterator::into_iter(values)
-            .zip(layout.iter())
-            .enumerate()
-        {
+        for (index, (row, area)) in rows.into_iter().zip(layout.iter()).enumerate() {
             let layout = Layout::horizontal(vec![Constraint::Length(30), Constraint::Fill(1)])
                 .areas::<2>(*area);
 
             if index == self.selected_row {
-                frame.render_widget(Paragraph::new("").bg(Color::Rgb(20, 20, 40)), *area);
+                frame.render_widget(Paragraph::new("").bg(selection_bg), *area);
+
+                if let Some(description) = description {
+                    let below = Rect::new(area.x, area.y.saturating_add(1), area.width, 1)
+                        .intersection(frame.area());
+                    frame.render_widget(description.dark_gray(), below);
+                }
             }
 
+            let value = match row {
+                Row::Header(section) => {
+                    let arrow = if self.collapsed.contains(&(plugin, section)) {
+                        "▶"
+                    } else {
+                        "▼"
+                    };
+                    frame.render_widget(format!("{arrow} {section}").bold().blue(), *area);
+                    continue;
+                }
+                Row::Value(value) => values.get_mut(value).and_then(Option::take),
+            };
+            let Some(value) = value else {
+                continue;
+            };
+
             frame.render_widget(value.name, layout[0]);
             match value.value {
                 arcane_settings::SettingsValue::Toogle(value) => {
@@ -214,7 +602,398 @@ impl Window for SettingsWindow {
                         .label(value.to_string());
                     frame.render_widget(bar, layout[1]);
                 }
+                arcane_settings::SettingsValue::Float {
+                    value, min, max, ..
+                } => {
+                    let ratio = ((*value - min) / (max - min)).clamp(0.0, 1.0);
+
+                    let bar = Gauge::default().ratio(ratio).label(format!("{value:.2}"));
+                    frame.render_widget(bar, layout[1]);
+                }
+                arcane_settings::SettingsValue::Text(text) => {
+                    let editing = self.editing && index == self.selected_row;
+                    let cursor = if editing && self.cursor_blink > 0.5 {
+                        Color::White
+                    } else {
+                        Color::Black
+                    };
+                    let background = if editing {
+                        Color::DarkGray
+                    } else {
+                        Color::Black
+                    };
+                    let text = Line::from(vec![text.clone().into(), "_".fg(cursor)]).bg(background);
+                    frame.render_widget(text, layout[1]);
+                }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use arcane_core::{EventManager, KeydownEvent, PluginStore};
+    use arcane_keybindings::{KeyCode, KeyModifiers, MenuEvent};
+    use arcane_settings::{
+        PluginSettings,
+        SaveSettings,
+        SettingsPlugin,
+        SettingsValue,
+        SettingsValueCommon,
+    };
+    use arcane_windows::Window;
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::KeyEvent;
+    use ratatui::Terminal;
+    use serde::{Deserialize, Serialize};
+
+    use super::{
+        FilterSettings,
+        Row,
+        SaveModifiedSettings,
+        SettingsWindow,
+        SettingsWindowSettings,
+    };
+
+    #[derive(Clone, Default, Serialize, Deserialize)]
+    struct TestSettings {
+        text: String,
+    }
+
+    #[typetag::serde]
+    impl PluginSettings for TestSettings {
+        fn name(&self) -> &'static str {
+            "Test"
+        }
+
+        fn values(&mut self) -> Box<[SettingsValueCommon]> {
+            Box::new([SettingsValueCommon {
+                name: "Text",
+                section: None,
+                value: SettingsValue::Text(&mut self.text),
+            }])
+        }
+
+        fn description(&self, value: &'static str) -> Option<&'static str> {
+            (value == "Text").then_some("Some help text")
+        }
+    }
+
+    fn plugins(text: &str) -> PluginStore {
+        let plugins = PluginStore::new();
+        plugins
+            .get_mut::<SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(TestSettings {
+                text: String::from(text),
+            }));
+        plugins
+    }
+
+    fn text(plugins: &PluginStore) -> String {
+        plugins
+            .get::<SettingsPlugin>()
+            .unwrap()
+            .get::<TestSettings>()
+            .unwrap()
+            .text
+            .clone()
+    }
+
+    fn send_menu(
+        window: &mut SettingsWindow,
+        events: &mut EventManager,
+        plugins: &PluginStore,
+        event: MenuEvent,
+    ) {
+        events.dispatch(event);
+        events.swap_buffers();
+        window.update(events, plugins, true, 0).unwrap();
+    }
+
+    fn send_key(
+        window: &mut SettingsWindow,
+        events: &mut EventManager,
+        plugins: &PluginStore,
+        key: KeyCode,
+    ) {
+        events.dispatch(KeydownEvent(KeyEvent::new(key, KeyModifiers::NONE)));
+        events.swap_buffers();
+        window.update(events, plugins, true, 0).unwrap();
+    }
+
+    #[test]
+    fn keys_ignored_when_not_editing() {
+        let plugins = plugins("abc");
+        let mut events = EventManager::new();
+        let mut window = SettingsWindow::new();
+
+        send_key(&mut window, &mut events, &plugins, KeyCode::Char('x'));
+        send_key(&mut window, &mut events, &plugins, KeyCode::Backspace);
+
+        assert!(!window.editing);
+        assert_eq!(text(&plugins), "abc");
+    }
+
+    #[test]
+    fn edit_text() {
+        let plugins = plugins("abc");
+        let mut events = EventManager::new();
+        let mut window = SettingsWindow::new();
+
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Select);
+        assert!(window.editing);
+
+        send_key(&mut window, &mut events, &plugins, KeyCode::Backspace);
+        send_key(&mut window, &mut events, &plugins, KeyCode::Char('x'));
+        send_key(&mut window, &mut events, &plugins, KeyCode::Left);
+        assert_eq!(text(&plugins), "abx");
+
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Down);
+        assert_eq!(window.selected_row, 0);
+
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Select);
+        assert!(!window.editing);
+
+        events.swap_buffers();
+        assert_eq!(events.read::<SaveSettings>().len(), 1);
+    }
+
+    #[test]
+    fn manual_save() {
+        let plugins = plugins("abc");
+        plugins
+            .get_mut::<SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(SettingsWindowSettings { manual_save: true }));
+        let mut events = EventManager::new();
+        let mut window = SettingsWindow::new();
+        // The tabs are sorted, so "Test" comes after "Settings Window"
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Right);
+
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Select);
+        send_key(&mut window, &mut events, &plugins, KeyCode::Char('x'));
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Select);
+        assert_eq!(text(&plugins), "abcx");
+        events.swap_buffers();
+        assert!(events.read::<SaveSettings>().is_empty());
+        assert_eq!(window.name(), "Settings (modified)");
+
+        events.dispatch(SaveModifiedSettings);
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        events.swap_buffers();
+        assert_eq!(events.read::<SaveSettings>().len(), 1);
+        assert_eq!(window.name(), "Settings");
+    }
+
+    #[test]
+    fn editing_wants_keyboard() {
+        let plugins = plugins("abc");
+        let mut events = EventManager::new();
+        let mut window = SettingsWindow::new();
+
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Select);
+        assert!(window.wants_keyboard());
+
+        send_key(&mut window, &mut events, &plugins, KeyCode::Char('j'));
+        send_key(&mut window, &mut events, &plugins, KeyCode::Enter);
+        assert!(!window.wants_keyboard());
+        assert_eq!(text(&plugins), "abcj");
+
+        events.swap_buffers();
+        assert_eq!(events.read::<SaveSettings>().len(), 1);
+    }
+
+    #[test]
+    fn description_shown() {
+        let plugins = plugins("abc");
+        let window = SettingsWindow::new();
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 6)).unwrap();
+        terminal
+            .draw(|frame| window.draw(frame, frame.area(), &plugins))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let screen = buffer
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        assert!(screen.contains("Some help text"));
+    }
+
+    #[derive(Clone, Default, Serialize, Deserialize)]
+    struct SectionSettings {
+        first: bool,
+        loose: bool,
+        second: bool,
+    }
+
+    #[typetag::serde]
+    impl PluginSettings for SectionSettings {
+        fn name(&self) -> &'static str {
+            "Sections"
+        }
+
+        fn values(&mut self) -> Box<[SettingsValueCommon]> {
+            Box::new([
+                SettingsValueCommon {
+                    name: "First",
+                    section: Some("Group"),
+                    value: SettingsValue::Toogle(&mut self.first),
+                },
+                SettingsValueCommon {
+                    name: "Loose",
+                    section: None,
+                    value: SettingsValue::Toogle(&mut self.loose),
+                },
+                SettingsValueCommon {
+                    name: "Second",
+                    section: Some("Group"),
+                    value: SettingsValue::Toogle(&mut self.second),
+                },
+            ])
+        }
+    }
+
+    /// Draw the window and return the first column of text on each row
+    fn draw_rows(window: &SettingsWindow, plugins: &PluginStore) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+        terminal
+            .draw(|frame| window.draw(frame, frame.area(), plugins))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        (0..10)
+            .map(|y| {
+                (0..30)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim()
+                    .to_owned()
+            })
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn sections_grouped_under_one_header() {
+        let plugins = PluginStore::new();
+        plugins
+            .get_mut::<SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(SectionSettings::default()));
+        let mut events = EventManager::new();
+        let mut window = SettingsWindow::new();
+
+        assert_eq!(
+            draw_rows(&window, &plugins),
+            ["Sections", "▼ Group", "First", "Second", "Loose"]
+        );
+
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Select);
+        assert_eq!(
+            draw_rows(&window, &plugins),
+            ["Sections", "▶ Group", "Loose"]
+        );
+
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Down);
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Select);
+        let settings = plugins.get::<SettingsPlugin>().unwrap();
+        let settings = settings.get::<SectionSettings>().unwrap();
+        assert!(settings.loose);
+        assert!(!settings.first);
+    }
+
+    #[test]
+    fn down_stops_at_last_row() {
+        let plugins = PluginStore::new();
+        plugins
+            .get_mut::<SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(SectionSettings::default()));
+        let mut events = EventManager::new();
+        let mut window = SettingsWindow::new();
+
+        for _ in 0..6 {
+            send_menu(&mut window, &mut events, &plugins, MenuEvent::Down);
+        }
+        assert_eq!(window.selected_row, 3);
+
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Select);
+        let settings = plugins.get::<SettingsPlugin>().unwrap();
+        assert!(settings.get::<SectionSettings>().unwrap().loose);
+    }
+
+    #[test]
+    fn collapse_clamps_selection() {
+        let plugins = PluginStore::new();
+        plugins
+            .get_mut::<SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(SectionSettings::default()));
+        let mut events = EventManager::new();
+        let mut window = SettingsWindow::new();
+
+        window.selected_row = 3;
+        window.collapsed.insert(("Sections", "Group"));
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        assert_eq!(window.selected_row, 1);
+    }
+
+    #[test]
+    fn filter_tabs() {
+        let plugins = plugins("abc");
+        plugins
+            .get_mut::<SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(SectionSettings::default()));
+        let mut events = EventManager::new();
+        let mut window = SettingsWindow::new();
+
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Right);
+        assert_eq!(window.selected_tab, 1);
+
+        events.dispatch(FilterSettings);
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 0).unwrap();
+        assert!(window.wants_keyboard());
+        for c in "sec".chars() {
+            send_key(&mut window, &mut events, &plugins, KeyCode::Char(c));
+        }
+        send_key(&mut window, &mut events, &plugins, KeyCode::Enter);
+        assert!(!window.wants_keyboard());
+        assert_eq!(window.matching_tabs, ["Sections"]);
+        assert_eq!(window.selected_tab, 0);
+
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Right);
+        assert_eq!(window.selected_tab, 0);
+        assert_eq!(
+            draw_rows(&window, &plugins),
+            ["/sec_", "Sections", "▼ Group", "First", "Second", "Loose"]
+        );
+
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Down);
+        send_menu(&mut window, &mut events, &plugins, MenuEvent::Select);
+        let settings = plugins.get::<SettingsPlugin>().unwrap();
+        assert!(settings.get::<SectionSettings>().unwrap().first);
+    }
+
+    #[test]
+    fn flat_without_sections() {
+        let plugins = plugins("abc");
+        let mut settings = plugins.get_mut::<SettingsPlugin>().unwrap();
+        let test = settings.sorted_settings().remove(0);
+        let window = SettingsWindow::new();
+        assert_eq!(window.rows(test.name(), &test.values()), [Row::Value(0)]);
+    }
+}
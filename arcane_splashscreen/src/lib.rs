@@ -1,32 +1,139 @@
 //! Splash screen, intro!
 
-use arcane_core::Result;
+use std::time::Duration;
+
+use arcane_core::{Result, Timer};
 use arcane_windows::{Window, WindowEvent, WindowID};
 use error_mancer::errors;
 use ratatui::layout::{Constraint, Layout};
 use ratatui::style::{Color, Stylize};
 use ratatui::text::{Line, Text};
 use ratatui::widgets::Paragraph;
+use serde::{Deserialize, Serialize};
+
+/// Settings for the splash screen
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct SplashSettings {
+    /// Show the splash screen on startup
+    enabled: bool,
+    /// The colors of the splash screen
+    theme: String,
+}
+
+impl Default for SplashSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            theme: String::from("Arcane"),
+        }
+    }
+}
+
+impl SplashSettings {
+    /// The logo and accent colors of the selected theme
+    fn colors(&self) -> (Color, Color) {
+        match self.theme.as_str() {
+            "Ember" => (Color::Red, Color::Yellow),
+            "Frost" => (Color::Cyan, Color::LightBlue),
+            _ => (Color::Magenta, Color::Magenta),
+        }
+    }
+}
+
+#[typetag::serde]
+impl arcane_settings::PluginSettings for SplashSettings {
+    fn name(&self) -> &'static str {
+        "Splash Screen"
+    }
+
+    fn values(&mut self) -> Box<[arcane_settings::SettingsValueCommon]> {
+        Box::new([
+            arcane_settings::SettingsValueCommon {
+                name: "enabled",
+                section: None,
+                value: arcane_settings::SettingsValue::Toogle(&mut self.enabled),
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "theme",
+                section: None,
+                value: arcane_settings::SettingsValue::Selection(
+                    &mut self.theme,
+                    &["Arcane", "Ember", "Frost"],
+                ),
+            },
+        ])
+    }
+}
 
 /// The plugin
-pub struct SplashScreenPlugin;
+pub struct SplashScreenPlugin {
+    /// Has the splash screen been opened, or skipped, already
+    shown: bool,
+}
 
 arcane_core::register_plugin!(SplashScreenPlugin);
 
 impl arcane_core::Plugin for SplashScreenPlugin {
     fn new() -> Self {
-        Self
+        Self { shown: false }
     }
+
     #[errors]
     fn on_load(&mut self, events: &mut arcane_core::EventManager) -> Result<()> {
-        events.dispatch(WindowEvent::CreateWindow(Box::new(SplashScreenWindow)));
+        events.dispatch(arcane_settings::RegisterSettings(Box::new(
+            SplashSettings::default(),
+        )));
+        Ok(())
+    }
+
+    // The settings are not readable during `on_load`, so the window is opened on the first update
+    // instead. Saved settings are loaded by the settings plugin's `on_load`, so they are available
+    // here even before our registration has been processed.
+    #[errors]
+    fn update(
+        &mut self,
+        events: &mut arcane_core::EventManager,
+        plugins: &arcane_core::PluginStore,
+    ) -> Result<()> {
+        if self.shown {
+            return Ok(());
+        }
+        self.shown = true;
+
+        let enabled = arcane_settings::get_settings::<SplashSettings>(plugins)
+            .is_none_or(|settings| settings.enabled);
+        if enabled {
+            events.dispatch(WindowEvent::CreateWindow(Box::new(
+                SplashScreenWindow::new(),
+            )));
+        }
         Ok(())
     }
+
+    fn plugin_name(&self) -> &'static str {
+        "Splash Screen"
+    }
 }
 
+/// How long the splash screen is shown if nothing else closes it
+const SPLASH_TIMEOUT: Duration = Duration::from_secs(3);
+
 /// The window showing the splash screen
 #[derive(Clone, Copy)]
-struct SplashScreenWindow;
+struct SplashScreenWindow {
+    /// Fires when the splash screen should close itself
+    timeout: Timer,
+}
+
+impl SplashScreenWindow {
+    /// Create a splash screen closing after `SPLASH_TIMEOUT`
+    const fn new() -> Self {
+        Self {
+            timeout: Timer::new(SPLASH_TIMEOUT),
+        }
+    }
+}
 
 impl Window for SplashScreenWindow {
     fn name(&self) -> String {
@@ -42,10 +149,18 @@ impl Window for SplashScreenWindow {
         id: WindowID,
     ) -> Result<()> {
         let (reader, mut writer) = events.split();
-        for event in reader.read::<WindowEvent>() {
-            if let WindowEvent::CreateWindow(_) = event {
-                writer.dispatch(WindowEvent::CloseWindow(id));
-            }
+        let window_created = reader
+            .read::<WindowEvent>()
+            .iter()
+            .any(|event| matches!(event, WindowEvent::CreateWindow(_)));
+        let key_pressed = !reader.read::<arcane_core::KeydownEvent>().is_empty();
+        let timed_out = reader
+            .read::<arcane_core::DeltaTimeEvent>()
+            .iter()
+            .any(|event| self.timeout.tick(event.0));
+
+        if window_created || key_pressed || timed_out {
+            writer.dispatch(WindowEvent::CloseWindow(id));
         }
 
         Ok(())
@@ -55,7 +170,7 @@ impl Window for SplashScreenWindow {
         &self,
         frame: &mut ratatui::Frame,
         area: ratatui::prelude::Rect,
-        _plugins: &arcane_core::PluginStore,
+        plugins: &arcane_core::PluginStore,
     ) {
         /// ASCII art of the logo
         const LOGO: &str = "
@@ -78,14 +193,17 @@ impl Window for SplashScreenWindow {
             Constraint::Fill(1),
         ])
         .areas::<4>(area);
-        let logo = Paragraph::new(LOGO).magenta().centered();
+        let (logo_color, accent) = arcane_settings::get_settings::<SplashSettings>(plugins)
+            .map(|settings| settings.colors())
+            .unwrap_or_else(|| SplashSettings::default().colors());
+        let logo = Paragraph::new(LOGO).fg(logo_color).centered();
         let gray = Color::Rgb(80, 80, 100);
         let splash = Paragraph::new(Text::from(vec![
             Line::from(vec![
                 "The ".white(),
                 "🔥blazingly🔥 fast".red().bold(),
                 " terminal editor by ".white(),
-                "Viv\n".magenta().bold(),
+                "Viv\n".fg(accent).bold(),
             ]),
             Line::from(vec![
                 "(We assume because we use ".fg(gray),
@@ -99,3 +217,74 @@ impl Window for SplashScreenWindow {
         frame.render_widget(splash, layout[2]);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use arcane_core::{DeltaTimeEvent, EventManager, Plugin, PluginStore};
+    use arcane_settings::SettingsPlugin;
+    use arcane_windows::{Window, WindowEvent};
+
+    use super::{SplashScreenPlugin, SplashScreenWindow, SplashSettings};
+
+    fn closed(events: &EventManager) -> bool {
+        events
+            .read::<WindowEvent>()
+            .iter()
+            .any(|event| matches!(event, WindowEvent::CloseWindow(3)))
+    }
+
+    #[test]
+    fn timeout() {
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        let mut window = SplashScreenWindow::new();
+
+        events.dispatch(DeltaTimeEvent(Duration::from_secs(2)));
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 3).unwrap();
+        events.swap_buffers();
+        assert!(!closed(&events));
+
+        events.dispatch(DeltaTimeEvent(Duration::from_secs(2)));
+        events.swap_buffers();
+        window.update(&mut events, &plugins, true, 3).unwrap();
+        events.swap_buffers();
+        assert!(closed(&events));
+    }
+
+    fn opens_window(settings: SplashSettings) -> bool {
+        let plugins = PluginStore::new();
+        plugins
+            .get_mut::<SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(settings));
+
+        let mut events = EventManager::new();
+        let mut plugin = SplashScreenPlugin::new();
+        plugin.on_load(&mut events).unwrap();
+        events.swap_buffers();
+        plugin.update(&mut events, &plugins).unwrap();
+        events.swap_buffers();
+
+        events
+            .read::<WindowEvent>()
+            .iter()
+            .any(|event| matches!(event, WindowEvent::CreateWindow(_)))
+    }
+
+    #[test]
+    fn enabled() {
+        assert!(opens_window(SplashSettings::default()));
+    }
+
+    #[test]
+    fn disabled() {
+        assert!(!opens_window(SplashSettings {
+            enabled: false,
+            ..SplashSettings::default()
+        }));
+    }
+}
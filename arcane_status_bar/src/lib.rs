@@ -0,0 +1,180 @@
+//! A one line status bar that plugins can add segments to
+
+use arcane_core::{EventManager, Plugin, PluginStore, Result};
+use error_mancer::errors;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear};
+
+/// The separator drawn between segments on the same side
+const SEPARATOR: &str = " │ ";
+
+/// Which side of the status bar a segment is drawn on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// Left aligned
+    Left,
+    /// Right aligned
+    Right,
+}
+
+/// A piece of text to show in the status bar
+///
+/// Segments only live for one frame, so plugins should dispatch them every update.
+/// Higher priority segments are drawn closer to the edge of their side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusSegment {
+    /// The text to show
+    pub text: String,
+    /// Higher priorities are drawn closer to the edge
+    pub priority: i32,
+    /// The side of the bar to draw on
+    pub side: Side,
+}
+
+/// Collects `StatusSegment`s and draws them at the bottom of the screen
+#[derive(Debug)]
+pub struct StatusBarPlugin {
+    /// The segments dispatched last frame, sorted in draw order
+    segments: Vec<StatusSegment>,
+}
+
+arcane_core::register_plugin!(StatusBarPlugin);
+
+impl StatusBarPlugin {
+    /// The text of the segments on the given side, in the order they are drawn from left to right
+    fn side_text(&self, side: Side) -> String {
+        let texts = self
+            .segments
+            .iter()
+            .filter(|segment| segment.side == side)
+            .map(|segment| segment.text.as_str());
+        match side {
+            Side::Left => texts.collect::<Vec<_>>().join(SEPARATOR),
+            Side::Right => texts.rev().collect::<Vec<_>>().join(SEPARATOR),
+        }
+    }
+}
+
+impl Plugin for StatusBarPlugin {
+    fn new() -> Self {
+        Self {
+            segments: Vec::new(),
+        }
+    }
+
+    #[errors]
+    fn on_load(&mut self, events: &mut EventManager) -> Result<()> {
+        events.ensure_event::<StatusSegment>();
+        Ok(())
+    }
+
+    #[errors]
+    fn update(&mut self, events: &mut EventManager, _plugins: &PluginStore) -> Result<()> {
+        self.segments = events.read::<StatusSegment>().to_vec();
+        self.segments
+            .sort_by_key(|segment| std::cmp::Reverse(segment.priority));
+        Ok(())
+    }
+
+    fn z_index(&self) -> u32 {
+        u32::MAX.saturating_sub(1)
+    }
+
+    fn draw(
+        &self,
+        frame: &mut ratatui::Frame,
+        area: ratatui::prelude::Rect,
+        _plugins: &PluginStore,
+    ) {
+        if self.segments.is_empty() {
+            return;
+        }
+
+        let area =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas::<2>(area)[1];
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(Block::new().bg(Color::DarkGray), area);
+        frame.render_widget(
+            Line::from(format!(" {} ", self.side_text(Side::Left))).white(),
+            area,
+        );
+        frame.render_widget(
+            Line::from(format!(" {} ", self.side_text(Side::Right)))
+                .right_aligned()
+                .white(),
+            area,
+        );
+    }
+
+    fn plugin_name(&self) -> &'static str {
+        "Status Bar"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arcane_core::{EventManager, Plugin, PluginStore};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use super::{Side, StatusBarPlugin, StatusSegment};
+
+    #[test]
+    fn segments_render_on_their_side() {
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        let mut bar = StatusBarPlugin::new();
+        bar.on_load(&mut events).unwrap();
+
+        events.dispatch(StatusSegment {
+            text: String::from("right"),
+            priority: 0,
+            side: Side::Right,
+        });
+        events.dispatch(StatusSegment {
+            text: String::from("inner"),
+            priority: 0,
+            side: Side::Left,
+        });
+        events.dispatch(StatusSegment {
+            text: String::from("edge"),
+            priority: 10,
+            side: Side::Left,
+        });
+        events.swap_buffers();
+        bar.update(&mut events, &plugins).unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(30, 3)).unwrap();
+        terminal
+            .draw(|frame| bar.draw(frame, frame.area(), &plugins))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let bottom = (0..30).map(|x| buffer[(x, 2)].symbol()).collect::<String>();
+
+        assert_eq!(bottom, " edge │ inner           right ");
+    }
+
+    #[test]
+    fn segments_last_one_frame() {
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        let mut bar = StatusBarPlugin::new();
+        bar.on_load(&mut events).unwrap();
+
+        events.dispatch(StatusSegment {
+            text: String::from("once"),
+            priority: 0,
+            side: Side::Left,
+        });
+        events.swap_buffers();
+        bar.update(&mut events, &plugins).unwrap();
+        assert_eq!(bar.segments.len(), 1);
+
+        events.swap_buffers();
+        bar.update(&mut events, &plugins).unwrap();
+        assert!(bar.segments.is_empty());
+    }
+}
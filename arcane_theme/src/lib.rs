@@ -0,0 +1,269 @@
+//! Semantic colors shared between plugins
+
+use std::str::FromStr;
+
+use arcane_core::{event, EventManager, Level, Plugin, PluginStore, Result};
+use error_mancer::errors;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+/// The theme colors, each a color name, a palette index or `#rrggbb`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+struct ThemeSettings {
+    /// The background of the selected element in lists and tables
+    selection_bg: String,
+    /// Highlights, like the focused window
+    accent: String,
+    /// Errors and failures
+    error: String,
+    /// Warnings
+    warning: String,
+    /// Informational messages
+    info: String,
+    /// Less important text, like hints
+    muted: String,
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        Self {
+            selection_bg: String::from("#141428"),
+            accent: String::from("LightYellow"),
+            error: String::from("Red"),
+            warning: String::from("Yellow"),
+            info: String::from("Blue"),
+            muted: String::from("DarkGray"),
+        }
+    }
+}
+
+impl ThemeSettings {
+    /// The setting for the color with the given name
+    fn get(&self, name: &str) -> Option<&str> {
+        let color = match name {
+            "selection_bg" => &self.selection_bg,
+            "accent" => &self.accent,
+            "error" => &self.error,
+            "warning" => &self.warning,
+            "info" => &self.info,
+            "muted" => &self.muted,
+            _ => return None,
+        };
+        Some(color)
+    }
+}
+
+#[typetag::serde]
+impl arcane_settings::PluginSettings for ThemeSettings {
+    fn name(&self) -> &'static str {
+        "Theme"
+    }
+
+    fn values(&mut self) -> Box<[arcane_settings::SettingsValueCommon]> {
+        Box::new([
+            arcane_settings::SettingsValueCommon {
+                name: "selection_bg",
+                section: None,
+                value: arcane_settings::SettingsValue::Text(&mut self.selection_bg),
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "accent",
+                section: None,
+                value: arcane_settings::SettingsValue::Text(&mut self.accent),
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "error",
+                section: None,
+                value: arcane_settings::SettingsValue::Text(&mut self.error),
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "warning",
+                section: None,
+                value: arcane_settings::SettingsValue::Text(&mut self.warning),
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "info",
+                section: None,
+                value: arcane_settings::SettingsValue::Text(&mut self.info),
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "muted",
+                section: None,
+                value: arcane_settings::SettingsValue::Text(&mut self.muted),
+            },
+        ])
+    }
+
+    fn description(&self, _value: &'static str) -> Option<&'static str> {
+        Some("A color name like \"LightBlue\", a palette index, or \"#rrggbb\"")
+    }
+}
+
+/// The default color with the given name
+fn default_color(name: &str) -> Option<Color> {
+    Color::from_str(ThemeSettings::default().get(name)?).ok()
+}
+
+/// Holds the configured theme for other plugins to draw with
+#[derive(Debug)]
+pub struct ThemePlugin {
+    /// The theme settings as of the last update
+    settings: ThemeSettings,
+}
+
+arcane_core::register_plugin!(ThemePlugin);
+
+impl ThemePlugin {
+    /// The color with the given name, `None` if the theme has no such color.
+    ///
+    /// Colors that are configured to something invalid fall back to the default theme.
+    pub fn get_color(&self, name: &str) -> Option<Color> {
+        let color = self.settings.get(name)?;
+        Color::from_str(color.trim())
+            .ok()
+            .or_else(|| default_color(name))
+    }
+}
+
+/// Get a color from the `ThemePlugin`, for use in `draw`.
+///
+/// Uses the default theme if the plugin is not loaded, and `Color::Reset` for unknown names.
+pub fn color(plugins: &PluginStore, name: &str) -> Color {
+    let color = match plugins.get::<ThemePlugin>() {
+        Some(theme) => theme.get_color(name),
+        None => default_color(name),
+    };
+    color.unwrap_or_else(|| {
+        event!(Level::WARN, "Unknown theme color {name}");
+        Color::Reset
+    })
+}
+
+impl Plugin for ThemePlugin {
+    fn new() -> Self {
+        Self {
+            settings: ThemeSettings::default(),
+        }
+    }
+
+    #[errors]
+    fn on_load(&mut self, events: &mut EventManager) -> Result<()> {
+        events.dispatch(arcane_settings::RegisterSettings(Box::new(
+            ThemeSettings::default(),
+        )));
+        Ok(())
+    }
+
+    #[errors]
+    fn update(&mut self, _events: &mut EventManager, plugins: &PluginStore) -> Result<()> {
+        if let Some(settings) = arcane_settings::get_settings::<ThemeSettings>(plugins) {
+            self.settings.clone_from(&settings);
+        }
+        Ok(())
+    }
+
+    fn plugin_name(&self) -> &'static str {
+        "Theme"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arcane_core::{Persistence, Plugin, PluginStore, StateManager};
+    use ratatui::backend::TestBackend;
+    use ratatui::style::{Color, Stylize};
+    use ratatui::widgets::Block;
+    use ratatui::Terminal;
+
+    use super::{color, ThemeSettings};
+
+    /// A plugin drawing its selection with the theme
+    struct ListPlugin;
+    impl Plugin for ListPlugin {
+        fn new() -> Self {
+            Self
+        }
+        fn draw(
+            &self,
+            frame: &mut ratatui::Frame,
+            area: ratatui::prelude::Rect,
+            plugins: &PluginStore,
+        ) {
+            frame.render_widget(Block::new().bg(color(plugins, "selection_bg")), area);
+        }
+    }
+
+    /// Another plugin drawing its selection with the theme
+    struct TablePlugin;
+    impl Plugin for TablePlugin {
+        fn new() -> Self {
+            Self
+        }
+        fn draw(
+            &self,
+            frame: &mut ratatui::Frame,
+            area: ratatui::prelude::Rect,
+            plugins: &PluginStore,
+        ) {
+            frame.render_widget(Block::new().bg(color(plugins, "selection_bg")), area);
+        }
+    }
+
+    /// The background color `plugin` draws with
+    fn drawn_bg(plugin: &impl Plugin, plugins: &PluginStore) -> Color {
+        let mut terminal = Terminal::new(TestBackend::new(4, 1)).unwrap();
+        terminal
+            .draw(|frame| plugin.draw(frame, frame.area(), plugins))
+            .unwrap();
+        terminal.backend().buffer()[(0, 0)].bg
+    }
+
+    /// Register `settings` as the theme settings and let the theme plugin pick them up
+    fn set_theme(states: &mut StateManager, settings: ThemeSettings) {
+        states
+            .events
+            .dispatch(arcane_settings::RegisterSettings(Box::new(settings)));
+        // The settings plugin might update after the theme plugin in the first frame
+        for _ in 0..2 {
+            states.events.swap_buffers();
+            states.update().unwrap();
+        }
+    }
+
+    #[test]
+    fn plugins_share_colors() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        assert_eq!(
+            drawn_bg(&ListPlugin, &states.plugins),
+            Color::Rgb(20, 20, 40)
+        );
+
+        set_theme(
+            &mut states,
+            ThemeSettings {
+                selection_bg: String::from("#ff8800"),
+                ..ThemeSettings::default()
+            },
+        );
+
+        let expected = Color::Rgb(0xff, 0x88, 0x00);
+        assert_eq!(drawn_bg(&ListPlugin, &states.plugins), expected);
+        assert_eq!(drawn_bg(&TablePlugin, &states.plugins), expected);
+    }
+
+    #[test]
+    fn invalid_color_uses_default() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        set_theme(
+            &mut states,
+            ThemeSettings {
+                error: String::from("not a color"),
+                ..ThemeSettings::default()
+            },
+        );
+
+        assert_eq!(color(&states.plugins, "error"), Color::Red);
+        assert_eq!(color(&states.plugins, "no_such_color"), Color::Reset);
+    }
+}
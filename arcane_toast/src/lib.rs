@@ -0,0 +1,193 @@
+//! Shows `NotificationEvent`s as toasts in the top right corner
+
+use std::time::Duration;
+
+use arcane_core::{
+    DeltaTimeEvent,
+    EventManager,
+    Level,
+    NotificationEvent,
+    Plugin,
+    PluginStore,
+    Result,
+};
+use error_mancer::errors;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Stylize};
+use ratatui::symbols::border;
+use ratatui::widgets::{Block, Clear, Paragraph};
+
+/// How long a toast is shown
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// The most toasts shown at once, older ones are dropped first
+const MAX_TOASTS: usize = 5;
+
+/// The widest a toast is drawn
+const TOAST_WIDTH: u16 = 40;
+
+/// A notification being shown
+#[derive(Debug)]
+struct Toast {
+    /// The notification to show
+    notification: NotificationEvent,
+    /// How long until the toast is hidden
+    remaining: Duration,
+}
+
+/// Collects `NotificationEvent`s and shows the recent ones
+#[derive(Debug)]
+pub struct ToastPlugin {
+    /// The toasts on screen, oldest first
+    toasts: Vec<Toast>,
+}
+
+arcane_core::register_plugin!(ToastPlugin);
+
+/// The color a notification of the given level is drawn in
+fn level_color(level: Level) -> Color {
+    match level {
+        Level::ERROR => Color::Red,
+        Level::WARN => Color::Yellow,
+        Level::INFO => Color::Blue,
+        _ => Color::Gray,
+    }
+}
+
+impl Plugin for ToastPlugin {
+    fn new() -> Self {
+        Self { toasts: Vec::new() }
+    }
+
+    #[errors]
+    fn on_load(&mut self, events: &mut EventManager) -> Result<()> {
+        events.ensure_event::<NotificationEvent>();
+        Ok(())
+    }
+
+    #[errors]
+    fn update(&mut self, events: &mut EventManager, _plugins: &PluginStore) -> Result<()> {
+        for event in events.read::<DeltaTimeEvent>() {
+            for toast in &mut self.toasts {
+                toast.remaining = toast.remaining.saturating_sub(event.0);
+            }
+        }
+        self.toasts.retain(|toast| !toast.remaining.is_zero());
+
+        self.toasts.extend(
+            events
+                .read::<NotificationEvent>()
+                .iter()
+                .map(|notification| Toast {
+                    notification: notification.clone(),
+                    remaining: TOAST_DURATION,
+                }),
+        );
+        let overflow = self.toasts.len().saturating_sub(MAX_TOASTS);
+        self.toasts.drain(..overflow);
+
+        Ok(())
+    }
+
+    fn z_index(&self) -> u32 {
+        u32::MAX.saturating_sub(2)
+    }
+
+    fn draw(
+        &self,
+        frame: &mut ratatui::Frame,
+        area: ratatui::prelude::Rect,
+        _plugins: &PluginStore,
+    ) {
+        let width = TOAST_WIDTH.min(area.width);
+        let mut y = area.y;
+        for toast in self.toasts.iter().rev() {
+            let toast_area = Rect {
+                x: area.right().saturating_sub(width),
+                y,
+                width,
+                height: 3,
+            }
+            .intersection(area);
+            if toast_area.is_empty() {
+                break;
+            }
+            y = y.saturating_add(3);
+
+            let color = level_color(toast.notification.level);
+            let block = Block::bordered()
+                .border_set(border::ROUNDED)
+                .title_top(toast.notification.level.as_str())
+                .fg(color);
+            let text = Paragraph::new(toast.notification.message.as_str())
+                .white()
+                .block(block);
+
+            frame.render_widget(Clear, toast_area);
+            frame.render_widget(text, toast_area);
+        }
+    }
+
+    fn plugin_name(&self) -> &'static str {
+        "Toasts"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arcane_core::{
+        DeltaTimeEvent,
+        EventManager,
+        Level,
+        NotificationEvent,
+        Plugin,
+        PluginStore,
+    };
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use super::{ToastPlugin, TOAST_DURATION};
+
+    fn render(toasts: &ToastPlugin, plugins: &PluginStore) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+        terminal
+            .draw(|frame| toasts.draw(frame, frame.area(), plugins))
+            .unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn notification_shown_then_expires() {
+        let plugins = PluginStore::new();
+        let mut events = EventManager::new();
+        let mut toasts = ToastPlugin::new();
+        toasts.on_load(&mut events).unwrap();
+
+        events.dispatch(NotificationEvent {
+            level: Level::WARN,
+            message: String::from("Invalid settings entry"),
+        });
+        events.swap_buffers();
+        toasts.update(&mut events, &plugins).unwrap();
+        let screen = render(&toasts, &plugins);
+        assert!(screen.contains("Invalid settings entry"));
+        assert!(screen.contains("WARN"));
+
+        events.dispatch(DeltaTimeEvent(TOAST_DURATION / 2));
+        events.swap_buffers();
+        toasts.update(&mut events, &plugins).unwrap();
+        assert!(render(&toasts, &plugins).contains("Invalid settings entry"));
+
+        events.dispatch(DeltaTimeEvent(TOAST_DURATION / 2));
+        events.swap_buffers();
+        toasts.update(&mut events, &plugins).unwrap();
+        assert!(toasts.toasts.is_empty());
+        assert!(!render(&toasts, &plugins).contains("Invalid settings entry"));
+    }
+}
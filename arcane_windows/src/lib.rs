@@ -1,22 +1,42 @@
 //! Handles drawing the core Windows
 #![feature(used_with_arg)]
 
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::mem;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use arcane_anymap::dyn_clone;
-use arcane_core::{event, Level, Result};
-use arcane_keybindings::{KeyBind, KeyCode, KeyModifiers, RegisterKeybind};
+use arcane_core::{event, Level, Persistence, Result};
+use arcane_keybindings::{KeyBind, KeyCode, KeyModifiers, LockKeybindings, RegisterKeybind};
+use crossterm::event::{MouseButton, MouseEventKind};
 use derive_more::derive::Debug;
-use ratatui::layout::{Constraint, Layout};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
 use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
 use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph, Tabs};
 use serde::{Deserialize, Serialize};
 
+mod list;
+
+pub use list::SelectableList;
+
 /// Id ofs a window
 pub type WindowID = u8;
 
+/// A reference to a specific window that stays valid only as long as that window is open.
+///
+/// Window ids get reused when the plugin runs out of them, so a stored `WindowID` can end up
+/// pointing at a different window. A handle notices this through its generation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WindowHandle {
+    /// The id of the window
+    pub id: WindowID,
+    /// The generation of the window the handle was created for
+    pub generation: u32,
+}
+
 /// Trait implementing all values needed for a window
 pub trait Window: dyn_clone::DynClone {
     /// The horizontal constarint for this window.
@@ -26,9 +46,43 @@ pub trait Window: dyn_clone::DynClone {
         Constraint::Fill(1)
     }
 
+    /// The vertical constraint for this window, used when windows are stacked.
+    ///
+    /// Defaults to `Fill(1)`, i.e take up the same spaces as all other "normal windows"
+    fn vertical_constraints(&self) -> Constraint {
+        Constraint::Fill(1)
+    }
+
+    /// The narrowest width this window is usable at.
+    ///
+    /// Unfocused windows given less space are collapsed into a placeholder.
+    fn min_width(&self) -> Option<u16> {
+        None
+    }
+
     /// The name for the window
     fn name(&self) -> String;
 
+    /// Extra info shown right aligned in the title bar, like an item count
+    fn title_right(&self) -> Option<String> {
+        None
+    }
+
+    /// Does the window want raw keyboard input, like a text input.
+    ///
+    /// Keybindings are locked while a window returning `true` is focused.
+    fn wants_keyboard(&self) -> bool {
+        false
+    }
+
+    /// The layer the window is drawn in, windows with a higher z index are drawn after the others.
+    ///
+    /// Only matters for windows drawing outside their area, like a popup over the other windows.
+    /// Windows in the same layer are drawn in tab order.
+    fn z_index(&self) -> u32 {
+        0
+    }
+
     /// Draw the window contents
     fn draw(
         &self,
@@ -48,6 +102,13 @@ pub trait Window: dyn_clone::DynClone {
         Ok(())
     }
 
+    /// The state needed to restore this window after a restart.
+    ///
+    /// Windows returning `None` are not restored, the `tag` needs a matching `register_window!`.
+    fn persist(&self) -> Option<PersistedWindow> {
+        None
+    }
+
     /// Called when the window is deleted
     fn on_remove(
         &mut self,
@@ -58,12 +119,129 @@ pub trait Window: dyn_clone::DynClone {
     }
 }
 
+/// The draw callback of a `ClosureWindow`
+type DrawFn = dyn Fn(&mut ratatui::Frame, Rect, &arcane_core::PluginStore);
+
+/// A window drawn by a closure, for simple panels that do not need their own type
+#[derive(Clone, Debug)]
+pub struct ClosureWindow {
+    /// The name of the window
+    name: String,
+    /// Draws the window contents
+    #[debug(skip)]
+    draw: Arc<DrawFn>,
+}
+
+impl ClosureWindow {
+    /// Create a window with the given name, drawn by calling `draw`
+    pub fn new(
+        name: impl Into<String>,
+        draw: impl Fn(&mut ratatui::Frame, Rect, &arcane_core::PluginStore) + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            draw: Arc::new(draw),
+        }
+    }
+}
+
+impl Window for ClosureWindow {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn draw(
+        &self,
+        frame: &mut ratatui::Frame,
+        area: ratatui::prelude::Rect,
+        plugins: &arcane_core::PluginStore,
+    ) {
+        (self.draw)(frame, area, plugins);
+    }
+}
+
+/// The saved state of a window
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PersistedWindow {
+    /// The tag the window constructor was registered under
+    pub tag: String,
+    /// The state passed to the constructor
+    pub state: serde_json::Value,
+}
+
+/// A constructor to restore persisted windows with
+pub struct RegisterWindow {
+    /// The tag used in `PersistedWindow::tag`
+    tag: &'static str,
+    /// Recreate the window from `PersistedWindow::state`
+    restore: fn(serde_json::Value) -> Option<Box<dyn Window>>,
+}
+
+arcane_core::inventory::collect!(RegisterWindow);
+
+impl RegisterWindow {
+    /// Register `restore` to recreate windows persisted with the given tag
+    pub const fn new(
+        tag: &'static str,
+        restore: fn(serde_json::Value) -> Option<Box<dyn Window>>,
+    ) -> Self {
+        Self { tag, restore }
+    }
+
+    /// Restore a persisted window using the constructor registered for its tag
+    fn restore(window: PersistedWindow) -> Option<Box<dyn Window>> {
+        let register = arcane_core::inventory::iter::<RegisterWindow>()
+            .find(|register| register.tag == window.tag);
+        let Some(register) = register else {
+            event!(Level::WARN, "No window registered for tag {}", window.tag);
+            return None;
+        };
+        (register.restore)(window.state)
+    }
+}
+
+#[macro_export]
+macro_rules! register_window {
+    ($tag:literal, $restore:path) => {
+        ::arcane_core::inventory::submit!($crate::RegisterWindow::new($tag, $restore));
+    };
+}
+
+/// The window layout saved to `windows.json`
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct SavedLayout {
+    /// The persisted windows of each tab, in order
+    tabs: Vec<Vec<PersistedWindow>>,
+    /// The focused tab
+    focused_tab: usize,
+}
+
 /// Events for stuff to do with windows
 pub enum WindowEvent {
     /// Create a new window
     CreateWindow(Box<dyn Window>),
     /// Close a window
     CloseWindow(WindowID),
+    /// Close a window through a handle, ignored if the window it refers to is gone
+    CloseWindowHandle(WindowHandle),
+    /// Move a window to the end of a tab, a new tab is created if `tab` is past the last one
+    MoveWindowToTab {
+        /// The window to move
+        window: WindowID,
+        /// The index of the target tab
+        tab: usize,
+    },
+    /// Focus a window, switching to its tab
+    FocusWindow(WindowID),
+}
+
+/// Dispatched when the focused window changes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WindowFocusChanged {
+    /// The previously focused window
+    pub from: Option<WindowID>,
+    /// The newly focused window
+    pub to: Option<WindowID>,
 }
 
 /// Settings for displaying windows
@@ -80,6 +258,16 @@ struct WindowSettings {
     all_full_border: bool,
     /// Always show the tab bar
     always_show_tab_bar: bool,
+    /// Draw the tab bar at the `Top` or `Bottom` of the screen
+    tab_bar_position: String,
+    /// The border color of the focused window, a color name or `#rrggbb`
+    focus_border_color: String,
+    /// The border color of unfocused windows, a color name or `#rrggbb`
+    other_border_color: String,
+    /// Tile windows `Horizontal`ly or `Vertical`ly
+    layout_direction: String,
+    /// Moving focus past the last window wraps around to the first, and the other way around
+    wrap_focus: bool,
 }
 
 impl Default for WindowSettings {
@@ -87,9 +275,14 @@ impl Default for WindowSettings {
         Self {
             focus_border_type: String::from("Double"),
             other_border_type: String::from("Rounded"),
+            focus_border_color: String::from("LightYellow"),
+            other_border_color: String::from("LightGreen"),
             focus_full_border: true,
             all_full_border: true,
             always_show_tab_bar: false,
+            tab_bar_position: String::from("Top"),
+            layout_direction: String::from("Horizontal"),
+            wrap_focus: false,
         }
     }
 }
@@ -105,6 +298,7 @@ impl arcane_settings::PluginSettings for WindowSettings {
         let mut options = vec![
             arcane_settings::SettingsValueCommon {
                 name: "focus_border_type",
+                section: None,
                 value: arcane_settings::SettingsValue::Selection(
                     &mut self.focus_border_type,
                     &["Double", "Rounded", "Plain"],
@@ -112,28 +306,81 @@ impl arcane_settings::PluginSettings for WindowSettings {
             },
             arcane_settings::SettingsValueCommon {
                 name: "other_border_type",
+                section: None,
                 value: arcane_settings::SettingsValue::Selection(
                     &mut self.other_border_type,
                     &["Double", "Rounded", "Plain"],
                 ),
             },
+            arcane_settings::SettingsValueCommon {
+                name: "focus_border_color",
+                section: None,
+                value: arcane_settings::SettingsValue::Text(&mut self.focus_border_color),
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "other_border_color",
+                section: None,
+                value: arcane_settings::SettingsValue::Text(&mut self.other_border_color),
+            },
             arcane_settings::SettingsValueCommon {
                 name: "all_full_border",
+                section: None,
                 value: arcane_settings::SettingsValue::Toogle(&mut self.all_full_border),
             },
             arcane_settings::SettingsValueCommon {
                 name: "always_show_tab_bar",
+                section: None,
                 value: arcane_settings::SettingsValue::Toogle(&mut self.always_show_tab_bar),
             },
+            arcane_settings::SettingsValueCommon {
+                name: "tab_bar_position",
+                section: None,
+                value: arcane_settings::SettingsValue::Selection(
+                    &mut self.tab_bar_position,
+                    &["Top", "Bottom"],
+                ),
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "layout_direction",
+                section: None,
+                value: arcane_settings::SettingsValue::Selection(
+                    &mut self.layout_direction,
+                    &["Horizontal", "Vertical"],
+                ),
+            },
+            arcane_settings::SettingsValueCommon {
+                name: "wrap_focus",
+                section: None,
+                value: arcane_settings::SettingsValue::Toogle(&mut self.wrap_focus),
+            },
         ];
         if !all_full_border {
             options.push(arcane_settings::SettingsValueCommon {
                 name: "focus_full_border",
+                section: None,
                 value: arcane_settings::SettingsValue::Toogle(&mut self.focus_full_border),
             });
         }
         options.into_boxed_slice()
     }
+
+    fn description(&self, value: &'static str) -> Option<&'static str> {
+        match value {
+            "focus_border_color" | "other_border_color" => {
+                Some("A color name like \"LightBlue\", a palette index, or \"#rrggbb\"")
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Something on screen that can be clicked to focus it
+#[derive(Clone, Copy, Debug)]
+enum ClickTarget {
+    /// The tab at the index in the tab bar
+    Tab(usize),
+    /// The window at the index in the current tab
+    Window(usize),
 }
 
 /// The plugin
@@ -142,38 +389,273 @@ pub struct WindowPlugin {
     windows: HashMap<WindowID, Box<dyn Window>>,
     /// the next free ID
     next_free: WindowID,
+    /// The generation of the window currently using each id
+    generations: HashMap<WindowID, u32>,
+    /// The generation given to the next window
+    next_generation: u32,
     /// The list of tabs
     tabs: Vec<Vec<WindowID>>,
+    /// The user given names of the tabs, parallel to `tabs`
+    tab_names: Vec<Option<String>>,
+    /// The new name of the focused tab while it is being renamed
+    renaming_tab: Option<String>,
     /// The currently focused window
     focused_window: usize,
     /// The currently focused tab
     focused_tab: usize,
+    /// The window maximized to take up the whole screen
+    zoomed: Option<WindowID>,
+    /// Where `windows.json` is loaded from and saved to
+    persistence: Persistence,
+    /// The areas of the tabs and windows in the last drawn frame
+    click_targets: Cell<Vec<(Rect, ClickTarget)>>,
+    /// Are keybindings locked because the focused window wants the keyboard
+    keyboard_locked: bool,
 }
 
 arcane_core::register_plugin!(WindowPlugin);
 
 impl WindowPlugin {
-    /// Re assign all window ids
+    /// Create the plugin with the given persistence, `new` persists to the config directory
+    pub fn with_persistence(persistence: Persistence) -> Self {
+        Self {
+            persistence,
+            ..<Self as arcane_core::Plugin>::new()
+        }
+    }
+
+    /// Re assign all window ids, keeping their order, tabs and zoom
     ///
-    /// Warning: This messes up references from the location datas, which I am honestly fine with
-    /// because this function is super rare to trigger, and you are asking for strange stuff
-    /// honestly.
+    /// Handles to the old ids are invalidated, as they get new generations.
     ///
     /// Considering this function is only useful when you hit >255 opened windows, but have also
     /// closed some.
     fn fill_gaps(&mut self) -> Result<()> {
-        let windows = mem::take(&mut self.windows);
-        let windows = windows.into_values();
-        for (index, window) in windows.enumerate() {
+        let mut windows = mem::take(&mut self.windows).into_iter().collect::<Vec<_>>();
+        windows.sort_unstable_by_key(|(id, _)| *id);
+        self.generations.clear();
+
+        let mut new_ids = HashMap::new();
+        for (index, (old_id, window)) in windows.into_iter().enumerate() {
             let index = index.try_into()?;
+            new_ids.insert(old_id, index);
             self.windows.insert(index, window);
+            let generation = self.new_generation();
+            self.generations.insert(index, generation);
         }
         self.next_free = self.windows.len().try_into()?;
 
+        for tab in &mut self.tabs {
+            tab.retain_mut(|id| match new_ids.get(id) {
+                Some(new_id) => {
+                    *id = *new_id;
+                    true
+                }
+                None => false,
+            });
+        }
+        self.zoomed = self.zoomed.and_then(|id| new_ids.get(&id).copied());
+
+        Ok(())
+    }
+
+    /// Take the next window generation
+    fn new_generation(&mut self) -> u32 {
+        let generation = self.next_generation;
+        self.next_generation = self.next_generation.wrapping_add(1);
+        generation
+    }
+
+    /// A handle to the window currently using `id`, `None` if there is no such window
+    pub fn handle(&self, id: WindowID) -> Option<WindowHandle> {
+        self.generations
+            .get(&id)
+            .map(|&generation| WindowHandle { id, generation })
+    }
+
+    /// Is the window the handle was created for still open
+    pub fn is_valid(&self, handle: WindowHandle) -> bool {
+        self.generations.get(&handle.id) == Some(&handle.generation)
+    }
+
+    /// Remove a window from the plugin and all tabs
+    fn close_window(
+        &mut self,
+        id: WindowID,
+        events: &arcane_core::EventManager,
+        plugins: &arcane_core::PluginStore,
+    ) -> Result<()> {
+        event!(Level::DEBUG, "Deleting window {id}");
+        if let Some(mut removed_window) = self.windows.remove(&id) {
+            removed_window.on_remove(events, plugins)?;
+        }
+        self.generations.remove(&id);
+        for tab in &mut self.tabs {
+            tab.retain(|window_id| *window_id != id);
+        }
+        if self.zoomed == Some(id) {
+            self.zoomed = None;
+        }
+        Ok(())
+    }
+
+    /// Add a window to the end of the current tab and focus it
+    fn add_window(&mut self, window: Box<dyn Window>) -> Result<()> {
+        let id = self.next_free;
+        event!(Level::DEBUG, "Created window {id}");
+        if let Some(new_id) = self.next_free.checked_add(1) {
+            self.next_free = new_id;
+        } else {
+            event!(Level::WARN, "Ran out of window IDS, Filling gaps");
+            self.fill_gaps()?;
+        }
+        self.windows.insert(id, window);
+        let generation = self.new_generation();
+        self.generations.insert(id, generation);
+        if let Some(current_tab) = self.tabs.get_mut(self.focused_tab) {
+            current_tab.push(id);
+            self.focused_window = current_tab.len().saturating_sub(1);
+        }
+
+        Ok(())
+    }
+
+    /// The current layout with only the windows that can be persisted
+    fn saved_layout(&self) -> SavedLayout {
+        SavedLayout {
+            tabs: self
+                .tabs
+                .iter()
+                .map(|tab| {
+                    tab.iter()
+                        .filter_map(|id| self.windows.get(id))
+                        .filter_map(|window| window.persist())
+                        .collect()
+                })
+                .collect(),
+            focused_tab: self.focused_tab,
+        }
+    }
+
+    /// The tabs and focus, compared between frames to notice when the layout needs saving
+    fn layout_key(&self) -> (Vec<Vec<WindowID>>, usize) {
+        (self.tabs.clone(), self.focused_tab)
+    }
+
+    /// Save the layout to `windows.json`.
+    ///
+    /// The layout is written to a temporary file first, which is then renamed over the target. So
+    /// a failed or interrupted write never corrupts the existing file.
+    fn save_layout(&self) -> Result<()> {
+        let Some(directory) = self.persistence.dir() else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(directory)?;
+
+        let layout_path = directory.join("windows.json");
+        let temp_path = directory.join("windows.json.tmp");
+        event!(Level::DEBUG, "Saving window layout to {layout_path:?}");
+
+        let file = std::fs::File::create(&temp_path)?;
+        if let Err(err) = serde_json::to_writer_pretty(&file, &self.saved_layout()) {
+            std::fs::remove_file(&temp_path)?;
+            return Err(err.into());
+        }
+        file.sync_all()?;
+
+        std::fs::rename(temp_path, layout_path)?;
+        Ok(())
+    }
+
+    /// Restore the layout saved in `windows.json`, keeping the default layout if it is invalid
+    fn load_layout(&mut self) -> Result<()> {
+        let Some(directory) = self.persistence.dir() else {
+            return Ok(());
+        };
+        let layout_path = directory.join("windows.json");
+        let Ok(file) = std::fs::File::open(&layout_path) else {
+            return Ok(());
+        };
+        let layout: SavedLayout = match serde_json::from_reader(file) {
+            Ok(layout) => layout,
+            Err(err) => {
+                event!(
+                    Level::ERROR,
+                    "Invalid window layout in {layout_path:?}, using the default: {err}"
+                );
+                return Ok(());
+            }
+        };
+
+        self.tabs = Vec::new();
+        for tab in layout.tabs {
+            self.tabs.push(Vec::new());
+            self.focused_tab = self.tabs.len().saturating_sub(1);
+            for window in tab.into_iter().filter_map(RegisterWindow::restore) {
+                self.add_window(window)?;
+            }
+        }
+        if self.tabs.is_empty() {
+            self.tabs.push(Vec::new());
+        }
+        self.focused_tab = layout.focused_tab.min(self.tabs.len().saturating_sub(1));
+        self.focused_window = 0;
+
         Ok(())
     }
+
+    /// The id of the focused window in the current tab, `None` if the tab is empty
+    pub fn focused_window_id(&self) -> Option<WindowID> {
+        self.tabs
+            .get(self.focused_tab)
+            .and_then(|tab| tab.get(self.focused_window))
+            .copied()
+    }
+
+    /// The name of the focused window in the current tab, `None` if the tab is empty
+    pub fn focused_window_name(&self) -> Option<String> {
+        let id = self.focused_window_id()?;
+        self.windows.get(&id).map(|window| window.name())
+    }
+
+    /// How many windows are in the current tab
+    fn tab_len(&self) -> usize {
+        self.tabs.get(self.focused_tab).map_or(0, Vec::len)
+    }
+
+    /// Keep `focused_window` inside the current tab
+    fn clamp_focus(&mut self) {
+        if let Some(current_tab) = self.tabs.get(self.focused_tab) {
+            self.focused_window = self.focused_window.min(current_tab.len().saturating_sub(1));
+        }
+    }
+
+    /// The title of a tab in the tab bar, its name or the window count if it has none
+    fn tab_title(&self, index: usize) -> String {
+        if index == self.focused_tab {
+            if let Some(name) = &self.renaming_tab {
+                return format!("{name}_");
+            }
+        }
+
+        match self.tab_names.get(index).cloned().flatten() {
+            Some(name) => name,
+            None => self
+                .tabs
+                .get(index)
+                .map(Vec::len)
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
 }
 
+/// The `LockKeybindings` source used while the focused window wants the keyboard
+const FOCUSED_WINDOW_LOCK: &str = "focused_window";
+
+/// The `LockKeybindings` source used while a tab is renamed
+const RENAME_TAB_LOCK: &str = "rename_tab";
+
 /// Ui Events for windows
 #[derive(Clone, Debug, Serialize, Deserialize)]
 enum WindowUiEvent {
@@ -204,6 +686,18 @@ enum WindowUiEvent {
     /// Close a tab
     #[debug("Window::CloseTab")]
     CloseTab,
+    /// Maximize the focused window, or restore the layout if a window is already maximized
+    #[debug("Window::ToggleZoom")]
+    ToggleZoom,
+    /// Type a new name for the current tab
+    #[debug("Window::RenameTab")]
+    RenameTab,
+    /// Close every window in every tab, except the one in focus
+    #[debug("Window::CloseOthers")]
+    CloseOthers,
+    /// Close every window in every tab
+    #[debug("Window::CloseAll")]
+    CloseAll,
 }
 
 #[typetag::serde]
@@ -214,15 +708,24 @@ impl arcane_core::Plugin for WindowPlugin {
         WindowPlugin {
             windows: HashMap::new(),
             next_free: 0,
+            generations: HashMap::new(),
+            next_generation: 0,
             tabs: vec![vec![]],
+            tab_names: Vec::new(),
+            renaming_tab: None,
             focused_tab: 0,
             focused_window: 0,
+            zoomed: None,
+            persistence: Persistence::config(),
+            click_targets: Cell::default(),
+            keyboard_locked: false,
         }
     }
     fn on_load(&mut self, events: &mut arcane_core::EventManager) -> Result<()> {
         events.dispatch(arcane_settings::RegisterSettings(Box::new(
             WindowSettings::default(),
         )));
+        self.load_layout()?;
 
         events.ensure_event::<WindowUiEvent>();
         events.dispatch(RegisterKeybind::single_key(
@@ -324,36 +827,148 @@ impl arcane_core::Plugin for WindowPlugin {
             ],
             WindowUiEvent::CloseTab,
         ));
+        events.dispatch(RegisterKeybind::chord(
+            [
+                KeyBind {
+                    modifiers: KeyModifiers::CONTROL,
+                    key: KeyCode::Char('m'),
+                },
+                KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Char('z'),
+                },
+            ],
+            WindowUiEvent::ToggleZoom,
+        ));
+        events.dispatch(RegisterKeybind::chord(
+            [
+                KeyBind {
+                    modifiers: KeyModifiers::CONTROL,
+                    key: KeyCode::Char('t'),
+                },
+                KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Char('r'),
+                },
+            ],
+            WindowUiEvent::RenameTab,
+        ));
+        events.dispatch(RegisterKeybind::chord(
+            [
+                KeyBind {
+                    modifiers: KeyModifiers::CONTROL,
+                    key: KeyCode::Char('m'),
+                },
+                KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Char('o'),
+                },
+            ],
+            WindowUiEvent::CloseOthers,
+        ));
+        events.dispatch(RegisterKeybind::chord(
+            [
+                KeyBind {
+                    modifiers: KeyModifiers::CONTROL,
+                    key: KeyCode::Char('m'),
+                },
+                KeyBind {
+                    modifiers: KeyModifiers::NONE,
+                    key: KeyCode::Char('a'),
+                },
+            ],
+            WindowUiEvent::CloseAll,
+        ));
 
         Ok(())
     }
 
+    fn on_unload(&mut self, _events: &mut arcane_core::EventManager) -> Result<()> {
+        // The layout is only saved when the tabs change, this also keeps window state changes
+        self.save_layout()
+    }
+
     fn update(
         &mut self,
         events: &mut arcane_core::EventManager,
         plugins: &arcane_core::PluginStore,
     ) -> Result<()> {
-        let focused_window_id = self
-            .tabs
-            .get(self.focused_tab)
-            .and_then(|tab| tab.get(self.focused_window))
-            .copied()
-            .unwrap_or_default();
+        let focused_window_id = self.focused_window_id();
         for (window_id, window) in &mut self.windows {
-            window.update(events, plugins, *window_id == focused_window_id, *window_id)?;
+            window.update(
+                events,
+                plugins,
+                Some(*window_id) == focused_window_id,
+                *window_id,
+            )?;
         }
 
+        let layout_before = self.layout_key();
+        let wrap_focus = arcane_settings::get_settings::<WindowSettings>(plugins)
+            .is_some_and(|settings| settings.wrap_focus);
+
         let (reader, mut writer) = events.split();
+        if let Some(name) = &mut self.renaming_tab {
+            for event in reader.read::<arcane_core::KeydownEvent>() {
+                match event.0.code {
+                    KeyCode::Char(c) => name.push(c),
+                    KeyCode::Backspace => {
+                        name.pop();
+                    }
+                    KeyCode::Enter => {
+                        let name = mem::take(name);
+                        self.tab_names.resize(self.tabs.len(), None);
+                        if let Some(tab_name) = self.tab_names.get_mut(self.focused_tab) {
+                            *tab_name = Some(name).filter(|name| !name.is_empty());
+                        }
+                        self.renaming_tab = None;
+                        writer.dispatch(LockKeybindings {
+                            source: RENAME_TAB_LOCK,
+                            locked: false,
+                        });
+                        break;
+                    }
+                    KeyCode::Esc => {
+                        self.renaming_tab = None;
+                        writer.dispatch(LockKeybindings {
+                            source: RENAME_TAB_LOCK,
+                            locked: false,
+                        });
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if let Some(name) = &mut self.renaming_tab {
+            for event in reader.read::<arcane_core::PasteEvent>() {
+                name.extend(event.0.chars().filter(|c| !c.is_control()));
+            }
+        }
+
         for event in reader.read::<WindowUiEvent>() {
             match event {
                 WindowUiEvent::FocusLeft => {
-                    self.focused_window = self.focused_window.saturating_sub(1);
+                    self.focused_window = if wrap_focus && self.focused_window == 0 {
+                        self.tab_len().saturating_sub(1)
+                    } else {
+                        self.focused_window.saturating_sub(1)
+                    };
+                    self.clamp_focus();
                 }
                 WindowUiEvent::FocusRight => {
-                    self.focused_window = self.focused_window.saturating_add(1);
+                    let next = self.focused_window.saturating_add(1);
+                    self.focused_window = if wrap_focus && next >= self.tab_len() {
+                        0
+                    } else {
+                        next
+                    };
+                    self.clamp_focus();
                 }
                 WindowUiEvent::DeleteFocus => {
-                    writer.dispatch(WindowEvent::CloseWindow(focused_window_id));
+                    if let Some(focused_window_id) = self.focused_window_id() {
+                        writer.dispatch(WindowEvent::CloseWindow(focused_window_id));
+                    }
                 }
                 WindowUiEvent::MoveLeft => {
                     let target = self.focused_window.saturating_sub(1);
@@ -363,6 +978,7 @@ impl arcane_core::Plugin for WindowPlugin {
                             self.focused_window = target;
                         }
                     }
+                    self.clamp_focus();
                 }
                 WindowUiEvent::MoveRight => {
                     let target = self.focused_window.saturating_add(1);
@@ -372,6 +988,7 @@ impl arcane_core::Plugin for WindowPlugin {
                             self.focused_window = target;
                         }
                     }
+                    self.clamp_focus();
                 }
                 WindowUiEvent::NewTab => {
                     self.tabs.push(vec![]);
@@ -386,6 +1003,9 @@ impl arcane_core::Plugin for WindowPlugin {
                 WindowUiEvent::CloseTab => {
                     if self.focused_tab < self.tabs.len() {
                         let removed_tab = self.tabs.remove(self.focused_tab);
+                        if self.focused_tab < self.tab_names.len() {
+                            self.tab_names.remove(self.focused_tab);
+                        }
                         for window_id in removed_tab {
                             writer.dispatch(WindowEvent::CloseWindow(window_id));
                         }
@@ -394,41 +1014,140 @@ impl arcane_core::Plugin for WindowPlugin {
                         }
                     }
                 }
+                WindowUiEvent::ToggleZoom => {
+                    self.zoomed = if self.zoomed.is_some() {
+                        None
+                    } else {
+                        self.focused_window_id()
+                    };
+                }
+                WindowUiEvent::RenameTab => {
+                    if self.renaming_tab.is_none() {
+                        self.renaming_tab = Some(
+                            self.tab_names
+                                .get(self.focused_tab)
+                                .cloned()
+                                .flatten()
+                                .unwrap_or_default(),
+                        );
+                        writer.dispatch(LockKeybindings {
+                            source: RENAME_TAB_LOCK,
+                            locked: true,
+                        });
+                    }
+                }
+                WindowUiEvent::CloseOthers | WindowUiEvent::CloseAll => {
+                    let keep = match event {
+                        WindowUiEvent::CloseOthers => self.focused_window_id(),
+                        _ => None,
+                    };
+                    for window_id in self.tabs.iter().flatten() {
+                        if Some(*window_id) != keep {
+                            writer.dispatch(WindowEvent::CloseWindow(*window_id));
+                        }
+                    }
+                }
+            }
+        }
+
+        let click_targets = self.click_targets.take();
+        for event in events.read::<arcane_core::MouseEvent>() {
+            if event.0.kind != MouseEventKind::Down(MouseButton::Left) {
+                continue;
+            }
+            let position = Position::new(event.0.column, event.0.row);
+            let target = click_targets
+                .iter()
+                .find(|(area, _)| area.contains(position))
+                .map(|(_, target)| *target);
+            match target {
+                Some(ClickTarget::Tab(tab)) => {
+                    self.focused_tab = tab;
+                    self.focused_window = 0;
+                }
+                Some(ClickTarget::Window(window)) => {
+                    self.focused_window = window;
+                }
+                None => {}
             }
         }
+        self.click_targets.set(click_targets);
 
         for event in events.read::<WindowEvent>() {
             match event {
                 WindowEvent::CreateWindow(window) => {
-                    let id = self.next_free;
-                    event!(Level::DEBUG, "Created window {id}");
-                    if let Some(new_id) = self.next_free.checked_add(1) {
-                        self.next_free = new_id;
+                    self.add_window(dyn_clone::clone_box(&**window))?;
+                }
+                WindowEvent::CloseWindow(id) => {
+                    self.close_window(*id, events, plugins)?;
+                }
+                WindowEvent::CloseWindowHandle(handle) => {
+                    if self.is_valid(*handle) {
+                        self.close_window(handle.id, events, plugins)?;
                     } else {
-                        event!(Level::WARN, "Ran out of window IDS, Filling gaps");
-                        self.fill_gaps()?;
+                        event!(Level::WARN, "Tried to close stale window handle {handle:?}");
                     }
-                    self.windows.insert(id, dyn_clone::clone_box(&**window));
-                    if let Some(current_tab) = self.tabs.get_mut(self.focused_tab) {
-                        current_tab.push(id);
-                        self.focused_window = current_tab.len().saturating_sub(1);
+                }
+                WindowEvent::FocusWindow(id) => {
+                    let position = self.tabs.iter().enumerate().find_map(|(tab, windows)| {
+                        windows
+                            .iter()
+                            .position(|window_id| window_id == id)
+                            .map(|window| (tab, window))
+                    });
+                    if let Some((tab, window)) = position {
+                        self.focused_tab = tab;
+                        self.focused_window = window;
                     }
                 }
-                WindowEvent::CloseWindow(id) => {
-                    event!(Level::DEBUG, "Deleting window {id}");
-                    if let Some(mut removed_window) = self.windows.remove(id) {
-                        removed_window.on_remove(events, plugins)?;
+                WindowEvent::MoveWindowToTab { window, tab } => {
+                    if !self.windows.contains_key(window) {
+                        event!(Level::WARN, "Tried to move unknown window {window}");
+                        continue;
                     }
+
+                    event!(Level::DEBUG, "Moving window {window} to tab {tab}");
                     for tab in &mut self.tabs {
-                        tab.retain(|window_id| window_id != id);
+                        tab.retain(|window_id| window_id != window);
+                    }
+                    if let Some(target) = self.tabs.get_mut(*tab) {
+                        target.push(*window);
+                    } else {
+                        self.tabs.push(vec![*window]);
                     }
                 }
             }
         }
 
         self.focused_tab = self.focused_tab.min(self.tabs.len().saturating_sub(1));
-        if let Some(current_tab) = self.tabs.get(self.focused_tab) {
-            self.focused_window = self.focused_window.min(current_tab.len().saturating_sub(1));
+        self.clamp_focus();
+        self.tab_names.resize(self.tabs.len(), None);
+
+        let new_focus = self.focused_window_id();
+        let wants_keyboard = new_focus
+            .and_then(|id| self.windows.get(&id))
+            .is_some_and(|window| window.wants_keyboard());
+        if wants_keyboard != self.keyboard_locked {
+            self.keyboard_locked = wants_keyboard;
+            events.dispatch(LockKeybindings {
+                source: FOCUSED_WINDOW_LOCK,
+                locked: wants_keyboard,
+            });
+        }
+
+        if new_focus != focused_window_id {
+            event!(
+                Level::TRACE,
+                "Focus moved from {focused_window_id:?} to {new_focus:?}"
+            );
+            events.dispatch(WindowFocusChanged {
+                from: focused_window_id,
+                to: new_focus,
+            });
+        }
+
+        if self.layout_key() != layout_before {
+            self.save_layout()?;
         }
 
         Ok(())
@@ -455,37 +1174,107 @@ impl arcane_core::Plugin for WindowPlugin {
         let Some(current_tab) = self.tabs.get(self.focused_tab) else {
             return;
         };
+        let mut click_targets = Vec::new();
+
         let windows = current_tab
             .iter()
             .filter_map(|id| self.windows.get(id))
             .collect::<Vec<_>>();
 
         if self.tabs.len() > 1 || settings.always_show_tab_bar {
-            let [tab_bar_area, new_area] =
-                Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas::<2>(area);
+            let [tab_bar_area, new_area] = if settings.tab_bar_position == "Bottom" {
+                let [new_area, tab_bar_area] =
+                    Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas::<2>(area);
+                [tab_bar_area, new_area]
+            } else {
+                Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas::<2>(area)
+            };
             area = new_area;
 
-            let tab_bar = Tabs::new(self.tabs.iter().enumerate().map(|(index, tab)| {
-                format!(
-                    "{}{}",
-                    if index == self.focused_tab { "> " } else { "" },
-                    tab.len()
-                )
-            }))
-            .select(self.focused_tab)
-            .divider(" | ")
-            .highlight_style(Style::default().yellow())
-            .on_black();
+            let titles = (0..self.tabs.len())
+                .map(|index| {
+                    format!(
+                        "{}{}",
+                        if index == self.focused_tab { "> " } else { "" },
+                        self.tab_title(index)
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            // Mirror how `Tabs` lays out the titles, one space of padding on each side
+            // followed by the divider
+            let mut x = tab_bar_area.x;
+            for (index, title) in titles.iter().enumerate() {
+                let width = u16::try_from(title.chars().count())
+                    .unwrap_or(u16::MAX)
+                    .saturating_add(2);
+                let tab_area = Rect {
+                    x,
+                    width,
+                    ..tab_bar_area
+                }
+                .intersection(tab_bar_area);
+                click_targets.push((tab_area, ClickTarget::Tab(index)));
+                x = x.saturating_add(width).saturating_add(3);
+            }
+
+            let tab_bar = Tabs::new(titles)
+                .select(self.focused_tab)
+                .divider(" | ")
+                .highlight_style(Style::default().yellow())
+                .on_black();
             frame.render_widget(tab_bar, tab_bar_area);
         }
 
-        let layout =
+        if let Some(zoomed) = self.zoomed.filter(|id| current_tab.contains(id)) {
+            if let Some(window) = self.windows.get(&zoomed) {
+                if let Some(position) = current_tab.iter().position(|id| *id == zoomed) {
+                    click_targets.push((area, ClickTarget::Window(position)));
+                }
+                self.click_targets.set(click_targets);
+                frame.render_widget(Clear, area);
+                window.draw(frame, area, plugins);
+                return;
+            }
+        }
+
+        let vertical = settings.layout_direction == "Vertical";
+        let layout = if vertical {
+            Layout::vertical(windows.iter().map(|window| window.vertical_constraints())).split(area)
+        } else {
             Layout::horizontal(windows.iter().map(|window| window.horizontal_constraints()))
-                .split(area);
+                .split(area)
+        };
+        let (side_borders, leading_border) = if vertical {
+            (Borders::TOP | Borders::BOTTOM, Borders::TOP)
+        } else {
+            (Borders::LEFT | Borders::RIGHT, Borders::LEFT)
+        };
 
-        for (position, window) in windows.into_iter().enumerate() {
+        let mut windows = windows.into_iter().enumerate().collect::<Vec<_>>();
+        windows.sort_by_key(|(_, window)| window.z_index());
+        for (position, window) in windows {
             let focused = position == self.focused_window;
 
+            let Some(area) = layout.get(position) else {
+                continue;
+            };
+            click_targets.push((*area, ClickTarget::Window(position)));
+            if !focused
+                && window
+                    .min_width()
+                    .is_some_and(|min_width| area.width < min_width)
+            {
+                let arrow = if position < self.focused_window {
+                    "◀"
+                } else {
+                    "▶"
+                };
+                frame.render_widget(Clear, *area);
+                frame.render_widget(Paragraph::new(arrow).fg(Color::DarkGray), *area);
+                continue;
+            }
+
             let borders = if current_tab.len() == 1 {
                 Borders::NONE
             } else if settings.all_full_border {
@@ -494,21 +1283,23 @@ impl arcane_core::Plugin for WindowPlugin {
                 if settings.focus_full_border {
                     Borders::ALL
                 } else {
-                    Borders::LEFT | Borders::RIGHT
+                    side_borders
                 }
             } else if position != 0 && position.saturating_sub(1) != self.focused_window {
-                Borders::LEFT
+                leading_border
             } else {
                 Borders::NONE
             };
             let (color, border_type) = if focused {
                 (
-                    Color::LightYellow,
+                    Color::from_str(settings.focus_border_color.trim())
+                        .unwrap_or(Color::LightYellow),
                     BorderType::from_str(&settings.focus_border_type).unwrap_or_default(),
                 )
             } else {
                 (
-                    Color::LightGreen,
+                    Color::from_str(settings.other_border_color.trim())
+                        .unwrap_or(Color::LightGreen),
                     BorderType::from_str(&settings.other_border_type).unwrap_or_default(),
                 )
             };
@@ -519,20 +1310,26 @@ impl arcane_core::Plugin for WindowPlugin {
                 .border_type(border_type);
 
             let block = if borders.contains(Borders::TOP) {
-                block.title_top(window.name())
+                let block = block.title_top(window.name());
+                match window.title_right() {
+                    Some(title) => block.title_top(Line::from(title).right_aligned()),
+                    None => block,
+                }
             } else {
                 block
             };
 
-            let Some(area) = layout.get(position) else {
-                continue;
-            };
             let inner_area = block.inner(*area);
             frame.render_widget(Clear, *area);
             frame.render_widget(block, *area);
             frame.render_widget(Clear, inner_area);
             window.draw(frame, inner_area, plugins);
         }
+        self.click_targets.set(click_targets);
+    }
+
+    fn plugin_name(&self) -> &'static str {
+        "Windows"
     }
 }
 
@@ -542,10 +1339,20 @@ mod tests {
     use std::cell::RefCell;
     use std::rc::Rc;
 
-    use arcane_core::{Plugin, StateManager};
+    use arcane_core::{Plugin, PluginStore, StateManager};
+    use arcane_settings::SettingsPlugin;
+    use crossterm::event::MouseEvent;
+    use ratatui::backend::TestBackend;
+    use ratatui::layout::Rect;
+    use ratatui::Terminal;
 
     use super::*;
 
+    /// A window plugin that does not touch the real config directory
+    fn plugin() -> WindowPlugin {
+        WindowPlugin::with_persistence(Persistence::Memory)
+    }
+
     #[derive(Clone)]
     struct TestWindow {
         update_calls: Rc<RefCell<u8>>,
@@ -576,8 +1383,8 @@ mod tests {
 
     #[test]
     fn delete_on_empty() {
-        let mut states = StateManager::new();
-        states.plugins.insert(WindowPlugin::new());
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
         states.on_load().unwrap();
 
         states.events.dispatch(WindowEvent::CloseWindow(0));
@@ -587,8 +1394,8 @@ mod tests {
 
     #[test]
     fn create_window() {
-        let mut states = StateManager::new();
-        states.plugins.insert(WindowPlugin::new());
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
         states.on_load().unwrap();
 
         let update_calls = Rc::new(RefCell::new(0));
@@ -608,8 +1415,8 @@ mod tests {
 
     #[test]
     fn destroy_window() {
-        let mut states = StateManager::new();
-        states.plugins.insert(WindowPlugin::new());
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
         states.on_load().unwrap();
 
         let update_calls = Rc::new(RefCell::new(0));
@@ -635,8 +1442,8 @@ mod tests {
 
     #[test]
     fn inserting_over_cap() {
-        let mut states = StateManager::new();
-        states.plugins.insert(WindowPlugin::new());
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
         states.on_load().unwrap();
 
         for _ in 0..=256 {
@@ -652,8 +1459,8 @@ mod tests {
 
     #[test]
     fn overflow_id_but_with_gaps() {
-        let mut states = StateManager::new();
-        states.plugins.insert(WindowPlugin::new());
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
         states.on_load().unwrap();
 
         for _ in 0..200 {
@@ -682,4 +1489,1064 @@ mod tests {
         states.events.swap_buffers();
         assert!(states.update().is_ok());
     }
+
+    #[test]
+    fn move_window_to_tab() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        for _ in 0..2 {
+            states
+                .events
+                .dispatch(WindowEvent::CreateWindow(Box::new(TestWindow {
+                    update_calls: Rc::default(),
+                })));
+        }
+        states.events.swap_buffers();
+        states.update().unwrap();
+
+        states
+            .events
+            .dispatch(WindowEvent::MoveWindowToTab { window: 0, tab: 1 });
+        states.events.swap_buffers();
+        states.update().unwrap();
+        assert_eq!(
+            states.plugins.get::<WindowPlugin>().unwrap().tabs,
+            vec![vec![1], vec![0]]
+        );
+
+        states
+            .events
+            .dispatch(WindowEvent::MoveWindowToTab { window: 1, tab: 1 });
+        states.events.swap_buffers();
+        states.update().unwrap();
+        let plugin = states.plugins.get::<WindowPlugin>().unwrap();
+        assert_eq!(plugin.tabs, vec![vec![], vec![0, 1]]);
+        assert_eq!(plugin.focused_window, 0);
+    }
+
+    #[derive(Clone)]
+    struct NamedWindow(&'static str);
+    impl Window for NamedWindow {
+        fn name(&self) -> String {
+            String::from(self.0)
+        }
+        fn draw(
+            &self,
+            frame: &mut ratatui::Frame,
+            area: ratatui::prelude::Rect,
+            _plugins: &arcane_core::PluginStore,
+        ) {
+            frame.render_widget(self.0, area);
+        }
+    }
+
+    fn send_window_events(
+        states: &mut StateManager,
+        events: impl IntoIterator<Item = WindowEvent>,
+    ) {
+        for event in events {
+            states.events.dispatch(event);
+        }
+        states.events.swap_buffers();
+        states.update().unwrap();
+    }
+
+    fn send_ui_event(states: &mut StateManager, event: WindowUiEvent) {
+        states.events.dispatch(event);
+        states.events.swap_buffers();
+        states.update().unwrap();
+    }
+
+    /// A window recording when it is removed
+    #[derive(Clone)]
+    struct RemovedWindow {
+        /// The name of the window
+        name: &'static str,
+        /// The names of the removed windows
+        removed: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl Window for RemovedWindow {
+        fn name(&self) -> String {
+            String::from(self.name)
+        }
+
+        fn draw(
+            &self,
+            _frame: &mut ratatui::Frame,
+            _area: ratatui::prelude::Rect,
+            _plugins: &arcane_core::PluginStore,
+        ) {
+        }
+
+        fn on_remove(
+            &mut self,
+            _events: &arcane_core::EventManager,
+            _plugins: &arcane_core::PluginStore,
+        ) -> arcane_core::Result<()> {
+            self.removed.borrow_mut().push(self.name);
+            Ok(())
+        }
+    }
+
+    /// Open windows "a" and "b" in the first tab, and "c" and "d" in the second, focusing "c"
+    fn two_tabs(states: &mut StateManager) -> Rc<RefCell<Vec<&'static str>>> {
+        let removed = Rc::new(RefCell::new(Vec::new()));
+        let window = |name| {
+            WindowEvent::CreateWindow(Box::new(RemovedWindow {
+                name,
+                removed: Rc::clone(&removed),
+            }))
+        };
+
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+        send_window_events(states, [window("a"), window("b")]);
+        send_ui_event(states, WindowUiEvent::NewTab);
+        send_window_events(states, [window("c"), window("d")]);
+        send_ui_event(states, WindowUiEvent::FocusLeft);
+        removed
+    }
+
+    #[test]
+    fn close_others() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        let removed = two_tabs(&mut states);
+
+        send_ui_event(&mut states, WindowUiEvent::CloseOthers);
+        states.events.swap_buffers();
+        states.update().unwrap();
+
+        removed.borrow_mut().sort_unstable();
+        assert_eq!(*removed.borrow(), ["a", "b", "d"]);
+        let plugin = states.plugins.get::<WindowPlugin>().unwrap();
+        assert_eq!(plugin.focused_window_name().as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn close_all() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        let removed = two_tabs(&mut states);
+
+        send_ui_event(&mut states, WindowUiEvent::CloseAll);
+        states.events.swap_buffers();
+        states.update().unwrap();
+
+        removed.borrow_mut().sort_unstable();
+        assert_eq!(*removed.borrow(), ["a", "b", "c", "d"]);
+        let plugin = states.plugins.get::<WindowPlugin>().unwrap();
+        assert!(plugin.windows.is_empty());
+        assert!(plugin.tabs.iter().all(Vec::is_empty));
+    }
+
+    #[test]
+    fn focused_window() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [
+                WindowEvent::CreateWindow(Box::new(NamedWindow("a"))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("b"))),
+            ],
+        );
+        send_ui_event(&mut states, WindowUiEvent::NewTab);
+        {
+            let plugin = states.plugins.get::<WindowPlugin>().unwrap();
+            assert_eq!(plugin.focused_window_id(), None);
+            assert_eq!(plugin.focused_window_name(), None);
+        }
+
+        send_window_events(
+            &mut states,
+            [WindowEvent::CreateWindow(Box::new(NamedWindow("c")))],
+        );
+        {
+            let plugin = states.plugins.get::<WindowPlugin>().unwrap();
+            assert_eq!(plugin.focused_window_id(), Some(2));
+            assert_eq!(plugin.focused_window_name().as_deref(), Some("c"));
+        }
+
+        send_ui_event(&mut states, WindowUiEvent::PreviousTab);
+        send_ui_event(&mut states, WindowUiEvent::FocusRight);
+        let plugin = states.plugins.get::<WindowPlugin>().unwrap();
+        assert_eq!(plugin.focused_window_id(), Some(1));
+        assert_eq!(plugin.focused_window_name().as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn fill_gaps_keeps_tabs() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [
+                WindowEvent::CreateWindow(Box::new(NamedWindow("a"))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("b"))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("c"))),
+            ],
+        );
+        send_ui_event(&mut states, WindowUiEvent::NewTab);
+        send_window_events(
+            &mut states,
+            [
+                WindowEvent::CreateWindow(Box::new(NamedWindow("d"))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("e"))),
+            ],
+        );
+        send_window_events(
+            &mut states,
+            [WindowEvent::CloseWindow(1), WindowEvent::CloseWindow(3)],
+        );
+
+        let mut plugin = states.plugins.get_mut::<WindowPlugin>().unwrap();
+        plugin.zoomed = Some(4);
+        plugin.fill_gaps().unwrap();
+
+        let names = plugin
+            .tabs
+            .iter()
+            .map(|tab| {
+                tab.iter()
+                    .map(|id| plugin.windows[id].name())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, [vec!["a", "c"], vec!["e"]]);
+        assert_eq!(plugin.tabs, [vec![0, 1], vec![2]]);
+        assert_eq!(plugin.zoomed, Some(2));
+        assert_eq!(plugin.next_free, 3);
+    }
+
+    #[test]
+    fn stale_handle_rejected() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [
+                WindowEvent::CreateWindow(Box::new(NamedWindow("a"))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("b"))),
+            ],
+        );
+        let stale = states
+            .plugins
+            .get::<WindowPlugin>()
+            .unwrap()
+            .handle(1)
+            .unwrap();
+
+        send_window_events(&mut states, [WindowEvent::CloseWindow(0)]);
+        states
+            .plugins
+            .get_mut::<WindowPlugin>()
+            .unwrap()
+            .fill_gaps()
+            .unwrap();
+        send_window_events(
+            &mut states,
+            [WindowEvent::CreateWindow(Box::new(NamedWindow("c")))],
+        );
+        {
+            let plugin = states.plugins.get::<WindowPlugin>().unwrap();
+            assert!(!plugin.is_valid(stale));
+            assert_eq!(plugin.windows[&1].name(), "c");
+        }
+
+        send_window_events(&mut states, [WindowEvent::CloseWindowHandle(stale)]);
+        let fresh = states
+            .plugins
+            .get::<WindowPlugin>()
+            .unwrap()
+            .handle(1)
+            .unwrap();
+        assert!(states
+            .plugins
+            .get::<WindowPlugin>()
+            .unwrap()
+            .windows
+            .contains_key(&1));
+
+        send_window_events(&mut states, [WindowEvent::CloseWindowHandle(fresh)]);
+        let plugin = states.plugins.get::<WindowPlugin>().unwrap();
+        assert!(!plugin.windows.contains_key(&1));
+        assert_eq!(plugin.handle(1), None);
+    }
+
+    #[derive(Clone)]
+    struct AreaWindow {
+        area: Rc<RefCell<Rect>>,
+        min_width: Option<u16>,
+    }
+    impl Window for AreaWindow {
+        fn name(&self) -> String {
+            String::from("Area")
+        }
+        fn min_width(&self) -> Option<u16> {
+            self.min_width
+        }
+        fn draw(
+            &self,
+            _frame: &mut ratatui::Frame,
+            area: ratatui::prelude::Rect,
+            _plugins: &arcane_core::PluginStore,
+        ) {
+            *self.area.borrow_mut() = area;
+        }
+    }
+
+    /// Draw two windows side by side and return the areas they were given
+    fn draw_areas(settings: WindowSettings) -> [Rect; 2] {
+        draw_windows(settings, [None, None], 40).0
+    }
+
+    /// Draw two windows with the given minimum widths, returning the areas they were drawn in and
+    /// the rendered screen
+    fn draw_windows(
+        settings: WindowSettings,
+        min_widths: [Option<u16>; 2],
+        width: u16,
+    ) -> ([Rect; 2], String) {
+        let (areas, buffer) = draw_buffer(settings, min_widths, width);
+        let screen = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        (areas, screen)
+    }
+
+    /// Draw two windows like `draw_windows`, returning the rendered buffer
+    fn draw_buffer(
+        settings: WindowSettings,
+        min_widths: [Option<u16>; 2],
+        width: u16,
+    ) -> ([Rect; 2], ratatui::buffer::Buffer) {
+        let plugins = PluginStore::new();
+        plugins
+            .get_mut::<SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(settings));
+
+        let areas = [Rc::default(), Rc::default()];
+        let mut plugin = plugin();
+        for (id, (area, min_width)) in areas.iter().zip(min_widths).enumerate() {
+            let id = id as WindowID;
+            plugin.windows.insert(
+                id,
+                Box::new(AreaWindow {
+                    area: Rc::clone(area),
+                    min_width,
+                }),
+            );
+            plugin.tabs[0].push(id);
+        }
+
+        let mut terminal = Terminal::new(TestBackend::new(width, 20)).unwrap();
+        terminal
+            .draw(|frame| plugin.draw(frame, frame.area(), &plugins))
+            .unwrap();
+
+        (
+            areas.map(|area| *area.borrow()),
+            terminal.backend().buffer().clone(),
+        )
+    }
+
+    /// A window filling the whole screen with its color
+    #[derive(Clone)]
+    struct LayeredWindow {
+        /// The color to fill with
+        color: Color,
+        /// The z index of the window
+        z_index: u32,
+    }
+
+    impl Window for LayeredWindow {
+        fn name(&self) -> String {
+            String::from("Layered")
+        }
+
+        fn z_index(&self) -> u32 {
+            self.z_index
+        }
+
+        fn draw(
+            &self,
+            frame: &mut ratatui::Frame,
+            _area: ratatui::prelude::Rect,
+            _plugins: &arcane_core::PluginStore,
+        ) {
+            frame.render_widget(Block::new().bg(self.color), frame.area());
+        }
+    }
+
+    #[test]
+    fn higher_z_drawn_last() {
+        let plugins = PluginStore::new();
+        plugins
+            .get_mut::<SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(WindowSettings::default()));
+
+        let mut plugin = plugin();
+        let windows = [
+            LayeredWindow {
+                color: Color::Red,
+                z_index: 1,
+            },
+            LayeredWindow {
+                color: Color::Blue,
+                z_index: 0,
+            },
+        ];
+        for (id, window) in windows.into_iter().enumerate() {
+            let id = id as WindowID;
+            plugin.windows.insert(id, Box::new(window));
+            plugin.tabs[0].push(id);
+        }
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+        terminal
+            .draw(|frame| plugin.draw(frame, frame.area(), &plugins))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        assert_eq!(buffer[(5, 5)].bg, Color::Red);
+        assert_eq!(buffer[(35, 5)].bg, Color::Red);
+    }
+
+    #[test]
+    fn border_colors() {
+        let (_, buffer) = draw_buffer(WindowSettings::default(), [None, None], 40);
+        assert_eq!(buffer[(0, 0)].fg, Color::LightYellow);
+        assert_eq!(buffer[(20, 0)].fg, Color::LightGreen);
+
+        let (_, buffer) = draw_buffer(
+            WindowSettings {
+                focus_border_color: String::from("Magenta"),
+                other_border_color: String::from("#102030"),
+                ..WindowSettings::default()
+            },
+            [None, None],
+            40,
+        );
+        assert_eq!(buffer[(0, 0)].fg, Color::Magenta);
+        assert_eq!(buffer[(20, 0)].fg, Color::Rgb(0x10, 0x20, 0x30));
+
+        let (_, buffer) = draw_buffer(
+            WindowSettings {
+                focus_border_color: String::from("not a color"),
+                ..WindowSettings::default()
+            },
+            [None, None],
+            40,
+        );
+        assert_eq!(buffer[(0, 0)].fg, Color::LightYellow);
+    }
+
+    #[test]
+    fn layout_direction() {
+        let [left, right] = draw_areas(WindowSettings::default());
+        assert_eq!(left.y, right.y);
+        assert!(left.x < right.x);
+
+        let [top, bottom] = draw_areas(WindowSettings {
+            layout_direction: String::from("Vertical"),
+            ..WindowSettings::default()
+        });
+        assert_eq!(top.x, bottom.x);
+        assert!(top.y < bottom.y);
+    }
+
+    #[test]
+    fn tab_bar_position() {
+        let ([top, _], screen) = draw_windows(
+            WindowSettings {
+                always_show_tab_bar: true,
+                ..WindowSettings::default()
+            },
+            [None, None],
+            40,
+        );
+        assert!(screen.starts_with(" > "), "{screen}");
+
+        let ([bottom, _], screen) = draw_windows(
+            WindowSettings {
+                always_show_tab_bar: true,
+                tab_bar_position: String::from("Bottom"),
+                ..WindowSettings::default()
+            },
+            [None, None],
+            40,
+        );
+        let last_row = screen.chars().skip(40 * 19).collect::<String>();
+        assert!(last_row.starts_with(" > "), "{screen}");
+        assert_eq!(bottom.y.saturating_add(1), top.y);
+        assert_eq!(bottom.height, top.height);
+    }
+
+    #[test]
+    fn min_width_collapses() {
+        let ([focused, collapsed], screen) =
+            draw_windows(WindowSettings::default(), [Some(15), Some(15)], 20);
+        assert_ne!(focused, Rect::default());
+        assert_eq!(collapsed, Rect::default());
+        assert!(screen.contains('▶'));
+
+        let ([first, second], screen) =
+            draw_windows(WindowSettings::default(), [None, Some(5)], 20);
+        assert_ne!(first, Rect::default());
+        assert_ne!(second, Rect::default());
+        assert!(!screen.contains('▶'));
+    }
+
+    fn render_screen(states: &StateManager) -> String {
+        let plugin = states.plugins.get::<WindowPlugin>().unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+        terminal
+            .draw(|frame| plugin.draw(frame, frame.area(), &states.plugins))
+            .unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn zoom() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [
+                WindowEvent::CreateWindow(Box::new(NamedWindow("alpha"))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("bravo"))),
+            ],
+        );
+        let screen = render_screen(&states);
+        assert!(screen.contains("alpha"));
+        assert!(screen.contains("bravo"));
+
+        send_ui_event(&mut states, WindowUiEvent::ToggleZoom);
+        let screen = render_screen(&states);
+        assert!(!screen.contains("alpha"));
+        assert!(screen.contains("bravo"));
+
+        send_ui_event(&mut states, WindowUiEvent::ToggleZoom);
+        let screen = render_screen(&states);
+        assert!(screen.contains("alpha"));
+        assert_eq!(
+            states
+                .plugins
+                .get::<WindowPlugin>()
+                .unwrap()
+                .focused_window_id(),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn zoom_keeps_tab_bar() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [
+                WindowEvent::CreateWindow(Box::new(NamedWindow("alpha"))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("bravo"))),
+            ],
+        );
+        send_ui_event(&mut states, WindowUiEvent::NewTab);
+        send_window_events(
+            &mut states,
+            [WindowEvent::CreateWindow(Box::new(NamedWindow("charlie")))],
+        );
+        send_ui_event(&mut states, WindowUiEvent::PreviousTab);
+        send_ui_event(&mut states, WindowUiEvent::ToggleZoom);
+
+        let screen = render_screen(&states);
+        let (tab_bar, windows) = screen.split_at(40);
+        assert!(tab_bar.starts_with(" > "), "{screen}");
+        assert!(windows.contains("alpha"), "{screen}");
+        assert!(!windows.contains("bravo"), "{screen}");
+    }
+
+    #[test]
+    fn closure_window() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        let window = ClosureWindow::new("scratch", |frame, area, _plugins| {
+            frame.render_widget(Paragraph::new("drawn by closure"), area);
+        });
+        send_window_events(
+            &mut states,
+            [
+                WindowEvent::CreateWindow(Box::new(window.clone())),
+                WindowEvent::CreateWindow(Box::new(window)),
+            ],
+        );
+
+        let screen = render_screen(&states);
+        assert!(screen.contains("scratch"));
+        assert_eq!(screen.matches("drawn by closure").count(), 2);
+    }
+
+    #[derive(Clone)]
+    struct SubtitleWindow;
+    impl Window for SubtitleWindow {
+        fn name(&self) -> String {
+            String::from("main")
+        }
+        fn title_right(&self) -> Option<String> {
+            Some(String::from("3 items"))
+        }
+        fn draw(
+            &self,
+            _frame: &mut ratatui::Frame,
+            _area: ratatui::prelude::Rect,
+            _plugins: &arcane_core::PluginStore,
+        ) {
+        }
+    }
+
+    #[test]
+    fn title_right() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [WindowEvent::CreateWindow(Box::new(SubtitleWindow))],
+        );
+        let screen = render_screen(&states);
+        assert!(!screen.contains("3 items"));
+
+        send_window_events(
+            &mut states,
+            [WindowEvent::CreateWindow(Box::new(NamedWindow("other")))],
+        );
+        let screen = render_screen(&states);
+        let first_line = screen.chars().take(40).collect::<String>();
+        assert!(first_line.contains("main"));
+        assert!(first_line.find("main") < first_line.find("3 items"));
+    }
+
+    #[derive(Clone)]
+    struct CounterWindow(u32);
+    impl Window for CounterWindow {
+        fn name(&self) -> String {
+            format!("Counter {}", self.0)
+        }
+        fn draw(
+            &self,
+            _frame: &mut ratatui::Frame,
+            _area: ratatui::prelude::Rect,
+            _plugins: &arcane_core::PluginStore,
+        ) {
+        }
+        fn persist(&self) -> Option<PersistedWindow> {
+            Some(PersistedWindow {
+                tag: String::from("test_counter"),
+                state: serde_json::json!(self.0),
+            })
+        }
+    }
+
+    fn restore_counter(state: serde_json::Value) -> Option<Box<dyn Window>> {
+        Some(Box::new(CounterWindow(serde_json::from_value(state).ok()?)))
+    }
+
+    crate::register_window!("test_counter", restore_counter);
+
+    #[test]
+    fn persist_layout() {
+        let config_dir = std::env::temp_dir().join("arcane_windows_persist_layout");
+        let _ = std::fs::remove_dir_all(&config_dir);
+
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states
+            .plugins
+            .insert(WindowPlugin::with_persistence(Persistence::Disk(
+                config_dir.clone(),
+            )));
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [
+                WindowEvent::CreateWindow(Box::new(CounterWindow(1))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("not persisted"))),
+                WindowEvent::CreateWindow(Box::new(CounterWindow(2))),
+            ],
+        );
+        send_ui_event(&mut states, WindowUiEvent::NewTab);
+        send_window_events(
+            &mut states,
+            [WindowEvent::CreateWindow(Box::new(CounterWindow(3)))],
+        );
+
+        assert!(!config_dir.join("windows.json.tmp").exists());
+        let mut restored = WindowPlugin::with_persistence(Persistence::Disk(config_dir.clone()));
+        restored
+            .on_load(&mut arcane_core::EventManager::new())
+            .unwrap();
+
+        let names = restored
+            .tabs
+            .iter()
+            .map(|tab| {
+                tab.iter()
+                    .map(|id| restored.windows[id].name())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![vec!["Counter 1", "Counter 2"], vec!["Counter 3"]]
+        );
+        assert_eq!(restored.focused_tab, 1);
+        assert_eq!(restored.focused_window_name().as_deref(), Some("Counter 3"));
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_layout_ignored() {
+        let config_dir = std::env::temp_dir().join("arcane_windows_invalid_layout");
+        let _ = std::fs::remove_dir_all(&config_dir);
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(config_dir.join("windows.json"), "{ not json").unwrap();
+
+        let mut plugin = WindowPlugin::with_persistence(Persistence::Disk(config_dir.clone()));
+        plugin
+            .on_load(&mut arcane_core::EventManager::new())
+            .unwrap();
+        assert_eq!(plugin.tabs, [Vec::<WindowID>::new()]);
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[test]
+    fn focus_window() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [
+                WindowEvent::CreateWindow(Box::new(NamedWindow("a"))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("b"))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("c"))),
+            ],
+        );
+        assert_eq!(
+            states.plugins.get::<WindowPlugin>().unwrap().focused_window,
+            2
+        );
+
+        send_window_events(&mut states, [WindowEvent::FocusWindow(1)]);
+        assert_eq!(
+            states.plugins.get::<WindowPlugin>().unwrap().focused_window,
+            1
+        );
+
+        send_ui_event(&mut states, WindowUiEvent::NewTab);
+        send_window_events(&mut states, [WindowEvent::FocusWindow(0)]);
+        let plugin = states.plugins.get::<WindowPlugin>().unwrap();
+        assert_eq!(plugin.focused_tab, 0);
+        assert_eq!(plugin.focused_window, 0);
+        drop(plugin);
+
+        send_window_events(&mut states, [WindowEvent::FocusWindow(42)]);
+        let plugin = states.plugins.get::<WindowPlugin>().unwrap();
+        assert_eq!(plugin.focused_tab, 0);
+        assert_eq!(plugin.focused_window, 0);
+    }
+
+    fn click(states: &mut StateManager, column: u16, row: u16) {
+        states.events.dispatch(arcane_core::MouseEvent(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }));
+        states.events.swap_buffers();
+        states.update().unwrap();
+    }
+
+    #[test]
+    fn click_to_focus() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [
+                WindowEvent::CreateWindow(Box::new(NamedWindow("a"))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("b"))),
+            ],
+        );
+        render_screen(&states);
+        click(&mut states, 5, 5);
+        assert_eq!(
+            states.plugins.get::<WindowPlugin>().unwrap().focused_window,
+            0
+        );
+
+        render_screen(&states);
+        click(&mut states, 35, 5);
+        assert_eq!(
+            states.plugins.get::<WindowPlugin>().unwrap().focused_window,
+            1
+        );
+    }
+
+    #[test]
+    fn click_tab() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [WindowEvent::CreateWindow(Box::new(NamedWindow("a")))],
+        );
+        send_ui_event(&mut states, WindowUiEvent::NewTab);
+        assert_eq!(states.plugins.get::<WindowPlugin>().unwrap().focused_tab, 1);
+
+        // The tab bar reads " 1 | > 0 "
+        render_screen(&states);
+        click(&mut states, 1, 0);
+        assert_eq!(states.plugins.get::<WindowPlugin>().unwrap().focused_tab, 0);
+    }
+
+    #[test]
+    fn focus_clamped_mid_update() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [
+                WindowEvent::CreateWindow(Box::new(NamedWindow("a"))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("b"))),
+            ],
+        );
+        send_ui_event(&mut states, WindowUiEvent::FocusLeft);
+        for _ in 0..10 {
+            states.events.dispatch(WindowUiEvent::FocusRight);
+        }
+        // Would be a no-op if focus pointed past the last window
+        states.events.dispatch(WindowUiEvent::MoveLeft);
+        states.events.swap_buffers();
+        states.update().unwrap();
+
+        let plugin = states.plugins.get::<WindowPlugin>().unwrap();
+        assert_eq!(plugin.focused_window, 0);
+        assert_eq!(plugin.focused_window_name().as_deref(), Some("b"));
+    }
+
+    /// The focused window index after sending `event` with the focus on the first or last window
+    fn focus_after(wrap_focus: bool, start_last: bool, event: WindowUiEvent) -> usize {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states
+            .plugins
+            .get_mut::<SettingsPlugin>()
+            .unwrap()
+            .settings
+            .insert_raw(Box::new(WindowSettings {
+                wrap_focus,
+                ..WindowSettings::default()
+            }));
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [
+                WindowEvent::CreateWindow(Box::new(NamedWindow("a"))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("b"))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("c"))),
+            ],
+        );
+        if !start_last {
+            states
+                .plugins
+                .get_mut::<WindowPlugin>()
+                .unwrap()
+                .focused_window = 0;
+        }
+        send_ui_event(&mut states, event);
+
+        let focused = states.plugins.get::<WindowPlugin>().unwrap().focused_window;
+        focused
+    }
+
+    #[test]
+    fn wrap_focus() {
+        assert_eq!(focus_after(true, true, WindowUiEvent::FocusRight), 0);
+        assert_eq!(focus_after(true, false, WindowUiEvent::FocusLeft), 2);
+        assert_eq!(focus_after(true, false, WindowUiEvent::FocusRight), 1);
+        assert_eq!(focus_after(true, true, WindowUiEvent::FocusLeft), 1);
+    }
+
+    #[test]
+    fn saturate_focus() {
+        assert_eq!(focus_after(false, true, WindowUiEvent::FocusRight), 2);
+        assert_eq!(focus_after(false, false, WindowUiEvent::FocusLeft), 0);
+    }
+
+    #[test]
+    fn focus_changed_event() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [WindowEvent::CreateWindow(Box::new(NamedWindow("a")))],
+        );
+        states.events.swap_buffers();
+        assert_eq!(
+            states.events.read::<WindowFocusChanged>(),
+            &[WindowFocusChanged {
+                from: None,
+                to: Some(0)
+            }]
+        );
+
+        send_window_events(
+            &mut states,
+            [WindowEvent::CreateWindow(Box::new(NamedWindow("b")))],
+        );
+        for _ in 0..3 {
+            states.events.swap_buffers();
+            states.update().unwrap();
+        }
+        states.events.swap_buffers();
+        assert!(states.events.read::<WindowFocusChanged>().is_empty());
+
+        send_ui_event(&mut states, WindowUiEvent::FocusLeft);
+        send_ui_event(&mut states, WindowUiEvent::FocusLeft);
+        states.events.swap_buffers();
+        assert!(states.events.read::<WindowFocusChanged>().is_empty());
+
+        send_ui_event(&mut states, WindowUiEvent::FocusRight);
+        states.events.swap_buffers();
+        assert_eq!(
+            states.events.read::<WindowFocusChanged>(),
+            &[WindowFocusChanged {
+                from: Some(0),
+                to: Some(1)
+            }]
+        );
+    }
+
+    #[derive(Clone)]
+    struct TypingWindow;
+    impl Window for TypingWindow {
+        fn name(&self) -> String {
+            String::from("typing")
+        }
+        fn wants_keyboard(&self) -> bool {
+            true
+        }
+        fn draw(
+            &self,
+            _frame: &mut ratatui::Frame,
+            _area: ratatui::prelude::Rect,
+            _plugins: &arcane_core::PluginStore,
+        ) {
+        }
+    }
+
+    #[test]
+    fn wants_keyboard_locks_keybinds() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [
+                WindowEvent::CreateWindow(Box::new(NamedWindow("a"))),
+                WindowEvent::CreateWindow(Box::new(TypingWindow)),
+            ],
+        );
+        states.events.swap_buffers();
+        assert_eq!(
+            states
+                .events
+                .read::<LockKeybindings>()
+                .iter()
+                .map(|lock| lock.locked)
+                .collect::<Vec<_>>(),
+            [true]
+        );
+
+        send_window_events(&mut states, [WindowEvent::FocusWindow(0)]);
+        states.events.swap_buffers();
+        assert_eq!(
+            states
+                .events
+                .read::<LockKeybindings>()
+                .iter()
+                .map(|lock| lock.locked)
+                .collect::<Vec<_>>(),
+            [false]
+        );
+    }
+
+    #[test]
+    fn rename_tab() {
+        let mut states = StateManager::with_persistence(Persistence::Memory);
+        states.plugins.insert(plugin());
+        states.on_load().unwrap();
+
+        send_window_events(
+            &mut states,
+            [
+                WindowEvent::CreateWindow(Box::new(NamedWindow("a"))),
+                WindowEvent::CreateWindow(Box::new(NamedWindow("b"))),
+            ],
+        );
+        send_ui_event(&mut states, WindowUiEvent::NewTab);
+        send_ui_event(&mut states, WindowUiEvent::PreviousTab);
+        let screen = render_screen(&states);
+        assert!(screen.starts_with(" > 2 "), "{screen}");
+
+        send_ui_event(&mut states, WindowUiEvent::RenameTab);
+        for key in "docs".chars().map(KeyCode::Char).chain([KeyCode::Enter]) {
+            states
+                .events
+                .dispatch(arcane_core::KeydownEvent(crossterm::event::KeyEvent::new(
+                    key,
+                    KeyModifiers::NONE,
+                )));
+        }
+        states.events.swap_buffers();
+        states.update().unwrap();
+
+        let screen = render_screen(&states);
+        assert!(screen.starts_with(" > docs "), "{screen}");
+    }
 }
@@ -0,0 +1,166 @@
+//! A focusable list shared by windows showing rows of items
+
+use arcane_keybindings::{MenuEvent, MENU_PAGE_SIZE};
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Text};
+
+/// Tracks the focused row of a list and which rows are scrolled into view.
+///
+/// The owner keeps the length up to date with `set_len`, the focus is always clamped to it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SelectableList {
+    /// The index of the focused row
+    focused: usize,
+    /// The number of rows
+    len: usize,
+}
+
+impl SelectableList {
+    /// The index of the focused row
+    pub fn focused(&self) -> usize {
+        self.focused
+    }
+
+    /// The number of rows
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Is the list empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Change the number of rows, moving the focus onto the last row if it is now past the end
+    pub fn set_len(&mut self, len: usize) {
+        self.len = len;
+        self.focus(self.focused);
+    }
+
+    /// Focus the row at `index`, clamped to the rows in the list
+    pub fn focus(&mut self, index: usize) {
+        self.focused = index.min(self.len.saturating_sub(1));
+    }
+
+    /// Move the focus for the navigation `MenuEvent`s, returning if the event was one of them
+    pub fn handle(&mut self, event: &MenuEvent) -> bool {
+        let index = match event {
+            MenuEvent::Down => self.focused.saturating_add(1),
+            MenuEvent::Up => self.focused.saturating_sub(1),
+            MenuEvent::Top => 0,
+            MenuEvent::Bottom => usize::MAX,
+            MenuEvent::PageDown => self.focused.saturating_add(MENU_PAGE_SIZE),
+            MenuEvent::PageUp => self.focused.saturating_sub(MENU_PAGE_SIZE),
+            _ => return false,
+        };
+        self.focus(index);
+        true
+    }
+
+    /// The first row shown when `visible` rows fit on screen.
+    ///
+    /// Keeps the focused row in the middle, without scrolling past the start or end of the list.
+    pub fn scroll(&self, visible: usize) -> usize {
+        let max_scroll = self.len.saturating_sub(visible);
+        self.focused.saturating_sub(visible / 2).min(max_scroll)
+    }
+
+    /// Draw the rows in view, with `highlight` applied to the focused one
+    pub fn render(
+        &self,
+        frame: &mut ratatui::Frame,
+        area: Rect,
+        lines: Vec<Line<'_>>,
+        highlight: Style,
+    ) {
+        let visible = usize::from(area.height);
+        let lines = lines
+            .into_iter()
+            .enumerate()
+            .skip(self.scroll(visible))
+            .take(visible)
+            .map(|(index, line)| {
+                if index == self.focused {
+                    line.patch_style(highlight)
+                } else {
+                    line
+                }
+            })
+            .collect::<Vec<_>>();
+        frame.render_widget(Text::from(lines), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arcane_keybindings::MenuEvent;
+    use ratatui::backend::TestBackend;
+    use ratatui::style::{Modifier, Style};
+    use ratatui::text::Line;
+    use ratatui::Terminal;
+
+    use super::SelectableList;
+
+    fn list(len: usize, focused: usize) -> SelectableList {
+        let mut list = SelectableList::default();
+        list.set_len(len);
+        list.focus(focused);
+        list
+    }
+
+    #[test]
+    fn scroll_centers_focus() {
+        assert_eq!(list(20, 10).scroll(5), 8);
+        assert_eq!(list(20, 11).scroll(6), 8);
+    }
+
+    #[test]
+    fn scroll_clamped() {
+        assert_eq!(list(20, 1).scroll(5), 0);
+        assert_eq!(list(20, 19).scroll(5), 15);
+        assert_eq!(list(3, 2).scroll(5), 0);
+        assert_eq!(list(0, 0).scroll(5), 0);
+    }
+
+    #[test]
+    fn focus_clamped() {
+        let mut list = list(5, 2);
+        assert!(list.handle(&MenuEvent::Bottom));
+        assert_eq!(list.focused(), 4);
+        assert!(list.handle(&MenuEvent::PageUp));
+        assert_eq!(list.focused(), 0);
+        assert!(list.handle(&MenuEvent::Up));
+        assert_eq!(list.focused(), 0);
+        assert!(!list.handle(&MenuEvent::Select));
+
+        list.focus(4);
+        list.set_len(2);
+        assert_eq!(list.focused(), 1);
+    }
+
+    #[test]
+    fn render_highlights_focused() {
+        let list = list(10, 6);
+        let mut terminal = Terminal::new(TestBackend::new(3, 3)).unwrap();
+        terminal
+            .draw(|frame| {
+                let lines = (0..10).map(|row| Line::from(row.to_string())).collect();
+                list.render(
+                    frame,
+                    frame.area(),
+                    lines,
+                    Style::new().add_modifier(Modifier::BOLD),
+                );
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let rows = (0..3)
+            .map(|y| buffer[(0, y)].symbol().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(rows, ["5", "6", "7"]);
+        assert!(buffer[(0, 1)].modifier.contains(Modifier::BOLD));
+        assert!(!buffer[(0, 0)].modifier.contains(Modifier::BOLD));
+    }
+}